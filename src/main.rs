@@ -1,7 +1,11 @@
-
 pub mod verse;
 fn main() {
-    
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if !args.is_empty() {
+        std::process::exit(verse::cli::run(&args));
+    }
+
  verse::tui::App::init().run();
 
 }
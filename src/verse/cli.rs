@@ -0,0 +1,975 @@
+use std::{collections::HashSet, time::Instant};
+
+use std::path::Path;
+
+use crate::verse::{
+    compiled,
+    crossref::CrossReferences,
+    date::Date,
+    db::{BibleBook, Database, Order, Testament, Verse},
+    export::{
+        self, Annotations,
+        study::{Format, Study},
+    },
+    import,
+    locale::Locale,
+    parse::{
+        Reference, parse_book, parse_books, parse_chapters, parse_reference, parse_reference_in,
+    },
+    plan::Plan,
+    remote, serve,
+    store::{PlanProgress, Store, data_dir},
+    strongs::{Lexicon, Strong},
+    tui::App,
+    versification::Versification,
+    votd::{self, Rng},
+};
+
+const USAGE: &str = "Usage: verse [--preload | command]
+
+Without a command the reader is opened. --preload reads the whole translation
+into memory first, for instant book switching.
+
+Commands:
+  plan list                     List the reading plans
+  plan start <name> [date]      Start a plan today or on a YYYY-MM-DD date
+  plan today                    Show today's passages of the plan
+  plan check <day> [passage]    Check off a passage, or the whole day
+  plan stop                     Stop following the plan
+  votd [--date <date>] [--any]  Verse of the day, from the whole Bible with --any
+  random [--book <book>] [--testament ot|nt]
+                                A random verse
+  serve [--bind <addr:port>]    Serve passages, search and more as JSON over HTTP,
+                                on 127.0.0.1:8080 by default
+  translations                  List the translations, their size and where they're read from
+  index [--translation <name>]  Read a translation into memory and report its size
+  compile <db> <out.vbin> [--translation <name>] [--zstd]
+                                Compile a translation of a SQLite DB into a .vbin
+                                file, read without SQLite when put next to verse
+  import <file> <out.vbin> [--translation <name>] [--zstd] [--dry-run]
+         [--versification <numbering>] [--language <code>]
+                                Import an OSIS, USFM, USX, Zefania, TheWord or
+                                scrollmapper CSV/JSON Bible into a .vbin file, <file>
+                                may be a directory with one file per book. --dry-run
+                                only reports what would be imported. Bibles numbered
+                                as the Hebrew, Septuagint or Vulgate need --versification,
+                                --language sets the language if the file doesn't
+  order [name]                  Show the book orders, or read the books in canon,
+                                protestant, tanakh, catholic or chronological order
+  language [code | auto]        Show the languages, or show book names and the reader
+                                in one, auto follows the translation's language
+  map <verse> [--from <numbering>]
+                                The verse in the English, Hebrew, Septuagint and
+                                Vulgate numbering, given in English by default
+  export epub <out.epub> [--books <first>[-<last>]] [--translation <name>] [--notes]
+                                Write the whole translation or some books as an EPUB
+                                e-book, with highlights and notes by --notes
+  export study <passage> [--org] [--out <file>] [--translation <name>] [--crossrefs <n>]
+                                Write a passage with your notes, highlights as tags and
+                                the n most voted cross references (5) as Markdown, or
+                                Org with --org, to stdout or the file
+  strongs <number> [--translation <name>]
+                                Strong's dictionary entry of a number like H7225 and
+                                the verses of a tagged translation with it
+  highlight <verses> [category] Highlight verses under a category like promise,
+                                without one the highlight is removed
+  note <verse> [text]           Note on a verse, without text the note is removed
+  remote <command>              Drive the running reader: goto <reference>,
+                                search <words>, translation [name], location
+                                or selection";
+
+//Runs a command line, returns the exit code
+pub fn run(args: &[String]) -> i32 {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["plan", rest @ ..] => plan(rest),
+        ["votd", rest @ ..] => verse_of_the_day(rest),
+        ["random", rest @ ..] => random(rest),
+        ["--preload"] => {
+            let mut app = App::init();
+            app.preload();
+            app.run();
+            Ok(())
+        }
+        ["serve", rest @ ..] => serve(rest),
+        ["translations"] => translations(),
+        ["index", rest @ ..] => index(rest),
+        #[cfg(feature = "sqlite")]
+        ["compile", db, out, rest @ ..] => compile(db, out, rest),
+        ["import", file, rest @ ..] => import(file, rest),
+        ["map", rest @ ..] => map(rest),
+        ["strongs", rest @ ..] => strongs(rest),
+        ["order", rest @ ..] => order(rest),
+        ["language", rest @ ..] => language(rest),
+        ["export", "epub", out, rest @ ..] => export_epub(out, rest),
+        ["export", "study", rest @ ..] => export_study(rest),
+        ["highlight", rest @ ..] => highlight(rest),
+        ["note", rest @ ..] => note(rest),
+        ["remote", command @ ..] if !command.is_empty() => remote(&command.join(" ")),
+        ["help" | "--help" | "-h"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("verse: {}", err);
+            1
+        }
+    }
+}
+
+fn plan(args: &[&str]) -> Result<(), String> {
+    let mut store = Store::load();
+
+    match args {
+        ["list"] => {
+            let active = store.plan.as_ref().map(|plan| plan.name.as_str());
+            for (name, title) in Plan::list() {
+                //canonical-tanakh is the canonical plan in another order
+                let marker = if active.is_some_and(|active| {
+                    active == name
+                        || active.strip_prefix("canonical-").is_some() && name == "canonical"
+                }) {
+                    "*"
+                } else {
+                    " "
+                };
+                println!("{} {:<16} {}", marker, name, title);
+            }
+            Ok(())
+        }
+        ["start", name, rest @ ..] => {
+            let start = match rest {
+                [] => Date::today(),
+                [date] => date
+                    .parse()
+                    .map_err(|_| format!("Bad date {}, expected YYYY-MM-DD", date))?,
+                _ => return Err("Too many arguments".to_string()),
+            };
+
+            let plan = Plan::find(&Plan::in_order(name, store.order))?;
+            store.plan = Some(PlanProgress {
+                name: plan.name.clone(),
+                start,
+                done: HashSet::new(),
+            });
+            save(&mut store)?;
+
+            println!(
+                "Started {} ({} days) on {}",
+                plan.title,
+                plan.days.len(),
+                start
+            );
+            Ok(())
+        }
+        ["today"] => {
+            let (plan, progress) = active_plan(&store)?;
+            let day = progress.day_on(Date::today());
+
+            if day < 1 {
+                println!("{} starts on {}", plan.title, progress.start);
+                return Ok(());
+            }
+
+            match plan.days.get(day as usize - 1) {
+                Some(passages) => {
+                    println!("{} - day {} of {}", plan.title, day, plan.days.len());
+                    for (i, passage) in passages.iter().enumerate() {
+                        let check = if progress.done.contains(&(day as u16, i as u8)) {
+                            "x"
+                        } else {
+                            " "
+                        };
+                        println!("  [{}] {}. {}", check, i + 1, passage);
+                    }
+                }
+                None => println!(
+                    "{} - finished on {}",
+                    plan.title,
+                    progress.start.add_days(plan.days.len() as i32 - 1)
+                ),
+            }
+
+            let behind = plan.behind(progress, day);
+            if let Some(first) = behind.first() {
+                println!(
+                    "{} day(s) behind, catch up from day {}",
+                    behind.len(),
+                    first
+                );
+            }
+            Ok(())
+        }
+        ["check", day, rest @ ..] => {
+            let (plan, _) = active_plan(&store)?;
+            let day: u16 = day
+                .parse()
+                .ok()
+                .filter(|day| *day >= 1 && *day as usize <= plan.days.len())
+                .ok_or_else(|| format!("Bad day {}", day))?;
+            let count = plan.days[day as usize - 1].len() as u8;
+
+            let passages: Vec<u8> = match rest {
+                [] => (0..count).collect(),
+                [passage] => vec![
+                    passage
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|passage| *passage >= 1 && *passage <= count)
+                        .ok_or_else(|| format!("Bad passage {}", passage))?
+                        - 1,
+                ],
+                _ => return Err("Too many arguments".to_string()),
+            };
+
+            let progress = store.plan.as_mut().expect("Active plan checked above");
+            progress
+                .done
+                .extend(passages.into_iter().map(|passage| (day, passage)));
+            save(&mut store)
+        }
+        ["stop"] => {
+            store.plan = None;
+            save(&mut store)
+        }
+        _ => Err(format!("Unknown plan command\n\n{}", USAGE)),
+    }
+}
+
+fn verse_of_the_day(args: &[&str]) -> Result<(), String> {
+    let mut date = Date::today();
+    let mut any = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--date" => {
+                let value = args.next().ok_or("--date needs a YYYY-MM-DD date")?;
+                date = value
+                    .parse()
+                    .map_err(|_| format!("Bad date {}, expected YYYY-MM-DD", value))?;
+            }
+            "--any" => any = true,
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let db = connect()?;
+    let verse = if any {
+        votd::random_of_the_day(&db, date)
+    } else {
+        votd::verse_of_the_day(&db, date)
+    }
+    .map_err(|err| format!("Couldn't read the verse: {}", err))?;
+
+    print_verse(&db, &verse);
+    Ok(())
+}
+
+fn random(args: &[&str]) -> Result<(), String> {
+    let mut book = None;
+    let mut testament = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--book" => {
+                let value = args.next().ok_or("--book needs a book name")?;
+                book = Some(parse_book(value).ok_or_else(|| format!("Unknown book {}", value))?);
+                testament = None;
+            }
+            "--testament" => {
+                testament = match args.next().map(|value| value.to_lowercase()).as_deref() {
+                    Some("ot" | "old") => Some(Testament::Old),
+                    Some("nt" | "new") => Some(Testament::New),
+                    _ => return Err("--testament needs ot or nt".to_string()),
+                };
+                book = None;
+            }
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let db = connect()?;
+    let mut rng = Rng::from_time();
+    //The translation's books of the testament, its deuterocanonical ones are in the Old
+    let verse = match (book, testament) {
+        (Some(book), _) => votd::random_verse(&db, book, book, &mut rng),
+        (None, Some(testament)) => {
+            let books: Vec<BibleBook> = db
+                .source()
+                .books(&db.translation)
+                .map_err(|err| format!("Couldn't read the books: {}", err))?
+                .into_iter()
+                .filter(|book| book.testament() == testament)
+                .collect();
+            votd::random_verse_in(&db, &books, &mut rng)
+        }
+        (None, None) => votd::random_verse(&db, BibleBook::Genesis, BibleBook::LAST, &mut rng),
+    }
+    .map_err(|err| format!("Couldn't read a verse: {}", err))?;
+
+    print_verse(&db, &verse);
+    Ok(())
+}
+
+fn serve(args: &[&str]) -> Result<(), String> {
+    let bind = match args {
+        [] => "127.0.0.1:8080",
+        ["--bind", bind] => bind,
+        _ => return Err(format!("Unknown serve options\n\n{}", USAGE)),
+    };
+
+    serve::serve(bind)
+}
+
+fn translations() -> Result<(), String> {
+    let db = connect()?;
+    let source = db.source();
+
+    for translation in db.translations().map_err(|err| err.to_string())? {
+        let metadata = source
+            .metadata(&translation)
+            .map_err(|err| format!("Couldn't read {}: {}", translation, err))?;
+        println!(
+            "{:<8} {:>2} books {:>6} verses  {:<10} {:<10} {:<5} {}",
+            metadata.translation,
+            metadata.books,
+            metadata.verses,
+            metadata.canon.as_str(),
+            metadata.versification.as_str(),
+            metadata.language.as_deref().unwrap_or("-"),
+            metadata.source
+        );
+    }
+    Ok(())
+}
+
+fn index(args: &[&str]) -> Result<(), String> {
+    let mut db = connect()?;
+    match args {
+        [] => {}
+        ["--translation", name] => {
+            db.translation = db
+                .find_translation(name)
+                .map_err(|err| err.to_string())?
+                .ok_or_else(|| format!("Unknown translation {}", name))?;
+        }
+        _ => return Err(format!("Unknown index options\n\n{}", USAGE)),
+    }
+
+    let started = Instant::now();
+    //Compiled translations are indexed already
+    let loaded;
+    let index = match db.index() {
+        Some(index) => index,
+        None => {
+            loaded = db
+                .load_index()
+                .map_err(|err| format!("Couldn't read {}: {}", db.translation, err))?;
+            &loaded
+        }
+    };
+
+    println!(
+        "{}: {} verses in {}, read in {}ms",
+        index.translation,
+        index.verse_count(),
+        index.memory_usage(),
+        started.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+fn compile(db: &str, out: &str, args: &[&str]) -> Result<(), String> {
+    let mut database = Database::open_sqlite(Path::new(db))
+        .map_err(|err| format!("Couldn't open {}: {}", db, err))?;
+    let mut compress = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--translation" => {
+                let name = args.next().ok_or("--translation needs a name")?;
+                database.translation = database
+                    .find_translation(name)
+                    .map_err(|err| err.to_string())?
+                    .ok_or_else(|| format!("Unknown translation {}", name))?;
+            }
+            "--zstd" => compress = true,
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let started = Instant::now();
+    let index = database
+        .load_index()
+        .map_err(|err| format!("Couldn't read {}: {}", database.translation, err))?;
+    let size = compiled::write(&index, Path::new(out), compress)
+        .map_err(|err| format!("Couldn't write {}: {}", out, err))?;
+
+    println!(
+        "{}: {} verses, {:.1} MiB written to {} in {}ms",
+        index.translation,
+        index.verse_count(),
+        size as f64 / (1024.0 * 1024.0),
+        out,
+        started.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+fn import(file: &str, args: &[&str]) -> Result<(), String> {
+    let mut out = None;
+    let mut name = None;
+    let mut compress = false;
+    let mut dry_run = false;
+    let mut versification = Versification::English;
+    let mut language = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--translation" => name = Some(args.next().ok_or("--translation needs a name")?),
+            "--zstd" => compress = true,
+            "--dry-run" => dry_run = true,
+            "--versification" => versification = numbering(args.next())?,
+            "--language" => {
+                let code = args.next().ok_or("--language needs a code like es")?;
+                language = Some(
+                    import::language_tag(code).ok_or_else(|| format!("Bad language {}", code))?,
+                );
+            }
+            _ if out.is_none() && !arg.starts_with("--") => out = Some(*arg),
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+    if out.is_none() && !dry_run {
+        return Err(format!("import needs a .vbin file to write\n\n{}", USAGE));
+    }
+
+    let mut import = import::read(Path::new(file))?;
+    import.versification = versification;
+    if language.is_some() {
+        import.language = language;
+    }
+    let translation = match name
+        .map(|name| name.to_string())
+        .or(import.translation.clone())
+    {
+        Some(translation) => translation,
+        //Nothing is written, so the name can wait
+        None if dry_run => "unnamed".to_string(),
+        None => {
+            return Err(
+                "The file doesn't name its translation, give one with --translation".to_string(),
+            );
+        }
+    };
+    if translation.is_empty()
+        || !translation
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_')
+    {
+        return Err(format!(
+            "Bad translation name {}, use letters, digits and _",
+            translation
+        ));
+    }
+
+    let flattened: Vec<String> = import
+        .flattened
+        .iter()
+        .map(|(feature, verses)| format!("{} in {} verses", feature, verses))
+        .collect();
+    let report = import.finish(&translation);
+    for warning in &report.warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    let written = match out {
+        Some(out) if !dry_run => {
+            compiled::write(&report.index, Path::new(out), compress)
+                .map_err(|err| format!("Couldn't write {}: {}", out, err))?;
+            format!("written to {}", out)
+        }
+        _ => "read, nothing written".to_string(),
+    };
+    println!(
+        "{}: {} books, {} verses {}, {} warnings",
+        translation,
+        report.books,
+        report.index.verse_count(),
+        written,
+        report.warnings.len()
+    );
+    if !flattened.is_empty() {
+        println!("Kept as plain text: {}", flattened.join(", "));
+    }
+    Ok(())
+}
+
+fn export_epub(out: &str, args: &[&str]) -> Result<(), String> {
+    let mut db = connect()?;
+    let mut range = None;
+    let mut annotations = Annotations::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--books" => {
+                let value = args
+                    .next()
+                    .ok_or("--books needs a book or a range of books")?;
+                range = Some(parse_books(value).ok_or_else(|| format!("Unknown books {}", value))?);
+            }
+            "--translation" => {
+                let name = args.next().ok_or("--translation needs a name")?;
+                db.translation = db
+                    .find_translation(name)
+                    .map_err(|err| err.to_string())?
+                    .ok_or_else(|| format!("Unknown translation {}", name))?;
+            }
+            "--notes" => annotations = Annotations::from_store(Store::load()),
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    //Ranges follow the translation's canon, Tobit-Sirach only means something in some
+    let canon = db.canon().map_err(|err| err.to_string())?;
+    let canon_books = canon.books();
+    let position = |book: BibleBook| {
+        canon_books
+            .iter()
+            .position(|b| *b == book)
+            .ok_or_else(|| format!("{} isn't in the {} canon", book.as_str(), canon.as_str()))
+    };
+    let (start, end) = match range {
+        Some((first, last)) => (position(first)?, position(last)?),
+        None => (0, canon_books.len() - 1),
+    };
+    if start > end {
+        return Err(format!(
+            "{} comes after {} in the {} canon",
+            canon_books[start].as_str(),
+            canon_books[end].as_str(),
+            canon.as_str()
+        ));
+    }
+    let books = &canon_books[start..=end];
+    let (first, last) = (books[0], books[books.len() - 1]);
+    let size = export::epub::write(&db, books, &annotations, Path::new(out))
+        .map_err(|err| format!("Couldn't write {}: {}", out, err))?;
+    println!(
+        "{}: {} to {} written to {}, {} KiB",
+        db.translation,
+        first.as_str(),
+        last.as_str(),
+        out,
+        size / 1024
+    );
+    Ok(())
+}
+
+fn export_study(args: &[&str]) -> Result<(), String> {
+    let mut db = connect()?;
+    let mut format = Format::Markdown;
+    let mut out = None;
+    let mut crossref_limit = 5;
+    let mut words = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--org" => format = Format::Org,
+            "--out" => out = Some(*args.next().ok_or("--out needs a file")?),
+            "--translation" => {
+                let name = args.next().ok_or("--translation needs a name")?;
+                db.translation = db
+                    .find_translation(name)
+                    .map_err(|err| err.to_string())?
+                    .ok_or_else(|| format!("Unknown translation {}", name))?;
+            }
+            "--crossrefs" => {
+                crossref_limit = args
+                    .next()
+                    .and_then(|count| count.parse().ok())
+                    .ok_or("--crossrefs needs a number")?;
+            }
+            _ if arg.starts_with("--") => {
+                return Err(format!("Unknown option {}\n\n{}", arg, USAGE));
+            }
+            _ => words.push(*arg),
+        }
+    }
+
+    //A range of verses in a chapter, or whole chapters, in the translation's numbering
+    let input = words.join(" ");
+    let versification = db.versification().map_err(|err| err.to_string())?;
+    let mut verses = vec![];
+    match parse_reference_in(&input, versification) {
+        Some(Reference {
+            book,
+            chapter,
+            verses: Some((first, last)),
+        }) => verses.extend(
+            db.fetch_range(book, chapter, first, last)
+                .map_err(|err| format!("Couldn't read {}: {}", input, err))?,
+        ),
+        _ => {
+            let (book, first, last) =
+                parse_chapters(&input).ok_or_else(|| format!("Bad passage {}", input))?;
+            //Numberings move verses by a chapter at most, the English ones around the
+            //chapters hold all their verses
+            for chapter in first.saturating_sub(1).max(1)..=(last + 1).min(book.max_chapter_count())
+            {
+                verses.extend(
+                    db.fetch_range(book, chapter, 1, u8::MAX)
+                        .map_err(|err| format!("Couldn't read {}: {}", input, err))?
+                        .into_iter()
+                        .filter(|verse| {
+                            let (native, chapter, _) =
+                                versification.from_english(verse.book, verse.chapter, verse.verse);
+                            native == book && (first..=last).contains(&chapter)
+                        }),
+                );
+            }
+        }
+    }
+    if verses.is_empty() {
+        return Err(format!("{} isn't in {}", input, db.translation));
+    }
+
+    let crossrefs = match crossref_limit {
+        0 => None,
+        _ => CrossReferences::load()
+            .inspect_err(|err| eprintln!("warning: cross references left out. {}", err))
+            .ok(),
+    };
+    let annotations = Annotations::from_store(Store::load());
+    let study = Study {
+        title: format!("{} ({})", input, db.translation),
+        annotations: &annotations,
+        crossrefs: crossrefs.as_ref(),
+        crossref_limit,
+        versification,
+    };
+    let document = study.render(format, &verses);
+
+    match out {
+        Some(out) => {
+            std::fs::write(out, document).map_err(|err| format!("Couldn't write {}: {}", out, err))
+        }
+        None => {
+            print!("{}", document);
+            Ok(())
+        }
+    }
+}
+
+fn highlight(args: &[&str]) -> Result<(), String> {
+    let (reference, rest) = split_reference(args)?;
+    let (first, last) = reference
+        .verses
+        .ok_or("Give the verses to highlight, e.g. John 3:16-18")?;
+    let category = match rest {
+        [] => None,
+        [category]
+            if category
+                .chars()
+                .all(|char| char.is_alphanumeric() || char == '-' || char == '_') =>
+        {
+            Some(category.to_lowercase())
+        }
+        _ => return Err("The category is one word, like promise".to_string()),
+    };
+
+    let mut store = Store::load();
+    for verse in first..=last {
+        let key = (reference.book, reference.chapter, verse);
+        match &category {
+            Some(category) => store.highlights.insert(key, category.clone()),
+            None => store.highlights.remove(&key),
+        };
+    }
+    save(&mut store)
+}
+
+fn note(args: &[&str]) -> Result<(), String> {
+    let (reference, text) = split_reference(args)?;
+    let verse = match reference.verses {
+        Some((first, last)) if first == last => first,
+        _ => return Err("Notes go on a single verse, e.g. John 3:16".to_string()),
+    };
+
+    let mut store = Store::load();
+    let key = (reference.book, reference.chapter, verse);
+    //Notes are kept one line each
+    let text = text
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        store.notes.remove(&key);
+    } else {
+        store.notes.insert(key, text);
+    }
+    save(&mut store)
+}
+
+fn order(args: &[&str]) -> Result<(), String> {
+    let mut store = Store::load();
+
+    match args {
+        [] => {
+            for order in Order::ALL {
+                let marker = if order == store.order { "*" } else { " " };
+                println!(
+                    "{} {:<14} {}",
+                    marker,
+                    order.as_str().to_lowercase(),
+                    order.description()
+                );
+            }
+            Ok(())
+        }
+        [name] => {
+            store.order =
+                Order::parse(name).ok_or_else(|| format!("Unknown order {}\n\n{}", name, USAGE))?;
+            save(&mut store)?;
+            println!(
+                "Books in {} order, {}",
+                store.order.as_str(),
+                store.order.description()
+            );
+            Ok(())
+        }
+        _ => Err("Too many arguments".to_string()),
+    }
+}
+
+fn language(args: &[&str]) -> Result<(), String> {
+    let mut store = Store::load();
+
+    match args {
+        [] => {
+            let marker = |chosen: bool| if chosen { "*" } else { " " };
+            //Without a translation there's nothing to follow, but the list still helps
+            let translation = connect().ok().and_then(|db| db.language().ok().flatten());
+            let following = Locale::pick(None, translation.as_deref());
+            println!(
+                "{} {:<6} the translation's language, {}",
+                marker(store.language.is_none()),
+                "auto",
+                match &translation {
+                    Some(code) => format!("{} ({})", following.name, code),
+                    None => format!("{} as it has none", following.name),
+                }
+            );
+            for locale in Locale::all() {
+                println!(
+                    "{} {:<6} {}",
+                    marker(store.language.as_deref() == Some(locale.code.as_str())),
+                    locale.code,
+                    locale.name
+                );
+            }
+            for error in Locale::errors() {
+                eprintln!("warning: {}", error);
+            }
+            Ok(())
+        }
+        ["auto"] => {
+            store.language = None;
+            save(&mut store)?;
+            println!("Book names and the reader follow the translation's language");
+            Ok(())
+        }
+        [code] => {
+            let locale = Locale::find(code).ok_or_else(|| {
+                format!(
+                    "No language {}, add one as {}",
+                    code,
+                    data_dir()
+                        .join("locales")
+                        .join(format!("{}.txt", code))
+                        .display()
+                )
+            })?;
+            store.language = Some(locale.code.clone());
+            save(&mut store)?;
+            println!("Book names and the reader in {}", locale.name);
+            Ok(())
+        }
+        _ => Err("Too many arguments".to_string()),
+    }
+}
+
+fn map(args: &[&str]) -> Result<(), String> {
+    let (input, from) = match args {
+        [input @ .., "--from", name] => (input, numbering(Some(name))?),
+        input => (input, Versification::English),
+    };
+
+    let input = input.join(" ");
+    let reference = parse_reference_in(&input, from)
+        .filter(|reference| reference.verses.is_some_and(|(first, last)| first == last))
+        .ok_or_else(|| format!("Give a single verse like Malachi 4:1, not {}", input))?;
+    let verse = reference.verses.map_or(1, |(verse, _)| verse);
+
+    for versification in Versification::ALL {
+        println!(
+            "{:<11} {}",
+            versification.as_str(),
+            versification.reference(reference.book, reference.chapter, verse)
+        );
+    }
+    Ok(())
+}
+
+fn strongs(args: &[&str]) -> Result<(), String> {
+    let mut db = connect()?;
+    let mut strong = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--translation" => {
+                let name = args.next().ok_or("--translation needs a name")?;
+                db.translation = db
+                    .find_translation(name)
+                    .map_err(|err| err.to_string())?
+                    .ok_or_else(|| format!("Unknown translation {}", name))?;
+            }
+            _ if arg.starts_with("--") => {
+                return Err(format!("Unknown option {}\n\n{}", arg, USAGE));
+            }
+            _ => {
+                strong = Some(Strong::parse(arg).ok_or_else(|| {
+                    format!(
+                        "Bad Strong's number {}, expected one like H7225 or G26",
+                        arg
+                    )
+                })?)
+            }
+        }
+    }
+    let strong = strong.ok_or("Give a Strong's number like H7225 or G26")?;
+
+    match Lexicon::load() {
+        Ok(lexicon) => match lexicon.get(strong) {
+            Some(entry) => {
+                println!(
+                    "{} {} {}{}",
+                    strong,
+                    entry.lemma,
+                    entry.transliteration,
+                    match entry.pronunciation.as_str() {
+                        "" => String::new(),
+                        pronunciation => format!(" ({})", pronunciation),
+                    }
+                );
+                for text in [&entry.derivation, &entry.definition] {
+                    if !text.is_empty() {
+                        println!("{}", text);
+                    }
+                }
+                if !entry.usage.is_empty() {
+                    println!("KJV: {}", entry.usage);
+                }
+                println!();
+            }
+            None => eprintln!("warning: {} isn't in the dictionary", strong),
+        },
+        Err(err) => eprintln!("warning: no definition. {}", err),
+    }
+
+    let verses = db
+        .occurrences(strong, BibleBook::Genesis, BibleBook::LAST)
+        .map_err(|err| format!("Couldn't search {}: {}", db.translation, err))?;
+    println!("{} verses in {}", verses.len(), db.translation);
+    for verse in &verses {
+        println!("{} {}", verse.reference(), verse.text);
+    }
+    Ok(())
+}
+
+//The numbering named by an option, "hebrew" or "lxx"
+fn numbering(name: Option<&&str>) -> Result<Versification, String> {
+    let name = name.ok_or("--versification needs english, hebrew, septuagint or vulgate")?;
+    Versification::parse(name).ok_or_else(|| {
+        format!(
+            "Unknown numbering {}, use english, hebrew, septuagint or vulgate",
+            name
+        )
+    })
+}
+
+//The reference at the start of the arguments, "1 John 3:16 rest" comes as four
+fn split_reference<'a>(args: &'a [&'a str]) -> Result<(Reference, &'a [&'a str]), String> {
+    (1..=args.len().min(3))
+        .rev()
+        .find_map(|count| {
+            parse_reference(&args[..count].join(" ")).map(|reference| (reference, &args[count..]))
+        })
+        .ok_or_else(|| format!("Give a reference like John 3:16\n\n{}", USAGE))
+}
+
+fn remote(command: &str) -> Result<(), String> {
+    let reply = remote::send(command).map_err(|err| err.to_string())?;
+
+    match reply.strip_prefix("error: ") {
+        Some(err) => Err(err.to_string()),
+        None => {
+            //Drop the "ok" line, only the output is of interest
+            let output = reply
+                .strip_prefix("ok")
+                .unwrap_or(&reply)
+                .trim_start_matches('\n');
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_verse(db: &Database, verse: &Verse) {
+    println!("{} ({})", verse.reference(), db.translation);
+    println!("{}", verse.text);
+}
+
+fn connect() -> Result<Database, String> {
+    Database::connect().map_err(|err| {
+        format!(
+            "Couldn't open the DB, it has to be in the same dir as the executable: {}",
+            err
+        )
+    })
+}
+
+fn active_plan(store: &Store) -> Result<(Plan, &PlanProgress), String> {
+    let progress = store
+        .plan
+        .as_ref()
+        .ok_or("No active plan, start one with: verse plan start <name>")?;
+
+    Ok((Plan::find(&progress.name)?, progress))
+}
+
+fn save(store: &mut Store) -> Result<(), String> {
+    store
+        .save()
+        .map_err(|err| format!("Couldn't save user data: {}", err))
+}
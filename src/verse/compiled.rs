@@ -0,0 +1,506 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::verse::{
+    db::{BibleBook, Error, Result},
+    index::{BibleIndex, Text},
+    versification::Versification,
+};
+
+/*
+Compiled translations, written by verse compile and read without SQLite.
+Numbers are little endian:
+
+    "VBIN"              magic
+    u16                 format version
+    u16                 flags, 1 = payload is zstd compressed
+    u32                 CRC32 of the payload as stored
+    u64                 payload length as stored
+    u16 + bytes         translation name
+    u8                  numbering the translation came in, an index into
+                        Versification::ALL
+    u8 + bytes          language code like "es", empty if unknown
+    payload             the BibleIndex tables and text:
+        u32                 verse count
+        u32                 text length
+        u32 * 1624          first verse of each chapter, then the verse count
+        u32 * (verses + 1)  where each verse starts in the text, then its length
+        bytes               all verse texts back to back, UTF-8
+
+Uncompressed files can be memory mapped, the text is then never copied.
+*/
+
+const MAGIC: &[u8; 4] = b"VBIN";
+pub const VERSION: u16 = 1;
+const FLAG_ZSTD: u16 = 1;
+
+//Writes the index to the file, returns the file size
+pub fn write(index: &BibleIndex, path: &Path, compress: bool) -> Result<usize> {
+    let (text, verse_starts, chapter_starts) = index.parts();
+    let language = index.language.as_deref().unwrap_or_default();
+    let translation_len = u16::try_from(index.translation.len()).map_err(|_| {
+        Error::Format(format!(
+            "the translation name is {} bytes, at most {} fit",
+            index.translation.len(),
+            u16::MAX
+        ))
+    })?;
+    let language_len = u8::try_from(language.len()).map_err(|_| {
+        Error::Format(format!(
+            "the language code is {} bytes, at most {} fit",
+            language.len(),
+            u8::MAX
+        ))
+    })?;
+
+    let mut payload =
+        Vec::with_capacity(8 + (verse_starts.len() + chapter_starts.len()) * 4 + text.len());
+    payload.extend((verse_starts.len() as u32 - 1).to_le_bytes());
+    payload.extend((text.len() as u32).to_le_bytes());
+    for start in chapter_starts.iter().chain(verse_starts) {
+        payload.extend(start.to_le_bytes());
+    }
+    payload.extend(text.as_bytes());
+
+    let (flags, payload) = if compress {
+        (FLAG_ZSTD, compress_payload(&payload)?)
+    } else {
+        (0, payload)
+    };
+
+    let mut file = Vec::with_capacity(payload.len() + 64);
+    file.extend(MAGIC);
+    file.extend(VERSION.to_le_bytes());
+    file.extend(flags.to_le_bytes());
+    file.extend(crc32(&payload).to_le_bytes());
+    file.extend((payload.len() as u64).to_le_bytes());
+    file.extend(translation_len.to_le_bytes());
+    file.extend(index.translation.as_bytes());
+    file.push(
+        Versification::ALL
+            .iter()
+            .position(|versification| *versification == index.versification)
+            .expect("every numbering is in ALL") as u8,
+    );
+    file.push(language_len);
+    file.extend(language.as_bytes());
+    file.extend(&payload);
+
+    //A reader may have the old file mapped, overwriting it in place would pull the
+    //text from under it. The new one is written next to it and renamed over it
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".{}.tmp", std::process::id()));
+    let temp = PathBuf::from(temp);
+    if let Err(err) = fs::write(&temp, &file).and_then(|_| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(err.into());
+    }
+    Ok(file.len())
+}
+
+#[cfg(feature = "zstd")]
+fn compress_payload(payload: &[u8]) -> Result<Vec<u8>> {
+    Ok(zstd::encode_all(payload, 19)?)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn compress_payload(_: &[u8]) -> Result<Vec<u8>> {
+    Err(Error::Format(
+        "compression needs a build with the zstd feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "zstd")]
+fn decompress_payload(payload: &[u8]) -> Result<Vec<u8>> {
+    Ok(zstd::decode_all(payload)?)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress_payload(_: &[u8]) -> Result<Vec<u8>> {
+    Err(Error::Format(
+        "the file is zstd compressed, this build lacks the zstd feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "mmap")]
+type Bytes = memmap2::Mmap;
+
+#[cfg(not(feature = "mmap"))]
+type Bytes = Vec<u8>;
+
+#[cfg(feature = "mmap")]
+fn read(path: &Path) -> Result<Bytes> {
+    let file = fs::File::open(path)?;
+    //SAFETY: the map is read only and the file isn't expected to change while verse runs
+    Ok(unsafe { memmap2::Mmap::map(&file)? })
+}
+
+#[cfg(not(feature = "mmap"))]
+fn read(path: &Path) -> Result<Bytes> {
+    Ok(fs::read(path)?)
+}
+
+//Text straight out of the file, mapped when possible
+#[cfg(feature = "mmap")]
+fn file_text(bytes: Bytes, start: usize, end: usize) -> Result<Text> {
+    std::str::from_utf8(&bytes[start..end]).map_err(|_| bad("text isn't UTF-8"))?;
+    Ok(Text::Mapped {
+        map: bytes,
+        start,
+        end,
+    })
+}
+
+#[cfg(not(feature = "mmap"))]
+fn file_text(bytes: Bytes, start: usize, end: usize) -> Result<Text> {
+    owned_text(bytes, start, end)
+}
+
+fn owned_text(mut bytes: Vec<u8>, start: usize, end: usize) -> Result<Text> {
+    bytes.truncate(end);
+    let text = bytes.split_off(start);
+    Ok(Text::Owned(
+        String::from_utf8(text).map_err(|_| bad("text isn't UTF-8"))?,
+    ))
+}
+
+pub fn open(path: &Path) -> Result<BibleIndex> {
+    let bytes = read(path)?;
+    let bad_file = |err: Error| Error::Format(format!("{}: {}", path.display(), err));
+
+    let mut header = Reader::new(&bytes);
+    if header.take(4).map_err(bad_file)? != MAGIC {
+        return Err(bad_file(bad("not a compiled translation")));
+    }
+    let version = header.u16().map_err(bad_file)?;
+    if version != VERSION {
+        return Err(bad_file(bad(&format!(
+            "format version {}, this build reads version {}",
+            version, VERSION
+        ))));
+    }
+    let flags = header.u16().map_err(bad_file)?;
+    let checksum = header.u32().map_err(bad_file)?;
+    let length = header.u64().map_err(bad_file)? as usize;
+    let name_length = header.u16().map_err(bad_file)? as usize;
+    let translation = String::from_utf8(header.take(name_length).map_err(bad_file)?.to_vec())
+        .map_err(|_| bad_file(bad("translation name isn't UTF-8")))?;
+    let number = header.take(1).map_err(bad_file)?[0];
+    let versification = *Versification::ALL
+        .get(number as usize)
+        .ok_or_else(|| bad_file(bad(&format!("unknown numbering {}", number))))?;
+    let language_length = header.take(1).map_err(bad_file)?[0] as usize;
+    let language = std::str::from_utf8(header.take(language_length).map_err(bad_file)?)
+        .map_err(|_| bad_file(bad("language isn't UTF-8")))?;
+    let language = Some(language.to_string()).filter(|language| !language.is_empty());
+
+    let start = header.position;
+    let payload = header.take(length).map_err(bad_file)?;
+    if crc32(payload) != checksum {
+        return Err(bad_file(bad("checksum mismatch, the file is damaged")));
+    }
+
+    let index = if flags & FLAG_ZSTD != 0 {
+        let payload = decompress_payload(payload).map_err(bad_file)?;
+        let (verse_starts, chapter_starts, text_start, text_end) =
+            tables(&payload).map_err(bad_file)?;
+        let text = owned_text(payload, text_start, text_end).map_err(bad_file)?;
+        BibleIndex::from_parts(&translation, text, verse_starts, chapter_starts)
+    } else {
+        let (verse_starts, chapter_starts, text_start, text_end) =
+            tables(payload).map_err(bad_file)?;
+        let text = file_text(bytes, start + text_start, start + text_end).map_err(bad_file)?;
+        BibleIndex::from_parts(&translation, text, verse_starts, chapter_starts)
+    };
+
+    let mut index = index.map_err(|err| bad_file(Error::Format(err)))?;
+    index.versification = versification;
+    index.language = language;
+    Ok(index)
+}
+
+//Verse and chapter tables of the payload, and where the text lies in it
+fn tables(payload: &[u8]) -> Result<(Vec<u32>, Vec<u32>, usize, usize)> {
+    let mut reader = Reader::new(payload);
+    let verses = reader.u32()? as usize;
+    let text_length = reader.u32()? as usize;
+
+    let chapter_starts = (0..=BibleBook::TOTAL_CHAPTERS)
+        .map(|_| reader.u32())
+        .collect::<Result<Vec<u32>>>()?;
+    let verse_starts = (0..=verses)
+        .map(|_| reader.u32())
+        .collect::<Result<Vec<u32>>>()?;
+
+    let text_start = reader.position;
+    reader.take(text_length)?;
+    Ok((verse_starts, chapter_starts, text_start, reader.position))
+}
+
+fn bad(message: &str) -> Error {
+    Error::Format(message.to_string())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, position: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| bad("file is cut short"))?;
+
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(
+            self.take(2)?.try_into().expect("2 bytes"),
+        ))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(
+            self.take(4)?.try_into().expect("4 bytes"),
+        ))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(
+            self.take(8)?.try_into().expect("8 bytes"),
+        ))
+    }
+}
+
+//CRC-32 as used by zip and PNG
+pub fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xEDB88320
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    !bytes.iter().fold(!0, |crc, byte| {
+        TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    //A file of its own in the temp dir, removed when dropped
+    struct TempFile(std::path::PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            TempFile(env::temp_dir().join(format!("verse-{}-{}.vbin", process::id(), name)))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn index() -> BibleIndex {
+        let mut index = BibleIndex::build(
+            "TEST",
+            [
+                (BibleBook::Genesis, 1, 1, "In the beginning".to_string()),
+                (
+                    BibleBook::Genesis,
+                    1,
+                    2,
+                    "Und die Erde war wüst".to_string(),
+                ),
+                (
+                    BibleBook::Malachi,
+                    4,
+                    1,
+                    "For, behold, the day cometh".to_string(),
+                ),
+                (
+                    BibleBook::Tobit,
+                    1,
+                    1,
+                    "The book of the words of Tobit".to_string(),
+                ),
+            ],
+        );
+        index.versification = Versification::Hebrew;
+        index.language = Some("de".to_string());
+        index
+    }
+
+    #[test]
+    fn round_trip() {
+        let file = TempFile::new("round-trip");
+        let size = write(&index(), &file.0, false).unwrap();
+        assert_eq!(size as u64, fs::metadata(&file.0).unwrap().len());
+
+        let index = open(&file.0).unwrap();
+        assert_eq!(index.translation, "TEST");
+        assert_eq!(index.versification, Versification::Hebrew);
+        assert_eq!(index.language.as_deref(), Some("de"));
+        assert_eq!(index.verse_count(), 4);
+        assert_eq!(
+            index.chapter(BibleBook::Genesis, 1),
+            ["In the beginning", "Und die Erde war wüst"]
+        );
+        assert_eq!(
+            index.verse(BibleBook::Malachi, 4, 1),
+            Some("For, behold, the day cometh")
+        );
+        assert_eq!(index.chapter(BibleBook::Tobit, 1).len(), 1);
+        assert!(index.chapter(BibleBook::Exodus, 1).is_empty());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn round_trip_compressed() {
+        let file = TempFile::new("compressed");
+        write(&index(), &file.0, true).unwrap();
+
+        let index = open(&file.0).unwrap();
+        assert_eq!(
+            index.verse(BibleBook::Genesis, 1, 2),
+            Some("Und die Erde war wüst")
+        );
+    }
+
+    #[test]
+    fn damaged() {
+        let file = TempFile::new("damaged");
+        write(&index(), &file.0, false).unwrap();
+
+        let mut bytes = fs::read(&file.0).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&file.0, &bytes).unwrap();
+        assert!(
+            open(&file.0)
+                .err()
+                .unwrap()
+                .to_string()
+                .contains("checksum")
+        );
+
+        bytes.truncate(last - 10);
+        fs::write(&file.0, &bytes).unwrap();
+        assert!(
+            open(&file.0)
+                .err()
+                .unwrap()
+                .to_string()
+                .contains("cut short")
+        );
+
+        fs::write(&file.0, b"PK\x03\x04 not a Bible").unwrap();
+        assert!(
+            open(&file.0)
+                .err()
+                .unwrap()
+                .to_string()
+                .contains("not a compiled")
+        );
+    }
+
+    #[test]
+    fn other_version() {
+        let file = TempFile::new("version");
+        write(&index(), &file.0, false).unwrap();
+
+        let mut bytes = fs::read(&file.0).unwrap();
+        bytes[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        fs::write(&file.0, &bytes).unwrap();
+        assert!(
+            open(&file.0)
+                .err()
+                .unwrap()
+                .to_string()
+                .contains("format version")
+        );
+    }
+
+    //An index read from the file, mapped with the mmap feature, outlives a new write
+    #[test]
+    fn rewritten() {
+        let file = TempFile::new("rewritten");
+        write(&index(), &file.0, false).unwrap();
+        let old = open(&file.0).unwrap();
+
+        let mut index = index();
+        index.translation = "NEW".to_string();
+        write(&index, &file.0, false).unwrap();
+        assert_eq!(old.translation, "TEST");
+        assert_eq!(
+            old.verse(BibleBook::Genesis, 1, 1),
+            Some("In the beginning")
+        );
+        assert_eq!(open(&file.0).unwrap().translation, "NEW");
+
+        let prefix = file.0.file_name().unwrap().to_string_lossy().to_string();
+        assert!(!fs::read_dir(env::temp_dir()).unwrap().any(|entry| {
+            entry
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with(&format!("{}.", prefix))
+        }));
+    }
+
+    #[test]
+    fn too_long() {
+        let file = TempFile::new("too-long");
+        let mut named = index();
+        named.translation = "T".repeat(u16::MAX as usize + 1);
+        assert!(matches!(
+            write(&named, &file.0, false),
+            Err(Error::Format(_))
+        ));
+
+        let mut tagged = index();
+        tagged.language = Some("x".repeat(256));
+        assert!(matches!(
+            write(&tagged, &file.0, false),
+            Err(Error::Format(_))
+        ));
+        assert!(!file.0.exists());
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+}
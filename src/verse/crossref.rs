@@ -0,0 +1,134 @@
+use std::{collections::HashMap, env, fs, path::PathBuf};
+
+use crate::verse::db::BibleBook;
+
+//A verse or range of verses another verse points to
+pub struct CrossReference {
+    pub first: (BibleBook, u8, u8),
+    pub last: (BibleBook, u8, u8),
+    pub votes: i32,
+}
+
+impl CrossReference {
+    //"Romans 5:8", "Romans 5:8-10" or "Romans 5:8-6:2"
+    pub fn reference(&self) -> String {
+        let (book, chapter, verse) = self.first;
+        let (to_book, to_chapter, to_verse) = self.last;
+
+        if self.first == self.last {
+            format!("{} {}:{}", book.as_str(), chapter, verse)
+        } else if book == to_book && chapter == to_chapter {
+            format!("{} {}:{}-{}", book.as_str(), chapter, verse, to_verse)
+        } else if book == to_book {
+            format!(
+                "{} {}:{}-{}:{}",
+                book.as_str(),
+                chapter,
+                verse,
+                to_chapter,
+                to_verse
+            )
+        } else {
+            format!(
+                "{} {}:{}-{} {}:{}",
+                book.as_str(),
+                chapter,
+                verse,
+                to_book.as_str(),
+                to_chapter,
+                to_verse
+            )
+        }
+    }
+
+    //"Rom.5.8" or "Rom.5.8-Rom.5.10"
+    pub fn osis(&self) -> String {
+        if self.first == self.last {
+            osis(self.first)
+        } else {
+            format!("{}-{}", osis(self.first), osis(self.last))
+        }
+    }
+}
+
+/*
+Cross references from openbible.info, cross_references.txt next to the executable.
+Tab separated with a header line:
+
+    From Verse	To Verse	Votes
+    Gen.1.1	Prov.8.22-Prov.8.30	59
+*/
+pub struct CrossReferences {
+    by_verse: HashMap<(BibleBook, u8, u8), Vec<CrossReference>>,
+}
+
+impl CrossReferences {
+    pub fn path() -> Option<PathBuf> {
+        env::current_exe()
+            .ok()
+            .and_then(|path| path.parent().map(|dir| dir.join("cross_references.txt")))
+    }
+
+    pub fn load() -> Result<Self, String> {
+        let path = Self::path().ok_or("Could not get path to dir.")?;
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| format!("Couldn't read {}: {}", path.display(), err))?;
+        Ok(Self::parse(&contents))
+    }
+
+    //The file's contents, lines that can't be read are skipped
+    pub fn parse(contents: &str) -> Self {
+        let mut by_verse: HashMap<(BibleBook, u8, u8), Vec<CrossReference>> = HashMap::new();
+        for line in contents.lines().skip(1) {
+            let mut fields = line.split('\t');
+            let (Some(from), Some(to), Some(votes)) = (
+                fields.next().and_then(parse_osis),
+                fields.next(),
+                fields.next().and_then(|votes| votes.trim().parse().ok()),
+            ) else {
+                continue;
+            };
+
+            let (to_first, to_last) = match to.split_once('-') {
+                Some((first, last)) => (parse_osis(first), parse_osis(last)),
+                None => (parse_osis(to), parse_osis(to)),
+            };
+            let (Some(to_first), Some(to_last)) = (to_first, to_last) else {
+                continue;
+            };
+
+            by_verse.entry(from).or_default().push(CrossReference {
+                first: to_first,
+                last: to_last,
+                votes,
+            });
+        }
+
+        //Most voted first
+        for references in by_verse.values_mut() {
+            references.sort_by_key(|reference| -reference.votes);
+        }
+
+        CrossReferences { by_verse }
+    }
+
+    pub fn get(&self, book: BibleBook, chapter: u8, verse: u8) -> &[CrossReference] {
+        self.by_verse
+            .get(&(book, chapter, verse))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+//"John.3.16" as book, chapter and verse
+pub fn parse_osis(id: &str) -> Option<(BibleBook, u8, u8)> {
+    let mut parts = id.trim().split('.');
+    let (book, chapter, verse) = (parts.next()?, parts.next()?, parts.next()?);
+
+    let book = BibleBook::from_osis_id(book)?;
+    Some((book, chapter.parse().ok()?, verse.parse().ok()?))
+}
+
+fn osis((book, chapter, verse): (BibleBook, u8, u8)) -> String {
+    format!("{}.{}.{}", book.osis_id(), chapter, verse)
+}
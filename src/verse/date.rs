@@ -0,0 +1,155 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+//Calendar date stored as days since 1970-01-01
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    days: i32,
+}
+
+impl Date {
+    //Today in UTC
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Date {
+            days: (secs / 86400) as i32,
+        }
+    }
+
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        //Howard Hinnant's days_from_civil
+        let y = if month <= 2 { year - 1 } else { year };
+        let era = y.div_euclid(400);
+        let yoe = y.rem_euclid(400);
+        let mp = (month + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy as i32;
+
+        Some(Date {
+            days: era * 146097 + doe - 719468,
+        })
+    }
+
+    pub fn ymd(&self) -> (i32, u32, u32) {
+        //Howard Hinnant's civil_from_days
+        let z = self.days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        (year, month, day)
+    }
+
+    pub fn add_days(&self, days: i32) -> Self {
+        Date {
+            days: self.days + days,
+        }
+    }
+
+    //Negative when other is later
+    pub fn days_since(&self, other: Date) -> i32 {
+        self.days - other.days
+    }
+
+    //Stable number for the date, used to seed per day choices
+    pub fn ordinal(&self) -> i32 {
+        self.days
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.ymd();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+//YYYY-MM-DD
+impl FromStr for Date {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().splitn(3, '-');
+        let year = parts.next().and_then(|p| p.parse().ok()).ok_or(())?;
+        let month = parts.next().and_then(|p| p.parse().ok()).ok_or(())?;
+        let day = parts.next().and_then(|p| p.parse().ok()).ok_or(())?;
+
+        Date::from_ymd(year, month, day).ok_or(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch() {
+        let date = Date::from_ymd(1970, 1, 1).unwrap();
+        assert_eq!(date.ordinal(), 0);
+        assert_eq!(date.ymd(), (1970, 1, 1));
+    }
+
+    #[test]
+    fn ymd_round_trip() {
+        for days in (-800_000..800_000).step_by(7) {
+            let date = Date { days };
+            let (year, month, day) = date.ymd();
+            assert!(Date::from_ymd(year, month, day) == Some(date), "{}", days);
+        }
+    }
+
+    #[test]
+    fn leap_years() {
+        assert!(Date::from_ymd(2024, 2, 29).is_some());
+        assert!(Date::from_ymd(2000, 2, 29).is_some());
+        assert!(Date::from_ymd(2023, 2, 29).is_none());
+        assert!(Date::from_ymd(1900, 2, 29).is_none());
+
+        let march = Date::from_ymd(2024, 3, 1).unwrap();
+        assert_eq!(march.add_days(-1).ymd(), (2024, 2, 29));
+        assert_eq!(Date::from_ymd(2025, 3, 1).unwrap().days_since(march), 365);
+    }
+
+    #[test]
+    fn bad_dates() {
+        assert!(Date::from_ymd(2026, 0, 1).is_none());
+        assert!(Date::from_ymd(2026, 13, 1).is_none());
+        assert!(Date::from_ymd(2026, 4, 31).is_none());
+        assert!(Date::from_ymd(2026, 1, 0).is_none());
+        assert!("2026-10".parse::<Date>().is_err());
+        assert!("2026-10-x".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn text() {
+        let date: Date = " 2026-10-19 ".parse().unwrap();
+        assert_eq!(date.ymd(), (2026, 10, 19));
+        assert_eq!(date.to_string(), "2026-10-19");
+        assert_eq!(Date::from_ymd(33, 4, 3).unwrap().to_string(), "0033-04-03");
+    }
+}
@@ -1,7 +1,20 @@
-use rusqlite::{Connection, OpenFlags, Result};
-use std::env;
+#[cfg(feature = "sqlite")]
+use std::path::Path;
+use std::{borrow::Cow, env, fmt, fs, io, str::FromStr, sync::Arc};
 
-#[derive(Copy, Clone, PartialEq)]
+#[cfg(feature = "sqlite")]
+use crate::verse::sqlite::Sqlite;
+use crate::verse::{
+    compiled,
+    index::BibleIndex,
+    locale::Locale,
+    parse::parse_book,
+    source::{self, BibleSource, Memory},
+    strongs::{self, Strong},
+    versification::Versification,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum BibleBook {
     Genesis = 1,
     Exodus,
@@ -69,6 +82,30 @@ pub enum BibleBook {
     ThirdJohn,
     Jude,
     Revelation,
+    //The deuterocanonical books, numbered after the Protestant canon so its ids stay put
+    Tobit,
+    Judith,
+    AdditionsToEsther,
+    Wisdom,
+    Sirach,
+    Baruch,
+    LetterOfJeremiah,
+    PrayerOfAzariah,
+    Susanna,
+    BelAndTheDragon,
+    FirstMaccabees,
+    SecondMaccabees,
+    ThirdMaccabees,
+    FourthMaccabees,
+    FirstEsdras,
+    SecondEsdras,
+    PrayerOfManasseh,
+    Psalm151,
+    Enoch,
+    Jubilees,
+    FirstMeqabyan,
+    SecondMeqabyan,
+    ThirdMeqabyan,
 }
 
 impl BibleBook {
@@ -140,15 +177,490 @@ impl BibleBook {
             BibleBook::ThirdJohn => "3 John",
             BibleBook::Jude => "Jude",
             BibleBook::Revelation => "Revelation",
+            BibleBook::Tobit => "Tobit",
+            BibleBook::Judith => "Judith",
+            BibleBook::AdditionsToEsther => "Additions to Esther",
+            BibleBook::Wisdom => "Wisdom of Solomon",
+            BibleBook::Sirach => "Sirach",
+            BibleBook::Baruch => "Baruch",
+            BibleBook::LetterOfJeremiah => "Letter of Jeremiah",
+            BibleBook::PrayerOfAzariah => "Prayer of Azariah",
+            BibleBook::Susanna => "Susanna",
+            BibleBook::BelAndTheDragon => "Bel and the Dragon",
+            BibleBook::FirstMaccabees => "1 Maccabees",
+            BibleBook::SecondMaccabees => "2 Maccabees",
+            BibleBook::ThirdMaccabees => "3 Maccabees",
+            BibleBook::FourthMaccabees => "4 Maccabees",
+            BibleBook::FirstEsdras => "1 Esdras",
+            BibleBook::SecondEsdras => "2 Esdras",
+            BibleBook::PrayerOfManasseh => "Prayer of Manasseh",
+            BibleBook::Psalm151 => "Psalm 151",
+            BibleBook::Enoch => "Enoch",
+            BibleBook::Jubilees => "Jubilees",
+            BibleBook::FirstMeqabyan => "1 Meqabyan",
+            BibleBook::SecondMeqabyan => "2 Meqabyan",
+            BibleBook::ThirdMeqabyan => "3 Meqabyan",
+        }
+    }
+
+    //OSIS book id, as used in references like "John.3.16"
+    pub fn osis_id(&self) -> &'static str {
+        match self {
+            BibleBook::Genesis => "Gen",
+            BibleBook::Exodus => "Exod",
+            BibleBook::Leviticus => "Lev",
+            BibleBook::Numbers => "Num",
+            BibleBook::Deuteronomy => "Deut",
+            BibleBook::Joshua => "Josh",
+            BibleBook::Judges => "Judg",
+            BibleBook::Ruth => "Ruth",
+            BibleBook::FirstSamuel => "1Sam",
+            BibleBook::SecondSamuel => "2Sam",
+            BibleBook::FirstKings => "1Kgs",
+            BibleBook::SecondKings => "2Kgs",
+            BibleBook::FirstChronicles => "1Chr",
+            BibleBook::SecondChronicles => "2Chr",
+            BibleBook::Ezra => "Ezra",
+            BibleBook::Nehemiah => "Neh",
+            BibleBook::Esther => "Esth",
+            BibleBook::Job => "Job",
+            BibleBook::Psalms => "Ps",
+            BibleBook::Proverbs => "Prov",
+            BibleBook::Ecclesiastes => "Eccl",
+            BibleBook::SongOfSolomon => "Song",
+            BibleBook::Isaiah => "Isa",
+            BibleBook::Jeremiah => "Jer",
+            BibleBook::Lamentations => "Lam",
+            BibleBook::Ezekiel => "Ezek",
+            BibleBook::Daniel => "Dan",
+            BibleBook::Hosea => "Hos",
+            BibleBook::Joel => "Joel",
+            BibleBook::Amos => "Amos",
+            BibleBook::Obadiah => "Obad",
+            BibleBook::Jonah => "Jonah",
+            BibleBook::Micah => "Mic",
+            BibleBook::Nahum => "Nah",
+            BibleBook::Habakkuk => "Hab",
+            BibleBook::Zephaniah => "Zeph",
+            BibleBook::Haggai => "Hag",
+            BibleBook::Zechariah => "Zech",
+            BibleBook::Malachi => "Mal",
+            BibleBook::Matthew => "Matt",
+            BibleBook::Mark => "Mark",
+            BibleBook::Luke => "Luke",
+            BibleBook::John => "John",
+            BibleBook::Acts => "Acts",
+            BibleBook::Romans => "Rom",
+            BibleBook::FirstCorinthians => "1Cor",
+            BibleBook::SecondCorinthians => "2Cor",
+            BibleBook::Galatians => "Gal",
+            BibleBook::Ephesians => "Eph",
+            BibleBook::Philippians => "Phil",
+            BibleBook::Colossians => "Col",
+            BibleBook::FirstThessalonians => "1Thess",
+            BibleBook::SecondThessalonians => "2Thess",
+            BibleBook::FirstTimothy => "1Tim",
+            BibleBook::SecondTimothy => "2Tim",
+            BibleBook::Titus => "Titus",
+            BibleBook::Philemon => "Phlm",
+            BibleBook::Hebrews => "Heb",
+            BibleBook::James => "Jas",
+            BibleBook::FirstPeter => "1Pet",
+            BibleBook::SecondPeter => "2Pet",
+            BibleBook::FirstJohn => "1John",
+            BibleBook::SecondJohn => "2John",
+            BibleBook::ThirdJohn => "3John",
+            BibleBook::Jude => "Jude",
+            BibleBook::Revelation => "Rev",
+            BibleBook::Tobit => "Tob",
+            BibleBook::Judith => "Jdt",
+            BibleBook::AdditionsToEsther => "AddEsth",
+            BibleBook::Wisdom => "Wis",
+            BibleBook::Sirach => "Sir",
+            BibleBook::Baruch => "Bar",
+            BibleBook::LetterOfJeremiah => "EpJer",
+            BibleBook::PrayerOfAzariah => "PrAzar",
+            BibleBook::Susanna => "Sus",
+            BibleBook::BelAndTheDragon => "Bel",
+            BibleBook::FirstMaccabees => "1Macc",
+            BibleBook::SecondMaccabees => "2Macc",
+            BibleBook::ThirdMaccabees => "3Macc",
+            BibleBook::FourthMaccabees => "4Macc",
+            BibleBook::FirstEsdras => "1Esd",
+            BibleBook::SecondEsdras => "2Esd",
+            BibleBook::PrayerOfManasseh => "PrMan",
+            BibleBook::Psalm151 => "AddPs",
+            BibleBook::Enoch => "1En",
+            BibleBook::Jubilees => "Jub",
+            BibleBook::FirstMeqabyan => "1Meq",
+            BibleBook::SecondMeqabyan => "2Meq",
+            BibleBook::ThirdMeqabyan => "3Meq",
         }
     }
 
+    //Book of an OSIS id, ignoring case
+    pub fn from_osis_id(id: &str) -> Option<BibleBook> {
+        Self::ALL
+            .into_iter()
+            .find(|book| book.osis_id().eq_ignore_ascii_case(id))
+    }
+
+    //USFM book code, as in "\id GEN"
+    pub fn usfm_code(&self) -> &'static str {
+        match self {
+            BibleBook::Genesis => "GEN",
+            BibleBook::Exodus => "EXO",
+            BibleBook::Leviticus => "LEV",
+            BibleBook::Numbers => "NUM",
+            BibleBook::Deuteronomy => "DEU",
+            BibleBook::Joshua => "JOS",
+            BibleBook::Judges => "JDG",
+            BibleBook::Ruth => "RUT",
+            BibleBook::FirstSamuel => "1SA",
+            BibleBook::SecondSamuel => "2SA",
+            BibleBook::FirstKings => "1KI",
+            BibleBook::SecondKings => "2KI",
+            BibleBook::FirstChronicles => "1CH",
+            BibleBook::SecondChronicles => "2CH",
+            BibleBook::Ezra => "EZR",
+            BibleBook::Nehemiah => "NEH",
+            BibleBook::Esther => "EST",
+            BibleBook::Job => "JOB",
+            BibleBook::Psalms => "PSA",
+            BibleBook::Proverbs => "PRO",
+            BibleBook::Ecclesiastes => "ECC",
+            BibleBook::SongOfSolomon => "SNG",
+            BibleBook::Isaiah => "ISA",
+            BibleBook::Jeremiah => "JER",
+            BibleBook::Lamentations => "LAM",
+            BibleBook::Ezekiel => "EZK",
+            BibleBook::Daniel => "DAN",
+            BibleBook::Hosea => "HOS",
+            BibleBook::Joel => "JOL",
+            BibleBook::Amos => "AMO",
+            BibleBook::Obadiah => "OBA",
+            BibleBook::Jonah => "JON",
+            BibleBook::Micah => "MIC",
+            BibleBook::Nahum => "NAM",
+            BibleBook::Habakkuk => "HAB",
+            BibleBook::Zephaniah => "ZEP",
+            BibleBook::Haggai => "HAG",
+            BibleBook::Zechariah => "ZEC",
+            BibleBook::Malachi => "MAL",
+            BibleBook::Matthew => "MAT",
+            BibleBook::Mark => "MRK",
+            BibleBook::Luke => "LUK",
+            BibleBook::John => "JHN",
+            BibleBook::Acts => "ACT",
+            BibleBook::Romans => "ROM",
+            BibleBook::FirstCorinthians => "1CO",
+            BibleBook::SecondCorinthians => "2CO",
+            BibleBook::Galatians => "GAL",
+            BibleBook::Ephesians => "EPH",
+            BibleBook::Philippians => "PHP",
+            BibleBook::Colossians => "COL",
+            BibleBook::FirstThessalonians => "1TH",
+            BibleBook::SecondThessalonians => "2TH",
+            BibleBook::FirstTimothy => "1TI",
+            BibleBook::SecondTimothy => "2TI",
+            BibleBook::Titus => "TIT",
+            BibleBook::Philemon => "PHM",
+            BibleBook::Hebrews => "HEB",
+            BibleBook::James => "JAS",
+            BibleBook::FirstPeter => "1PE",
+            BibleBook::SecondPeter => "2PE",
+            BibleBook::FirstJohn => "1JN",
+            BibleBook::SecondJohn => "2JN",
+            BibleBook::ThirdJohn => "3JN",
+            BibleBook::Jude => "JUD",
+            BibleBook::Revelation => "REV",
+            BibleBook::Tobit => "TOB",
+            BibleBook::Judith => "JDT",
+            BibleBook::AdditionsToEsther => "ESG",
+            BibleBook::Wisdom => "WIS",
+            BibleBook::Sirach => "SIR",
+            BibleBook::Baruch => "BAR",
+            BibleBook::LetterOfJeremiah => "LJE",
+            BibleBook::PrayerOfAzariah => "S3Y",
+            BibleBook::Susanna => "SUS",
+            BibleBook::BelAndTheDragon => "BEL",
+            BibleBook::FirstMaccabees => "1MA",
+            BibleBook::SecondMaccabees => "2MA",
+            BibleBook::ThirdMaccabees => "3MA",
+            BibleBook::FourthMaccabees => "4MA",
+            BibleBook::FirstEsdras => "1ES",
+            BibleBook::SecondEsdras => "2ES",
+            BibleBook::PrayerOfManasseh => "MAN",
+            BibleBook::Psalm151 => "PS2",
+            BibleBook::Enoch => "ENO",
+            BibleBook::Jubilees => "JUB",
+            BibleBook::FirstMeqabyan => "1MQ",
+            BibleBook::SecondMeqabyan => "2MQ",
+            BibleBook::ThirdMeqabyan => "3MQ",
+        }
+    }
+
+    //Book of a USFM code, ignoring case
+    pub fn from_usfm_code(code: &str) -> Option<BibleBook> {
+        Self::ALL
+            .into_iter()
+            .find(|book| book.usfm_code().eq_ignore_ascii_case(code))
+    }
+
+    //Next book by id, None after the last. Readers follow a Canon's order instead
+    pub fn next(&self) -> Option<BibleBook> {
+        Self::ALL.get(*self as usize).copied()
+    }
+
+    //Previous book by id, None before Genesis
+    pub fn prev(&self) -> Option<BibleBook> {
+        (*self as usize).checked_sub(2).map(|i| Self::ALL[i])
+    }
+
     #[inline]
     pub fn max_chapter_count(&self) -> u8 {
         Self::CHAPTER_COUNT[*self as usize]
     }
 
-    const CHAPTER_COUNT: [u8; 67] = [
+    //Chapters in all the books before this one
+    pub fn chapter_offset(&self) -> u16 {
+        Self::CHAPTER_COUNT[..*self as usize]
+            .iter()
+            .map(|count| *count as u16)
+            .sum()
+    }
+
+    //Verses of a chapter in the KJV versification, 0 past the last chapter
+    pub fn kjv_verse_count(&self, chapter: u8) -> u8 {
+        Self::VERSE_COUNT
+            .get(*self as usize)
+            .and_then(|counts| counts.get((chapter as usize).wrapping_sub(1)))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    //Every book by id, the Protestant canon in its order then the deuterocanonical books
+    pub fn all() -> impl Iterator<Item = BibleBook> {
+        Self::ALL.into_iter()
+    }
+
+    //Books of no Protestant Bible, from Tobit on
+    pub fn is_deuterocanonical(&self) -> bool {
+        *self as u8 > BibleBook::Revelation as u8
+    }
+
+    pub fn testament(&self) -> Testament {
+        if (*self as u8) <= BibleBook::Malachi as u8 || self.is_deuterocanonical() {
+            Testament::Old
+        } else {
+            Testament::New
+        }
+    }
+
+    //The group the book is usually listed under
+    pub fn genre(&self) -> Genre {
+        let through = |last: BibleBook| (*self as u8) <= last as u8;
+
+        match self {
+            BibleBook::Wisdom
+            | BibleBook::Sirach
+            | BibleBook::PrayerOfManasseh
+            | BibleBook::Psalm151 => return Genre::Wisdom,
+            BibleBook::Baruch
+            | BibleBook::LetterOfJeremiah
+            | BibleBook::PrayerOfAzariah
+            | BibleBook::Susanna
+            | BibleBook::BelAndTheDragon => return Genre::MajorProphets,
+            BibleBook::SecondEsdras | BibleBook::Enoch => return Genre::Apocalyptic,
+            book if book.is_deuterocanonical() => return Genre::History,
+            _ => {}
+        }
+
+        if through(BibleBook::Deuteronomy) {
+            Genre::Law
+        } else if through(BibleBook::Esther) {
+            Genre::History
+        } else if through(BibleBook::SongOfSolomon) {
+            Genre::Wisdom
+        } else if through(BibleBook::Daniel) {
+            Genre::MajorProphets
+        } else if through(BibleBook::Malachi) {
+            Genre::MinorProphets
+        } else if through(BibleBook::John) {
+            Genre::Gospels
+        } else if through(BibleBook::Acts) {
+            Genre::History
+        } else if through(BibleBook::Jude) {
+            Genre::Epistles
+        } else {
+            Genre::Apocalyptic
+        }
+    }
+
+    //SBL Handbook of Style abbreviation, the OSIS id with a space after the number, "1 Sam"
+    pub fn sbl_abbreviation(&self) -> String {
+        //OSIS runs these words together
+        match self {
+            BibleBook::AdditionsToEsther => return "Add Esth".to_string(),
+            BibleBook::LetterOfJeremiah => return "Ep Jer".to_string(),
+            BibleBook::PrayerOfAzariah => return "Pr Azar".to_string(),
+            BibleBook::PrayerOfManasseh => return "Pr Man".to_string(),
+            BibleBook::Psalm151 => return "Ps 151".to_string(),
+            _ => {}
+        }
+
+        let id = self.osis_id();
+        match id.strip_prefix(|char: char| char.is_ascii_digit()) {
+            Some(rest) => format!("{} {}", &id[..1], rest),
+            None => id.to_string(),
+        }
+    }
+
+    //Paratext book number, used in file names like 41MATKJV.SFM. The NT starts at 41,
+    //books from Enoch on have letter codes instead
+    pub fn paratext_number(&self) -> Option<u8> {
+        match *self as u8 {
+            id if id <= BibleBook::Malachi as u8 => Some(id),
+            id if id <= BibleBook::Psalm151 as u8 => Some(id + 1),
+            _ => None,
+        }
+    }
+
+    //For each book, the shortest start of its name in the language without spaces and
+    //dots that no other of the books starts with
+    pub fn menu_prefixes(books: &[BibleBook], locale: &Locale) -> Vec<String> {
+        let names: Vec<String> = books
+            .iter()
+            .map(|book| menu_name(locale.book(*book)))
+            .collect();
+
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                name.char_indices()
+                    .map(|(start, char)| &name[..start + char.len_utf8()])
+                    .find(|prefix| {
+                        !names
+                            .iter()
+                            .enumerate()
+                            .any(|(j, other)| i != j && other.starts_with(prefix))
+                    })
+                    .unwrap_or(name)
+                    .to_string()
+            })
+            .collect()
+    }
+
+    //Verses in the book in the KJV versification
+    pub fn kjv_verse_total(&self) -> u16 {
+        Self::VERSE_COUNT
+            .get(*self as usize)
+            .map_or(&[][..], |counts| counts)
+            .iter()
+            .map(|count| *count as u16)
+            .sum()
+    }
+
+    //Chapters of every book, the size of an index's chapter table
+    pub const TOTAL_CHAPTERS: u16 = 1623;
+
+    //Last book by id, ranges from Genesis to it take in every book
+    pub const LAST: BibleBook = BibleBook::ThirdMeqabyan;
+
+    pub const ALL: [BibleBook; 89] = [
+        BibleBook::Genesis,
+        BibleBook::Exodus,
+        BibleBook::Leviticus,
+        BibleBook::Numbers,
+        BibleBook::Deuteronomy,
+        BibleBook::Joshua,
+        BibleBook::Judges,
+        BibleBook::Ruth,
+        BibleBook::FirstSamuel,
+        BibleBook::SecondSamuel,
+        BibleBook::FirstKings,
+        BibleBook::SecondKings,
+        BibleBook::FirstChronicles,
+        BibleBook::SecondChronicles,
+        BibleBook::Ezra,
+        BibleBook::Nehemiah,
+        BibleBook::Esther,
+        BibleBook::Job,
+        BibleBook::Psalms,
+        BibleBook::Proverbs,
+        BibleBook::Ecclesiastes,
+        BibleBook::SongOfSolomon,
+        BibleBook::Isaiah,
+        BibleBook::Jeremiah,
+        BibleBook::Lamentations,
+        BibleBook::Ezekiel,
+        BibleBook::Daniel,
+        BibleBook::Hosea,
+        BibleBook::Joel,
+        BibleBook::Amos,
+        BibleBook::Obadiah,
+        BibleBook::Jonah,
+        BibleBook::Micah,
+        BibleBook::Nahum,
+        BibleBook::Habakkuk,
+        BibleBook::Zephaniah,
+        BibleBook::Haggai,
+        BibleBook::Zechariah,
+        BibleBook::Malachi,
+        BibleBook::Matthew,
+        BibleBook::Mark,
+        BibleBook::Luke,
+        BibleBook::John,
+        BibleBook::Acts,
+        BibleBook::Romans,
+        BibleBook::FirstCorinthians,
+        BibleBook::SecondCorinthians,
+        BibleBook::Galatians,
+        BibleBook::Ephesians,
+        BibleBook::Philippians,
+        BibleBook::Colossians,
+        BibleBook::FirstThessalonians,
+        BibleBook::SecondThessalonians,
+        BibleBook::FirstTimothy,
+        BibleBook::SecondTimothy,
+        BibleBook::Titus,
+        BibleBook::Philemon,
+        BibleBook::Hebrews,
+        BibleBook::James,
+        BibleBook::FirstPeter,
+        BibleBook::SecondPeter,
+        BibleBook::FirstJohn,
+        BibleBook::SecondJohn,
+        BibleBook::ThirdJohn,
+        BibleBook::Jude,
+        BibleBook::Revelation,
+        BibleBook::Tobit,
+        BibleBook::Judith,
+        BibleBook::AdditionsToEsther,
+        BibleBook::Wisdom,
+        BibleBook::Sirach,
+        BibleBook::Baruch,
+        BibleBook::LetterOfJeremiah,
+        BibleBook::PrayerOfAzariah,
+        BibleBook::Susanna,
+        BibleBook::BelAndTheDragon,
+        BibleBook::FirstMaccabees,
+        BibleBook::SecondMaccabees,
+        BibleBook::ThirdMaccabees,
+        BibleBook::FourthMaccabees,
+        BibleBook::FirstEsdras,
+        BibleBook::SecondEsdras,
+        BibleBook::PrayerOfManasseh,
+        BibleBook::Psalm151,
+        BibleBook::Enoch,
+        BibleBook::Jubilees,
+        BibleBook::FirstMeqabyan,
+        BibleBook::SecondMeqabyan,
+        BibleBook::ThirdMeqabyan,
+    ];
+
+    const CHAPTER_COUNT: [u8; 90] = [
         0,   // Invalid
         50,  // Genesis
         40,  // Exodus
@@ -185,9 +697,9 @@ impl BibleBook {
         7,   // Micah
         3,   // Nahum
         3,   // Habakkuk
-        2,   // Zephaniah
-        14,  // Haggai
-        8,   // Zechariah
+        3,   // Zephaniah
+        2,   // Haggai
+        14,  // Zechariah
         4,   // Malachi
         28,  // Matthew
         16,  // Mark
@@ -216,62 +728,1197 @@ impl BibleBook {
         1,   // 3 John
         1,   // Jude
         22,  // Revelation
+        14,  // Tobit
+        16,  // Judith
+        16,  // Additions to Esther
+        19,  // Wisdom of Solomon
+        51,  // Sirach
+        6,   // Baruch
+        1,   // Letter of Jeremiah
+        1,   // Prayer of Azariah
+        1,   // Susanna
+        1,   // Bel and the Dragon
+        16,  // 1 Maccabees
+        15,  // 2 Maccabees
+        7,   // 3 Maccabees
+        18,  // 4 Maccabees
+        9,   // 1 Esdras
+        16,  // 2 Esdras
+        1,   // Prayer of Manasseh
+        1,   // Psalm 151
+        108, // Enoch
+        50,  // Jubilees
+        36,  // 1 Meqabyan
+        21,  // 2 Meqabyan
+        10,  // 3 Meqabyan
     ];
+
+    //Verses in each chapter of the 66 books in the KJV versification, which TheWord files follow
+    const VERSE_COUNT: [&[u8]; 67] = [
+        &[], // Invalid
+        &[
+            31, 25, 24, 26, 32, 22, 24, 22, 29, 32, 32, 20, 18, 24, 21, 16, 27, 33, 38, 18, 34, 24,
+            20, 67, 34, 35, 46, 22, 35, 43, 55, 32, 20, 31, 29, 43, 36, 30, 23, 23, 57, 38, 34, 34,
+            28, 34, 31, 22, 33, 26,
+        ], // Genesis
+        &[
+            22, 25, 22, 31, 23, 30, 25, 32, 35, 29, 10, 51, 22, 31, 27, 36, 16, 27, 25, 26, 36, 31,
+            33, 18, 40, 37, 21, 43, 46, 38, 18, 35, 23, 35, 35, 38, 29, 31, 43, 38,
+        ], // Exodus
+        &[
+            17, 16, 17, 35, 19, 30, 38, 36, 24, 20, 47, 8, 59, 57, 33, 34, 16, 30, 37, 27, 24, 33,
+            44, 23, 55, 46, 34,
+        ], // Leviticus
+        &[
+            54, 34, 51, 49, 31, 27, 89, 26, 23, 36, 35, 16, 33, 45, 41, 50, 13, 32, 22, 29, 35, 41,
+            30, 25, 18, 65, 23, 31, 40, 16, 54, 42, 56, 29, 34, 13,
+        ], // Numbers
+        &[
+            46, 37, 29, 49, 33, 25, 26, 20, 29, 22, 32, 32, 18, 29, 23, 22, 20, 22, 21, 20, 23, 30,
+            25, 22, 19, 19, 26, 68, 29, 20, 30, 52, 29, 12,
+        ], // Deuteronomy
+        &[
+            18, 24, 17, 24, 15, 27, 26, 35, 27, 43, 23, 24, 33, 15, 63, 10, 18, 28, 51, 9, 45, 34,
+            16, 33,
+        ], // Joshua
+        &[
+            36, 23, 31, 24, 31, 40, 25, 35, 57, 18, 40, 15, 25, 20, 20, 31, 13, 31, 30, 48, 25,
+        ], // Judges
+        &[22, 23, 18, 22], // Ruth
+        &[
+            28, 36, 21, 22, 12, 21, 17, 22, 27, 27, 15, 25, 23, 52, 35, 23, 58, 30, 24, 42, 15, 23,
+            29, 22, 44, 25, 12, 25, 11, 31, 13,
+        ], // 1 Samuel
+        &[
+            27, 32, 39, 12, 25, 23, 29, 18, 13, 19, 27, 31, 39, 33, 37, 23, 29, 33, 43, 26, 22, 51,
+            39, 25,
+        ], // 2 Samuel
+        &[
+            53, 46, 28, 34, 18, 38, 51, 66, 28, 29, 43, 33, 34, 31, 34, 34, 24, 46, 21, 43, 29, 53,
+        ], // 1 Kings
+        &[
+            18, 25, 27, 44, 27, 33, 20, 29, 37, 36, 21, 21, 25, 29, 38, 20, 41, 37, 37, 21, 26, 20,
+            37, 20, 30,
+        ], // 2 Kings
+        &[
+            54, 55, 24, 43, 26, 81, 40, 40, 44, 14, 47, 40, 14, 17, 29, 43, 27, 17, 19, 8, 30, 19,
+            32, 31, 31, 32, 34, 21, 30,
+        ], // 1 Chronicles
+        &[
+            17, 18, 17, 22, 14, 42, 22, 18, 31, 19, 23, 16, 22, 15, 19, 14, 19, 34, 11, 37, 20, 12,
+            21, 27, 28, 23, 9, 27, 36, 27, 21, 33, 25, 33, 27, 23,
+        ], // 2 Chronicles
+        &[11, 70, 13, 24, 17, 22, 28, 36, 15, 44], // Ezra
+        &[11, 20, 32, 23, 19, 19, 73, 18, 38, 39, 36, 47, 31], // Nehemiah
+        &[22, 23, 15, 17, 14, 14, 10, 17, 32, 3], // Esther
+        &[
+            22, 13, 26, 21, 27, 30, 21, 22, 35, 22, 20, 25, 28, 22, 35, 22, 16, 21, 29, 29, 34, 30,
+            17, 25, 6, 14, 23, 28, 25, 31, 40, 22, 33, 37, 16, 33, 24, 41, 30, 24, 34, 17,
+        ], // Job
+        &[
+            6, 12, 8, 8, 12, 10, 17, 9, 20, 18, 7, 8, 6, 7, 5, 11, 15, 50, 14, 9, 13, 31, 6, 10,
+            22, 12, 14, 9, 11, 12, 24, 11, 22, 22, 28, 12, 40, 22, 13, 17, 13, 11, 5, 26, 17, 11,
+            9, 14, 20, 23, 19, 9, 6, 7, 23, 13, 11, 11, 17, 12, 8, 12, 11, 10, 13, 20, 7, 35, 36,
+            5, 24, 20, 28, 23, 10, 12, 20, 72, 13, 19, 16, 8, 18, 12, 13, 17, 7, 18, 52, 17, 16,
+            15, 5, 23, 11, 13, 12, 9, 9, 5, 8, 28, 22, 35, 45, 48, 43, 13, 31, 7, 10, 10, 9, 8, 18,
+            19, 2, 29, 176, 7, 8, 9, 4, 8, 5, 6, 5, 6, 8, 8, 3, 18, 3, 3, 21, 26, 9, 8, 24, 13, 10,
+            7, 12, 15, 21, 10, 20, 14, 9, 6,
+        ], // Psalms
+        &[
+            33, 22, 35, 27, 23, 35, 27, 36, 18, 32, 31, 28, 25, 35, 33, 33, 28, 24, 29, 30, 31, 29,
+            35, 34, 28, 28, 27, 28, 27, 33, 31,
+        ], // Proverbs
+        &[18, 26, 22, 16, 20, 12, 29, 17, 18, 20, 10, 14], // Ecclesiastes
+        &[17, 17, 11, 16, 16, 13, 13, 14], // Song of Solomon
+        &[
+            31, 22, 26, 6, 30, 13, 25, 22, 21, 34, 16, 6, 22, 32, 9, 14, 14, 7, 25, 6, 17, 25, 18,
+            23, 12, 21, 13, 29, 24, 33, 9, 20, 24, 17, 10, 22, 38, 22, 8, 31, 29, 25, 28, 28, 25,
+            13, 15, 22, 26, 11, 23, 15, 12, 17, 13, 12, 21, 14, 21, 22, 11, 12, 19, 12, 25, 24,
+        ], // Isaiah
+        &[
+            19, 37, 25, 31, 31, 30, 34, 22, 26, 25, 23, 17, 27, 22, 21, 21, 27, 23, 15, 18, 14, 30,
+            40, 10, 38, 24, 22, 17, 32, 24, 40, 44, 26, 22, 19, 32, 21, 28, 18, 16, 18, 22, 13, 30,
+            5, 28, 7, 47, 39, 46, 64, 34,
+        ], // Jeremiah
+        &[22, 22, 66, 22, 22], // Lamentations
+        &[
+            28, 10, 27, 17, 17, 14, 27, 18, 11, 22, 25, 28, 23, 23, 8, 63, 24, 32, 14, 49, 32, 31,
+            49, 27, 17, 21, 36, 26, 21, 26, 18, 32, 33, 31, 15, 38, 28, 23, 29, 49, 26, 20, 27, 31,
+            25, 24, 23, 35,
+        ], // Ezekiel
+        &[21, 49, 30, 37, 31, 28, 28, 27, 27, 21, 45, 13], // Daniel
+        &[11, 23, 5, 19, 15, 11, 16, 14, 17, 15, 12, 14, 16, 9], // Hosea
+        &[20, 32, 21], // Joel
+        &[15, 16, 15, 13, 27, 14, 17, 14, 15], // Amos
+        &[21], // Obadiah
+        &[17, 10, 10, 11], // Jonah
+        &[16, 13, 12, 13, 15, 16, 20], // Micah
+        &[15, 13, 19], // Nahum
+        &[17, 20, 19], // Habakkuk
+        &[18, 15, 20], // Zephaniah
+        &[15, 23], // Haggai
+        &[21, 13, 10, 14, 11, 15, 14, 23, 17, 12, 17, 14, 9, 21], // Zechariah
+        &[14, 17, 18, 6], // Malachi
+        &[
+            25, 23, 17, 25, 48, 34, 29, 34, 38, 42, 30, 50, 58, 36, 39, 28, 27, 35, 30, 34, 46, 46,
+            39, 51, 46, 75, 66, 20,
+        ], // Matthew
+        &[
+            45, 28, 35, 41, 43, 56, 37, 38, 50, 52, 33, 44, 37, 72, 47, 20,
+        ], // Mark
+        &[
+            80, 52, 38, 44, 39, 49, 50, 56, 62, 42, 54, 59, 35, 35, 32, 31, 37, 43, 48, 47, 38, 71,
+            56, 53,
+        ], // Luke
+        &[
+            51, 25, 36, 54, 47, 71, 53, 59, 41, 42, 57, 50, 38, 31, 27, 33, 26, 40, 42, 31, 25,
+        ], // John
+        &[
+            26, 47, 26, 37, 42, 15, 60, 40, 43, 48, 30, 25, 52, 28, 41, 40, 34, 28, 41, 38, 40, 30,
+            35, 27, 27, 32, 44, 31,
+        ], // Acts
+        &[
+            32, 29, 31, 25, 21, 23, 25, 39, 33, 21, 36, 21, 14, 23, 33, 27,
+        ], // Romans
+        &[
+            31, 16, 23, 21, 13, 20, 40, 13, 27, 33, 34, 31, 13, 40, 58, 24,
+        ], // 1 Corinthians
+        &[24, 17, 18, 18, 21, 18, 16, 24, 15, 18, 33, 21, 14], // 2 Corinthians
+        &[24, 21, 29, 31, 26, 18], // Galatians
+        &[23, 22, 21, 32, 33, 24], // Ephesians
+        &[30, 30, 21, 23], // Philippians
+        &[29, 23, 25, 18], // Colossians
+        &[10, 20, 13, 18, 28], // 1 Thessalonians
+        &[12, 17, 18], // 2 Thessalonians
+        &[20, 15, 16, 16, 25, 21], // 1 Timothy
+        &[18, 26, 17, 22], // 2 Timothy
+        &[16, 15, 15], // Titus
+        &[25], // Philemon
+        &[14, 18, 19, 16, 14, 20, 28, 13, 28, 39, 40, 29, 25], // Hebrews
+        &[27, 26, 18, 17, 20], // James
+        &[25, 25, 22, 19, 14], // 1 Peter
+        &[21, 22, 18], // 2 Peter
+        &[10, 29, 24, 21, 21], // 1 John
+        &[13], // 2 John
+        &[14], // 3 John
+        &[25], // Jude
+        &[
+            20, 29, 22, 11, 14, 17, 17, 13, 21, 11, 19, 17, 18, 20, 8, 21, 18, 24, 21, 15, 27, 21,
+        ], // Revelation
+    ];
+}
+
+//"1samuel", names are typed in lower case and without spaces in the book menu
+fn menu_name(name: &str) -> String {
+    name.replace([' ', '.'], "").to_lowercase()
+}
+
+impl FromStr for BibleBook {
+    type Err = Error;
+
+    //Full name, OSIS id or any prefix that only one book starts with
+    fn from_str(name: &str) -> Result<Self> {
+        parse_book(name)
+            .or_else(|| BibleBook::from_osis_id(name))
+            .ok_or_else(|| Error::UnknownBook(name.to_string()))
+    }
+}
+
+impl TryFrom<u8> for BibleBook {
+    type Error = Error;
+
+    //Book number in canonical order, Genesis is 1
+    fn try_from(number: u8) -> Result<Self> {
+        (number as usize)
+            .checked_sub(1)
+            .and_then(|i| BibleBook::ALL.get(i))
+            .copied()
+            .ok_or_else(|| Error::UnknownBook(number.to_string()))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Testament {
+    Old,
+    New,
+}
+
+impl Testament {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Testament::Old => "Old Testament",
+            Testament::New => "New Testament",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Genre {
+    Law,
+    History,
+    Wisdom,
+    MajorProphets,
+    MinorProphets,
+    Gospels,
+    Epistles,
+    Apocalyptic,
+}
+
+impl Genre {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Genre::Law => "Law",
+            Genre::History => "History",
+            Genre::Wisdom => "Wisdom",
+            Genre::MajorProphets => "Major Prophets",
+            Genre::MinorProphets => "Minor Prophets",
+            Genre::Gospels => "Gospels",
+            Genre::Epistles => "Epistles",
+            Genre::Apocalyptic => "Apocalyptic",
+        }
+    }
+}
+
+//The books a church reads as scripture and their order. Translations are read under
+//the smallest canon that has all their books
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Canon {
+    Protestant,
+    Catholic,
+    Orthodox,
+    Ethiopian,
+}
+
+impl Canon {
+    pub const ALL: [Canon; 4] = [
+        Canon::Protestant,
+        Canon::Catholic,
+        Canon::Orthodox,
+        Canon::Ethiopian,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Canon::Protestant => "Protestant",
+            Canon::Catholic => "Catholic",
+            Canon::Orthodox => "Orthodox",
+            Canon::Ethiopian => "Ethiopian",
+        }
+    }
+
+    //Books the canon adds to the Protestant one, each group after the book it follows
+    fn additions(&self) -> &'static [(BibleBook, &'static [BibleBook])] {
+        use BibleBook::*;
+
+        match self {
+            Canon::Protestant => &[],
+            Canon::Catholic => &[
+                (Nehemiah, &[Tobit, Judith]),
+                (
+                    Esther,
+                    &[AdditionsToEsther, FirstMaccabees, SecondMaccabees],
+                ),
+                (SongOfSolomon, &[Wisdom, Sirach]),
+                (Lamentations, &[Baruch, LetterOfJeremiah]),
+                (Daniel, &[PrayerOfAzariah, Susanna, BelAndTheDragon]),
+            ],
+            Canon::Orthodox => &[
+                (SecondChronicles, &[PrayerOfManasseh, FirstEsdras]),
+                (Nehemiah, &[Tobit, Judith]),
+                (
+                    Esther,
+                    &[
+                        AdditionsToEsther,
+                        FirstMaccabees,
+                        SecondMaccabees,
+                        ThirdMaccabees,
+                    ],
+                ),
+                (Psalms, &[Psalm151]),
+                (SongOfSolomon, &[Wisdom, Sirach]),
+                (Lamentations, &[Baruch, LetterOfJeremiah]),
+                (Daniel, &[PrayerOfAzariah, Susanna, BelAndTheDragon]),
+                //The appendix of Greek and Slavonic Bibles
+                (Malachi, &[FourthMaccabees, SecondEsdras]),
+            ],
+            Canon::Ethiopian => &[
+                (
+                    SecondChronicles,
+                    &[PrayerOfManasseh, Jubilees, Enoch, FirstEsdras],
+                ),
+                (Nehemiah, &[SecondEsdras, Tobit, Judith]),
+                (
+                    Esther,
+                    &[
+                        AdditionsToEsther,
+                        FirstMeqabyan,
+                        SecondMeqabyan,
+                        ThirdMeqabyan,
+                    ],
+                ),
+                (Psalms, &[Psalm151]),
+                (SongOfSolomon, &[Wisdom, Sirach]),
+                (Lamentations, &[Baruch, LetterOfJeremiah]),
+                (Daniel, &[PrayerOfAzariah, Susanna, BelAndTheDragon]),
+            ],
+        }
+    }
+
+    //Books of the canon in its order
+    pub fn books(&self) -> Vec<BibleBook> {
+        let additions = self.additions();
+        let mut books = vec![];
+        for book in BibleBook::all().filter(|book| !book.is_deuterocanonical()) {
+            books.push(book);
+            for (_, added) in additions.iter().filter(|(after, _)| *after == book) {
+                books.extend_from_slice(added);
+            }
+        }
+        books
+    }
+
+    pub fn contains(&self, book: BibleBook) -> bool {
+        !book.is_deuterocanonical()
+            || self
+                .additions()
+                .iter()
+                .any(|(_, added)| added.contains(&book))
+    }
+
+    //The first canon with all the books, or else the one missing the fewest
+    pub fn of(books: &[BibleBook]) -> Canon {
+        let missing = |canon: &Canon| books.iter().filter(|book| !canon.contains(**book)).count();
+        Canon::ALL
+            .into_iter()
+            .min_by_key(missing)
+            .unwrap_or(Canon::Protestant)
+    }
+}
+
+//Orders the books can be read in. The canon decides which books there are, the order
+//only arranges them, book ids stay as stored
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Order {
+    //As the translation's canon has them
+    Canon,
+    Protestant,
+    Tanakh,
+    Catholic,
+    Chronological,
+}
+
+impl Order {
+    pub const ALL: [Order; 5] = [
+        Order::Canon,
+        Order::Protestant,
+        Order::Tanakh,
+        Order::Catholic,
+        Order::Chronological,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Order::Canon => "Canon",
+            Order::Protestant => "Protestant",
+            Order::Tanakh => "Tanakh",
+            Order::Catholic => "Catholic",
+            Order::Chronological => "Chronological",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Order::Canon => "as the translation's canon orders them",
+            Order::Protestant => "English Protestant, the deuterocanon between the testaments",
+            Order::Tanakh => "Hebrew Tanakh, Torah, Prophets and Writings",
+            Order::Catholic => "Catholic, the deuterocanon among the Old Testament",
+            Order::Chronological => "roughly as written or taking place",
+        }
+    }
+
+    //Name in any case, "tanakh"
+    pub fn parse(name: &str) -> Option<Order> {
+        Order::ALL
+            .into_iter()
+            .find(|order| order.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    //The one after this, for cycling through them
+    pub fn next(&self) -> Order {
+        let i = Order::ALL
+            .iter()
+            .position(|order| order == self)
+            .unwrap_or(0);
+        Order::ALL[(i + 1) % Order::ALL.len()]
+    }
+
+    //The canon's books in this order
+    pub fn arrange(&self, canon: Canon) -> Arrangement {
+        let books = match self {
+            Order::Canon => canon.books(),
+            Order::Protestant => with_deuterocanon(&Canon::Protestant.books()),
+            //The Hebrew Bible ends with Chronicles, the New Testament follows as usual
+            Order::Tanakh => with_deuterocanon(
+                &[
+                    &TANAKH[..],
+                    &BibleBook::ALL
+                        [BibleBook::Matthew as usize - 1..BibleBook::Revelation as usize],
+                ]
+                .concat(),
+            ),
+            Order::Catholic => with_deuterocanon(&Canon::Catholic.books()),
+            Order::Chronological => with_deuterocanon(&CHRONOLOGICAL),
+        };
+        Arrangement {
+            books: books
+                .into_iter()
+                .filter(|book| canon.contains(*book))
+                .collect(),
+        }
+    }
+}
+
+//The order with the deuterocanonical books it lacks put between the testaments
+fn with_deuterocanon(order: &[BibleBook]) -> Vec<BibleBook> {
+    let new_testament = order
+        .iter()
+        .position(|book| *book == BibleBook::Matthew)
+        .unwrap_or(order.len());
+    let missing =
+        BibleBook::all().filter(|book| book.is_deuterocanonical() && !order.contains(book));
+
+    let mut books = order[..new_testament].to_vec();
+    books.extend(missing);
+    books.extend_from_slice(&order[new_testament..]);
+    books
+}
+
+//The Hebrew Bible's order, Torah, Nevi'im and Ketuvim
+const TANAKH: [BibleBook; 39] = {
+    use BibleBook::*;
+    [
+        Genesis,
+        Exodus,
+        Leviticus,
+        Numbers,
+        Deuteronomy,
+        Joshua,
+        Judges,
+        FirstSamuel,
+        SecondSamuel,
+        FirstKings,
+        SecondKings,
+        Isaiah,
+        Jeremiah,
+        Ezekiel,
+        Hosea,
+        Joel,
+        Amos,
+        Obadiah,
+        Jonah,
+        Micah,
+        Nahum,
+        Habakkuk,
+        Zephaniah,
+        Haggai,
+        Zechariah,
+        Malachi,
+        Psalms,
+        Proverbs,
+        Job,
+        SongOfSolomon,
+        Ruth,
+        Lamentations,
+        Ecclesiastes,
+        Esther,
+        Daniel,
+        Ezra,
+        Nehemiah,
+        FirstChronicles,
+        SecondChronicles,
+    ]
+};
+
+//Books roughly in the order they were written or take place in
+const CHRONOLOGICAL: [BibleBook; 66] = {
+    use BibleBook::*;
+    [
+        Genesis,
+        Job,
+        Exodus,
+        Leviticus,
+        Numbers,
+        Deuteronomy,
+        Joshua,
+        Judges,
+        Ruth,
+        FirstSamuel,
+        SecondSamuel,
+        Psalms,
+        FirstChronicles,
+        FirstKings,
+        Proverbs,
+        Ecclesiastes,
+        SongOfSolomon,
+        SecondKings,
+        SecondChronicles,
+        Obadiah,
+        Joel,
+        Jonah,
+        Amos,
+        Hosea,
+        Isaiah,
+        Micah,
+        Nahum,
+        Zephaniah,
+        Habakkuk,
+        Jeremiah,
+        Lamentations,
+        Ezekiel,
+        Daniel,
+        Ezra,
+        Haggai,
+        Zechariah,
+        Esther,
+        Nehemiah,
+        Malachi,
+        Matthew,
+        Mark,
+        Luke,
+        John,
+        Acts,
+        James,
+        Galatians,
+        FirstThessalonians,
+        SecondThessalonians,
+        FirstCorinthians,
+        SecondCorinthians,
+        Romans,
+        Ephesians,
+        Philippians,
+        Colossians,
+        Philemon,
+        FirstTimothy,
+        Titus,
+        FirstPeter,
+        Hebrews,
+        SecondTimothy,
+        SecondPeter,
+        Jude,
+        FirstJohn,
+        SecondJohn,
+        ThirdJohn,
+        Revelation,
+    ]
+};
+
+//A canon's books in a reading order, what the menu lists and navigation goes through
+pub struct Arrangement {
+    books: Vec<BibleBook>,
+}
+
+impl Arrangement {
+    pub fn books(&self) -> &[BibleBook] {
+        &self.books
+    }
+
+    pub fn position(&self, book: BibleBook) -> Option<usize> {
+        self.books.iter().position(|other| *other == book)
+    }
+
+    //Book after this one, None at the end or for books not in the arrangement
+    pub fn next(&self, book: BibleBook) -> Option<BibleBook> {
+        self.books.get(self.position(book)? + 1).copied()
+    }
+
+    //Book before this one, None at the start or for books not in the arrangement
+    pub fn prev(&self, book: BibleBook) -> Option<BibleBook> {
+        self.position(book)?.checked_sub(1).map(|i| self.books[i])
+    }
+
+    //Chapters in the books before this one
+    pub fn chapter_offset(&self, book: BibleBook) -> u16 {
+        self.books
+            .iter()
+            .take_while(|other| **other != book)
+            .map(|other| other.max_chapter_count() as u16)
+            .sum()
+    }
+
+    pub fn total_chapters(&self) -> u16 {
+        self.books
+            .iter()
+            .map(|book| book.max_chapter_count() as u16)
+            .sum()
+    }
+}
+
+//A single verse with its text
+pub struct Verse {
+    pub book: BibleBook,
+    pub chapter: u8,
+    pub verse: u8,
+    pub text: String,
+}
+
+impl Verse {
+    //"John 3:16"
+    pub fn reference(&self) -> String {
+        format!("{} {}:{}", self.book.as_str(), self.chapter, self.verse)
+    }
 }
 
 pub struct Database {
-    connection: Connection,
+    source: Arc<dyn BibleSource>,
     cache: Vec<Vec<String>>,
     pub book: BibleBook,
+    //Name of the translation, one of the source's
+    pub translation: String,
+    //Whole translation in memory, answers instead of the source when it's for the current translation
+    index: Option<BibleIndex>,
 }
 
 impl Database {
-    pub fn connect_and_load(book: BibleBook) -> Result<Self, ()> {
-        let path = env::current_exe()
+    //Opens ASV.db next to the executable, or the .vbin files there without it
+    pub fn connect() -> Result<Self> {
+        let dir = env::current_exe()
             .ok()
-            .and_then(|path| path.parent().map(|dir| dir.join("ASV.db")))
+            .and_then(|path| path.parent().map(|dir| dir.to_path_buf()))
             .expect("Could not get path to dir.");
 
-        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
-            .expect("Could not connect to DB. The DB has to be in the same dir as the executable.");
+        #[cfg(feature = "sqlite")]
+        if dir.join("ASV.db").exists() {
+            return Database::open_sqlite(&dir.join("ASV.db"));
+        }
+
+        let mut indexes = vec![];
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "vbin")
+            {
+                indexes.push(compiled::open(&path)?);
+            }
+        }
+        if indexes.is_empty() {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no ASV.db or .vbin files in {}", dir.display()),
+            )));
+        }
+
+        let source = Memory::new(&format!("compiled files in {}", dir.display()), indexes);
+        Database::new(source)
+    }
+
+    //A scrollmapper DB anywhere, for compiling it
+    #[cfg(feature = "sqlite")]
+    pub fn open_sqlite(path: &Path) -> Result<Self> {
+        Database::new(Sqlite::open(path)?)
+    }
+
+    //Reads from any source, starting on ASV or else its first translation
+    pub fn new(source: impl BibleSource + 'static) -> Result<Self> {
+        let translations = source.translations()?;
+        let translation = translations
+            .iter()
+            .find(|translation| *translation == "ASV")
+            .or(translations.first())
+            .cloned()
+            .ok_or_else(|| Error::UnknownTranslation("any".to_string()))?;
 
-        let mut db = Database {
-            connection,
+        Ok(Database {
+            source: Arc::new(source),
             cache: vec![],
-            book,
-        };
-        db.load_book(book)?;
+            book: BibleBook::Genesis,
+            translation,
+            index: None,
+        })
+    }
 
-        Ok(db)
+    //The source for reading from other threads, with the translation to read
+    pub fn source(&self) -> Arc<dyn BibleSource> {
+        Arc::clone(&self.source)
+    }
+
+    //Reads the whole current translation for an in-memory index
+    pub fn load_index(&self) -> Result<BibleIndex> {
+        self.source.load_index(&self.translation)
+    }
+
+    pub fn set_index(&mut self, index: BibleIndex) {
+        self.index = Some(index);
+    }
+
+    //The in-memory index, if it is of the current translation
+    pub fn index(&self) -> Option<&BibleIndex> {
+        self.index
+            .as_ref()
+            .filter(|index| index.translation == self.translation)
+            .or_else(|| self.source.index(&self.translation))
     }
 
     pub fn get_chapter(&self, num: u8) -> Option<&Vec<String>> {
         if self.book.max_chapter_count() >= num && num > 0 {
-            return Some(&(self.cache[num as usize - 1]));
+            return self.cache.get(num as usize - 1);
         }
 
         None
     }
 
-    pub fn load_book(&mut self, book: BibleBook) -> Result<(), ()> {
+    //Makes the book current, until set_chapters fills the cache its chapters are read one by one
+    pub fn switch_book(&mut self, book: BibleBook) {
         self.cache = vec![];
         self.book = book;
+    }
+
+    //Chapters of the book as the source gives them, tags are taken out
+    pub fn set_chapters(&mut self, chapters: Vec<Vec<String>>) {
+        self.cache = chapters.into_iter().map(untagged).collect();
+    }
+
+    //Every chapter of the book, to fill the cache with
+    pub fn fetch_book(&self, book: BibleBook) -> Result<Vec<Vec<String>>> {
+        if self.index().is_some() {
+            return (1..=book.max_chapter_count())
+                .map(|chapter| self.fetch_chapter(book, chapter))
+                .collect();
+        }
+
+        Ok(self
+            .source
+            .book(&self.translation, book)?
+            .into_iter()
+            .map(untagged)
+            .collect())
+    }
+
+    //Reads a chapter straight from the source, bypassing the cache of the loaded book
+    pub fn fetch_chapter(&self, book: BibleBook, chapter: u8) -> Result<Vec<String>> {
+        Ok(untagged(self.tagged_chapter(book, chapter)?))
+    }
 
-        let mut stmt = self
-            .connection
-            .prepare_cached("SELECT text FROM ASV_verses WHERE book_id = ? AND chapter = ?")
-            .expect("Couldn't prepare statement");
+    //A chapter as the source has it, with the Strong's numbers of a tagged translation
+    pub fn tagged_chapter(&self, book: BibleBook, chapter: u8) -> Result<Vec<String>> {
+        if let Some(index) = self.index() {
+            return Ok(index
+                .chapter(book, chapter)
+                .into_iter()
+                .map(String::from)
+                .collect());
+        }
 
-        for chapter in 1..=self.book.max_chapter_count() {
-            let verses: Vec<String> = stmt
-                .query_map([book as i32, chapter as i32], |row| row.get(0))
-                .map_err(|_| ())? // Extract `text` column
-                .collect::<Result<Vec<String>, _>>()
-                .map_err(|_| ())?;
+        self.source.chapter(&self.translation, book, chapter)
+    }
 
-            self.cache.push(verses);
+    //Verses first to last of a chapter, as far as it goes
+    pub fn fetch_range(
+        &self,
+        book: BibleBook,
+        chapter: u8,
+        first: u8,
+        last: u8,
+    ) -> Result<Vec<Verse>> {
+        if self.index().is_some() {
+            let texts = self.fetch_chapter(book, chapter)?;
+            return Ok(source::verses_between(book, chapter, texts, first, last));
         }
 
-        Ok(())
+        Ok(self
+            .source
+            .range(&self.translation, book, chapter, first, last)?
+            .into_iter()
+            .map(untag)
+            .collect())
+    }
+
+    pub fn get_verse(&self, book: BibleBook, chapter: u8, verse: u8) -> Result<Option<String>> {
+        if let Some(index) = self.index() {
+            return Ok(index
+                .verse(book, chapter, verse)
+                .map(|text| strongs::strip(text).into_owned()));
+        }
+
+        Ok(self
+            .source
+            .verse(&self.translation, book, chapter, verse)?
+            .map(|text| strongs::strip(&text).into_owned()))
+    }
+
+    //Number of verses in the books from first to last
+    pub fn count_verses(&self, first: BibleBook, last: BibleBook) -> Result<u32> {
+        if let Some(index) = self.index() {
+            return Ok(index.count_verses(first, last));
+        }
+
+        self.source.count_verses(&self.translation, first, last)
+    }
+
+    //The nth verse (from 0) of the books from first to last
+    pub fn nth_verse(&self, first: BibleBook, last: BibleBook, n: u32) -> Result<Verse> {
+        if let Some(index) = self.index() {
+            return index
+                .nth_verse(first, last, n)
+                .map(untag)
+                .ok_or(Error::NoSuchVerse);
+        }
+
+        self.source
+            .nth_verse(&self.translation, first, last, n)
+            .map(untag)
+    }
+
+    //Verses of the books from first to last containing the words, case insensitive
+    pub fn search(
+        &self,
+        words: &str,
+        first: BibleBook,
+        last: BibleBook,
+        limit: u32,
+    ) -> Result<Vec<Verse>> {
+        let verses = match self.index() {
+            Some(index) => index.search(words, first, last, limit),
+            None => self
+                .source
+                .search(&self.translation, words, first, last, limit)?,
+        };
+        Ok(verses.into_iter().map(untag).collect())
+    }
+
+    //Verses of the books from first to last with a word of the Strong's number
+    pub fn occurrences(
+        &self,
+        strong: Strong,
+        first: BibleBook,
+        last: BibleBook,
+    ) -> Result<Vec<Verse>> {
+        let verses = match self.index() {
+            Some(index) => index.tagged(strong, first, last),
+            None => self.source.tagged(&self.translation, strong, first, last)?,
+        };
+        Ok(verses.into_iter().map(untag).collect())
+    }
+
+    //Translation of the source with the name, ignoring case. Sources may put names
+    //in SQL, so only ones found here may be used
+    pub fn find_translation(&self, name: &str) -> Result<Option<String>> {
+        Ok(self
+            .translations()?
+            .into_iter()
+            .find(|translation| translation.eq_ignore_ascii_case(name)))
+    }
+
+    pub fn translations(&self) -> Result<Vec<String>> {
+        self.source.translations()
+    }
+
+    //Canon of the current translation, from the books it has
+    pub fn canon(&self) -> Result<Canon> {
+        Ok(Canon::of(&self.source.books(&self.translation)?))
+    }
+
+    //Numbering the current translation shows its verses in
+    pub fn versification(&self) -> Result<Versification> {
+        self.source.versification(&self.translation)
+    }
+
+    //Language of the current translation, if known
+    pub fn language(&self) -> Result<Option<String>> {
+        self.source.language(&self.translation)
+    }
+}
+
+//Verse texts without the tags of a translation with Strong's numbers
+fn untagged(texts: Vec<String>) -> Vec<String> {
+    texts
+        .into_iter()
+        .map(|text| match strongs::strip(&text) {
+            Cow::Borrowed(_) => text,
+            Cow::Owned(stripped) => stripped,
+        })
+        .collect()
+}
+
+fn untag(verse: Verse) -> Verse {
+    Verse {
+        text: strongs::strip(&verse.text).into_owned(),
+        ..verse
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+    Io(io::Error),
+    //A compiled file that is damaged, or of a version this build can't read
+    Format(String),
+    UnknownTranslation(String),
+    UnknownBook(String),
+    NoSuchVerse,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Error::Sqlite(err) => write!(f, "{}", err),
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Format(err) => write!(f, "{}", err),
+            Error::UnknownTranslation(name) => write!(f, "no translation {}", name),
+            Error::UnknownBook(name) => write!(f, "unknown book {}", name),
+            Error::NoSuchVerse => write!(f, "no such verse"),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chapter_and_verse_counts() {
+        let protestant = &BibleBook::ALL[..66];
+        let chapters: u16 = protestant
+            .iter()
+            .map(|book| book.max_chapter_count() as u16)
+            .sum();
+        assert_eq!(chapters, 1189);
+        assert_eq!(
+            BibleBook::all()
+                .map(|book| book.max_chapter_count() as u16)
+                .sum::<u16>(),
+            BibleBook::TOTAL_CHAPTERS
+        );
+        assert_eq!(BibleBook::Genesis.chapter_offset(), 0);
+        assert_eq!(BibleBook::Exodus.chapter_offset(), 50);
+        assert_eq!(BibleBook::Tobit.chapter_offset(), 1189);
+
+        for book in protestant {
+            let counts =
+                (1..=book.max_chapter_count()).map(|chapter| book.kjv_verse_count(chapter));
+            assert!(counts.clone().all(|count| count > 0), "{:?}", book);
+            assert_eq!(
+                counts.map(|count| count as u16).sum::<u16>(),
+                book.kjv_verse_total()
+            );
+            assert_eq!(book.kjv_verse_count(book.max_chapter_count() + 1), 0);
+        }
+        let verses: u32 = protestant
+            .iter()
+            .map(|book| book.kjv_verse_total() as u32)
+            .sum();
+        assert_eq!(verses, 31102);
+        assert_eq!(BibleBook::Psalms.kjv_verse_count(119), 176);
+        assert_eq!(BibleBook::Tobit.kjv_verse_total(), 0);
+    }
+
+    #[test]
+    fn testaments_and_genres() {
+        assert_eq!(BibleBook::Malachi.testament(), Testament::Old);
+        assert_eq!(BibleBook::Matthew.testament(), Testament::New);
+        assert_eq!(BibleBook::Tobit.testament(), Testament::Old);
+        assert_eq!(
+            BibleBook::all()
+                .filter(|book| book.testament() == Testament::New)
+                .count(),
+            27
+        );
+
+        assert_eq!(BibleBook::Deuteronomy.genre(), Genre::Law);
+        assert_eq!(BibleBook::Joshua.genre(), Genre::History);
+        assert_eq!(BibleBook::Job.genre(), Genre::Wisdom);
+        assert_eq!(BibleBook::Daniel.genre(), Genre::MajorProphets);
+        assert_eq!(BibleBook::Hosea.genre(), Genre::MinorProphets);
+        assert_eq!(BibleBook::John.genre(), Genre::Gospels);
+        assert_eq!(BibleBook::Acts.genre(), Genre::History);
+        assert_eq!(BibleBook::Jude.genre(), Genre::Epistles);
+        assert_eq!(BibleBook::Revelation.genre(), Genre::Apocalyptic);
+        assert_eq!(BibleBook::Sirach.genre(), Genre::Wisdom);
+        assert_eq!(BibleBook::Baruch.genre(), Genre::MajorProphets);
+        assert_eq!(BibleBook::FirstMaccabees.genre(), Genre::History);
+        assert_eq!(BibleBook::Enoch.genre(), Genre::Apocalyptic);
+    }
+
+    #[test]
+    fn ids_and_abbreviations() {
+        for book in BibleBook::all() {
+            assert_eq!(BibleBook::from_osis_id(book.osis_id()), Some(book));
+            assert_eq!(BibleBook::from_usfm_code(book.usfm_code()), Some(book));
+            assert_eq!(BibleBook::try_from(book as u8).ok(), Some(book));
+        }
+        assert!(BibleBook::try_from(0).is_err());
+        assert!(BibleBook::try_from(BibleBook::LAST as u8 + 1).is_err());
+        assert_eq!(BibleBook::from_usfm_code("jhn"), Some(BibleBook::John));
+
+        assert_eq!(BibleBook::FirstSamuel.sbl_abbreviation(), "1 Sam");
+        assert_eq!(BibleBook::Genesis.sbl_abbreviation(), "Gen");
+        assert_eq!(BibleBook::PrayerOfManasseh.sbl_abbreviation(), "Pr Man");
+
+        assert_eq!(BibleBook::Malachi.paratext_number(), Some(39));
+        assert_eq!(BibleBook::Matthew.paratext_number(), Some(41));
+        assert_eq!(BibleBook::Enoch.paratext_number(), None);
+
+        assert_eq!(BibleBook::Genesis.prev(), None);
+        assert_eq!(BibleBook::Genesis.next(), Some(BibleBook::Exodus));
+        assert_eq!(BibleBook::LAST.next(), None);
+    }
+
+    #[test]
+    fn menu_prefixes() {
+        let books = [
+            BibleBook::Genesis,
+            BibleBook::Galatians,
+            BibleBook::John,
+            BibleBook::Jude,
+            BibleBook::Job,
+            BibleBook::FirstJohn,
+        ];
+        assert_eq!(
+            BibleBook::menu_prefixes(&books, Locale::english()),
+            ["ge", "ga", "joh", "ju", "job", "1"]
+        );
+    }
+
+    #[test]
+    fn canons() {
+        assert_eq!(Canon::Protestant.books(), BibleBook::ALL[..66]);
+        for canon in Canon::ALL {
+            let books = canon.books();
+            assert!(books.iter().all(|book| canon.contains(*book)));
+            assert_eq!(
+                books.len(),
+                BibleBook::all()
+                    .filter(|book| canon.contains(*book))
+                    .count()
+            );
+        }
+
+        let catholic = Canon::Catholic.books();
+        let after = |book| catholic[catholic.iter().position(|other| *other == book).unwrap() + 1];
+        assert_eq!(after(BibleBook::Nehemiah), BibleBook::Tobit);
+        assert_eq!(after(BibleBook::Daniel), BibleBook::PrayerOfAzariah);
+        assert!(!Canon::Catholic.contains(BibleBook::Psalm151));
+        assert!(Canon::Orthodox.contains(BibleBook::Psalm151));
+        assert!(Canon::Ethiopian.contains(BibleBook::Enoch));
+        assert!(!Canon::Orthodox.contains(BibleBook::Enoch));
+    }
+
+    //The smallest canon with every book, or the one missing the fewest
+    #[test]
+    fn canon_of_books() {
+        assert_eq!(Canon::of(&BibleBook::ALL[..66]), Canon::Protestant);
+        assert_eq!(Canon::of(&[BibleBook::Genesis]), Canon::Protestant);
+        assert_eq!(
+            Canon::of(&[BibleBook::Genesis, BibleBook::Tobit]),
+            Canon::Catholic
+        );
+        assert_eq!(
+            Canon::of(&[BibleBook::Tobit, BibleBook::Psalm151]),
+            Canon::Orthodox
+        );
+        assert_eq!(
+            Canon::of(&[BibleBook::Enoch, BibleBook::Jubilees]),
+            Canon::Ethiopian
+        );
+        assert_eq!(
+            Canon::of(&[
+                BibleBook::Tobit,
+                BibleBook::Psalm151,
+                BibleBook::FourthMaccabees,
+                BibleBook::Enoch
+            ]),
+            Canon::Orthodox
+        );
+        assert_eq!(Canon::of(&[]), Canon::Protestant);
+    }
+
+    #[test]
+    fn orders_keep_every_book() {
+        for canon in Canon::ALL {
+            let mut books = canon.books();
+            books.sort_by_key(|book| *book as u8);
+            for order in Order::ALL {
+                let mut arranged = order.arrange(canon).books().to_vec();
+                assert_eq!(arranged.len(), books.len(), "{:?} {:?}", order, canon);
+                arranged.sort_by_key(|book| *book as u8);
+                assert_eq!(arranged, books, "{:?} {:?}", order, canon);
+            }
+        }
+    }
+
+    #[test]
+    fn tanakh() {
+        let tanakh = Order::Tanakh.arrange(Canon::Protestant);
+        let books = tanakh.books();
+        assert_eq!(
+            books[..6],
+            [
+                BibleBook::Genesis,
+                BibleBook::Exodus,
+                BibleBook::Leviticus,
+                BibleBook::Numbers,
+                BibleBook::Deuteronomy,
+                BibleBook::Joshua,
+            ]
+        );
+        assert_eq!(books[38], BibleBook::SecondChronicles);
+        assert_eq!(books[39], BibleBook::Matthew);
+        assert_eq!(books[65], BibleBook::Revelation);
+        assert_eq!(tanakh.total_chapters(), 1189);
+
+        //The deuterocanon goes between the testaments
+        let books = Order::Tanakh.arrange(Canon::Catholic).books().to_vec();
+        assert_eq!(books[38], BibleBook::SecondChronicles);
+        assert_eq!(books[39], BibleBook::Tobit);
+        assert_eq!(books[books.len() - 27], BibleBook::Matthew);
+    }
+
+    #[test]
+    fn canon_order() {
+        let catholic = Order::Canon.arrange(Canon::Catholic);
+        assert_eq!(catholic.next(BibleBook::Nehemiah), Some(BibleBook::Tobit));
+        assert_eq!(catholic.prev(BibleBook::Tobit), Some(BibleBook::Nehemiah));
+        assert_eq!(catholic.next(BibleBook::Revelation), None);
+        assert_eq!(catholic.prev(BibleBook::Genesis), None);
+        assert_eq!(catholic.position(BibleBook::Enoch), None);
+        assert_eq!(catholic.next(BibleBook::Enoch), None);
+
+        let protestant = Order::Protestant.arrange(Canon::Catholic);
+        assert_eq!(protestant.next(BibleBook::Malachi), Some(BibleBook::Tobit));
+        assert_eq!(
+            protestant.chapter_offset(BibleBook::Exodus),
+            BibleBook::Genesis.max_chapter_count() as u16
+        );
+
+        let chronological = Order::Chronological.arrange(Canon::Protestant);
+        assert_ne!(chronological.books(), Canon::Protestant.books());
+    }
+
+    #[test]
+    fn order_names() {
+        assert_eq!(Order::parse(" tanakh "), Some(Order::Tanakh));
+        assert_eq!(Order::parse("talmud"), None);
+        for order in Order::ALL {
+            assert_eq!(Order::parse(order.as_str()), Some(order));
+        }
+        assert_eq!(Order::Chronological.next(), Order::Canon);
+        assert_eq!(Order::Canon.next(), Order::Protestant);
     }
 }
@@ -0,0 +1,93 @@
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use crossterm::event::{self, Event};
+
+use crate::verse::{
+    db::{self, BibleBook},
+    index::BibleIndex,
+    remote::Command,
+};
+
+//Everything the main loop wakes up for
+pub enum AppEvent {
+    Input(Event),
+    //Sent at the tick rate, drives animations and timers
+    Tick,
+    Remote(Command),
+    //A book read in the background, for the translation it was asked for
+    BookLoaded {
+        book: BibleBook,
+        translation: String,
+        chapters: db::Result<Vec<Vec<String>>>,
+    },
+    //A whole translation read into memory in the background
+    IndexLoaded {
+        index: db::Result<BibleIndex>,
+        took: Duration,
+    },
+}
+
+impl From<Command> for AppEvent {
+    fn from(command: Command) -> Self {
+        AppEvent::Remote(command)
+    }
+}
+
+//One queue that input, the ticker and background work all send to
+pub struct Events {
+    sender: Sender<AppEvent>,
+    receiver: Receiver<AppEvent>,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Events { sender, receiver }
+    }
+
+    //Starts reading the terminal and ticking, once the terminal is set up
+    pub fn start(&self, tick_rate: Duration) {
+        let sender = self.sender();
+        thread::spawn(move || {
+            while let Ok(event) = event::read() {
+                if sender.send(AppEvent::Input(event)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let sender = self.sender();
+        thread::spawn(move || {
+            loop {
+                thread::sleep(tick_rate);
+                if sender.send(AppEvent::Tick).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    pub fn sender(&self) -> Sender<AppEvent> {
+        self.sender.clone()
+    }
+
+    //Waits for the next event
+    pub fn next(&self) -> Option<AppEvent> {
+        self.receiver.recv().ok()
+    }
+
+    //Next event if one is already queued
+    pub fn try_next(&self) -> Option<AppEvent> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,588 @@
+use std::{fs, path::Path};
+
+use crate::verse::{
+    compiled::crc32,
+    date::Date,
+    db::{BibleBook, Database, Result},
+    export::{Annotations, escape_xml},
+    versification::Versification,
+};
+
+/*
+EPUB 3, a zip of XHTML pages with a package document listing them:
+
+    mimetype                    "application/epub+zip", first and uncompressed
+    META-INF/container.xml      where the package document is
+    OEBPS/content.opf           metadata, the files and their reading order
+    OEBPS/nav.xhtml             table of contents, books then chapters
+    OEBPS/toc.ncx               the same for EPUB 2 readers
+    OEBPS/style.css
+    OEBPS/<osis id>.xhtml       one page per book, a section per chapter
+
+Entries are stored without compression, which every reader accepts.
+*/
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+const STYLE: &str = "body { margin: 0 1em; line-height: 1.5; }
+h1, h2 { text-align: center; }
+sup.verse { font-size: 0.6em; font-weight: bold; padding-right: 0.2em; }
+mark { background: #fff3a0; }
+a.noteref { font-size: 0.7em; vertical-align: super; text-decoration: none; }
+aside.note { font-size: 0.85em; margin: 0.5em 0; }
+";
+
+//Language of translations that don't say theirs
+const LANGUAGE: &str = "en";
+
+//A book as it goes into the file
+struct Page {
+    book: BibleBook,
+    file: String,
+    //Chapters with their verses, empty ones left out
+    chapters: Vec<(u8, Vec<String>)>,
+}
+
+//Writes the books of the current translation, returns the file size
+pub fn write(
+    db: &Database,
+    books: &[BibleBook],
+    annotations: &Annotations,
+    path: &Path,
+) -> Result<usize> {
+    let metadata = db.source().metadata(&db.translation)?;
+
+    let mut pages = vec![];
+    for book in books {
+        let chapters: Vec<(u8, Vec<String>)> = (1..)
+            .zip(db.fetch_book(*book)?)
+            .filter(|(_, verses)| !verses.is_empty())
+            .collect();
+        if !chapters.is_empty() {
+            pages.push(Page {
+                book: *book,
+                file: format!("{}.xhtml", book.osis_id()),
+                chapters,
+            });
+        }
+    }
+
+    let title = match pages.as_slice() {
+        [page] if metadata.books > 1 => {
+            format!("{} ({})", page.book.as_str(), metadata.translation)
+        }
+        [first, .., last] if pages.len() < metadata.books => format!(
+            "{} - {} ({})",
+            first.book.as_str(),
+            last.book.as_str(),
+            metadata.translation
+        ),
+        _ => metadata.translation.clone(),
+    };
+    //Stable for the same books of a translation, so readers see a new export as the same book
+    let identifier = match pages.as_slice() {
+        [first, .., last] => format!(
+            "verse:{}:{}-{}",
+            metadata.translation,
+            first.book.osis_id(),
+            last.book.osis_id()
+        ),
+        [page] => format!("verse:{}:{}", metadata.translation, page.book.osis_id()),
+        [] => format!("verse:{}", metadata.translation),
+    };
+
+    let language = metadata.language.as_deref().unwrap_or(LANGUAGE);
+
+    let mut zip = Zip::default();
+    zip.add("mimetype", b"application/epub+zip");
+    zip.add("META-INF/container.xml", CONTAINER.as_bytes());
+    zip.add(
+        "OEBPS/content.opf",
+        package(&title, &identifier, &metadata.source, language, &pages).as_bytes(),
+    );
+    zip.add(
+        "OEBPS/nav.xhtml",
+        navigation(&title, language, &pages).as_bytes(),
+    );
+    zip.add("OEBPS/toc.ncx", ncx(&title, &identifier, &pages).as_bytes());
+    zip.add("OEBPS/style.css", STYLE.as_bytes());
+    for page in &pages {
+        zip.add(
+            &format!("OEBPS/{}", page.file),
+            book_page(page, annotations, language, metadata.versification).as_bytes(),
+        );
+    }
+
+    let bytes = zip.finish();
+    fs::write(path, &bytes)?;
+    Ok(bytes.len())
+}
+
+fn package(title: &str, identifier: &str, source: &str, language: &str, pages: &[Page]) -> String {
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    for page in pages {
+        //Ids can't start with a digit like 1Sam
+        let id = format!("b{}", page.book.osis_id());
+        manifest.push_str(&format!(
+            "    <item id=\"{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+            id, page.file
+        ));
+        spine.push_str(&format!("    <itemref idref=\"{}\"/>\n", id));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id" xml:lang="{language}">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="id">{identifier}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:language>{language}</dc:language>
+    <dc:source>{source}</dc:source>
+    <dc:publisher>verse</dc:publisher>
+    <meta property="dcterms:modified">{modified}T00:00:00Z</meta>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+    <item id="style" href="style.css" media-type="text/css"/>
+{manifest}  </manifest>
+  <spine toc="ncx">
+{spine}  </spine>
+</package>
+"#,
+        language = language,
+        identifier = escape_xml(identifier),
+        title = escape_xml(title),
+        source = escape_xml(source),
+        modified = Date::today(),
+    )
+}
+
+fn navigation(title: &str, language: &str, pages: &[Page]) -> String {
+    let mut list = String::new();
+    for page in pages {
+        list.push_str(&format!(
+            "      <li><a href=\"{}\">{}</a>\n        <ol>\n",
+            page.file,
+            page.book.as_str()
+        ));
+        for (chapter, _) in &page.chapters {
+            list.push_str(&format!(
+                "          <li><a href=\"{}#c{}\">{}</a></li>\n",
+                page.file, chapter, chapter
+            ));
+        }
+        list.push_str("        </ol>\n      </li>\n");
+    }
+
+    xhtml(
+        title,
+        language,
+        &format!(
+            "  <nav epub:type=\"toc\" id=\"toc\">\n    <h1>{}</h1>\n    <ol>\n{}    </ol>\n  </nav>\n",
+            escape_xml(title),
+            list
+        ),
+    )
+}
+
+fn ncx(title: &str, identifier: &str, pages: &[Page]) -> String {
+    let mut points = String::new();
+    let mut order = 0;
+    for page in pages {
+        order += 1;
+        points.push_str(&format!(
+            "    <navPoint id=\"p{order}\" playOrder=\"{order}\"><navLabel><text>{}</text></navLabel><content src=\"{}\"/>\n",
+            page.book.as_str(),
+            page.file
+        ));
+        for (chapter, _) in &page.chapters {
+            order += 1;
+            points.push_str(&format!(
+                "      <navPoint id=\"p{order}\" playOrder=\"{order}\"><navLabel><text>{} {}</text></navLabel><content src=\"{}#c{}\"/></navPoint>\n",
+                page.book.as_str(),
+                chapter,
+                page.file,
+                chapter
+            ));
+        }
+        points.push_str("    </navPoint>\n");
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+  <head><meta name="dtb:uid" content="{}"/></head>
+  <docTitle><text>{}</text></docTitle>
+  <navMap>
+{}  </navMap>
+</ncx>
+"#,
+        escape_xml(identifier),
+        escape_xml(title),
+        points
+    )
+}
+
+//A book with a section per chapter, verse numbers as superscripts and notes after
+//the chapter they belong to
+//Verses are numbered as the translation numbers them, ones that belong to another of its
+//chapters as "3:19". Anchors and annotations stay in the English numbering
+fn book_page(
+    page: &Page,
+    annotations: &Annotations,
+    language: &str,
+    versification: Versification,
+) -> String {
+    let book = page.book;
+    let mut body = format!("  <h1>{}</h1>\n", book.as_str());
+
+    for (chapter, verses) in &page.chapters {
+        body.push_str(&format!(
+            "  <section epub:type=\"chapter\" id=\"c{chapter}\">\n    <h2>{} {chapter}</h2>\n    <p>",
+            book.as_str()
+        ));
+
+        let mut notes = String::new();
+        let (_, first_chapter, _) = versification.from_english(book, *chapter, 1);
+        for (verse, text) in (1..).zip(verses) {
+            let (_, native_chapter, native_verse) =
+                versification.from_english(book, *chapter, verse);
+            let number = match native_chapter == first_chapter {
+                true => native_verse.to_string(),
+                false => format!("{}:{}", native_chapter, native_verse),
+            };
+            let text = match annotations.highlight(book, *chapter, verse) {
+                Some(category) => format!(
+                    "<mark title=\"{}\">{}</mark>",
+                    escape_xml(category),
+                    escape_xml(text)
+                ),
+                None => escape_xml(text),
+            };
+            body.push_str(&format!(
+                "<span id=\"v{chapter}-{verse}\"><sup class=\"verse\">{number}</sup>{text}</span>"
+            ));
+
+            if let Some(note) = annotations.note(book, *chapter, verse) {
+                body.push_str(&format!(
+                    "<a epub:type=\"noteref\" class=\"noteref\" href=\"#n{chapter}-{verse}\">*</a>"
+                ));
+                notes.push_str(&format!(
+                    "    <aside epub:type=\"footnote\" class=\"note\" id=\"n{chapter}-{verse}\"><p><a href=\"#v{chapter}-{verse}\">{native_chapter}:{native_verse}</a> {}</p></aside>\n",
+                    escape_xml(note)
+                ));
+            }
+            body.push(' ');
+        }
+
+        body.push_str("</p>\n");
+        body.push_str(&notes);
+        body.push_str("  </section>\n");
+    }
+
+    xhtml(book.as_str(), language, &body)
+}
+
+fn xhtml(title: &str, language: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{language}" lang="{language}">
+<head>
+  <meta charset="UTF-8"/>
+  <title>{}</title>
+  <link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+{}</body>
+</html>
+"#,
+        escape_xml(title),
+        body,
+        language = language
+    )
+}
+
+//Zip archive with stored entries, all in memory
+#[derive(Default)]
+struct Zip {
+    bytes: Vec<u8>,
+    //Central directory records
+    directory: Vec<u8>,
+    entries: u16,
+}
+
+impl Zip {
+    fn add(&mut self, name: &str, data: &[u8]) {
+        let offset = self.bytes.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+
+        //Version 2.0, no flags, stored, 1980-01-01 00:00
+        let common = |record: &mut Vec<u8>| {
+            for value in [20u16, 0, 0, 0, 0x21] {
+                record.extend(value.to_le_bytes());
+            }
+            record.extend(crc.to_le_bytes());
+            record.extend(size.to_le_bytes());
+            record.extend(size.to_le_bytes());
+            record.extend((name.len() as u16).to_le_bytes());
+            //No extra field
+            record.extend(0u16.to_le_bytes());
+        };
+
+        self.bytes.extend(0x04034b50u32.to_le_bytes());
+        common(&mut self.bytes);
+        self.bytes.extend(name.as_bytes());
+        self.bytes.extend(data);
+
+        self.directory.extend(0x02014b50u32.to_le_bytes());
+        //Made by version 2.0
+        self.directory.extend(20u16.to_le_bytes());
+        common(&mut self.directory);
+        //No comment, disk 0, no attributes
+        for value in [0u16, 0, 0] {
+            self.directory.extend(value.to_le_bytes());
+        }
+        self.directory.extend(0u32.to_le_bytes());
+        self.directory.extend(offset.to_le_bytes());
+        self.directory.extend(name.as_bytes());
+
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let offset = self.bytes.len() as u32;
+        let size = self.directory.len() as u32;
+        self.bytes.append(&mut self.directory);
+
+        //End of central directory, all on disk 0
+        self.bytes.extend(0x06054b50u32.to_le_bytes());
+        for value in [0u16, 0, self.entries, self.entries] {
+            self.bytes.extend(value.to_le_bytes());
+        }
+        self.bytes.extend(size.to_le_bytes());
+        self.bytes.extend(offset.to_le_bytes());
+        //No comment
+        self.bytes.extend(0u16.to_le_bytes());
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, env, process};
+
+    use super::*;
+    use crate::verse::{index::BibleIndex, source::Memory};
+
+    struct TempFile(std::path::PathBuf);
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn u16_at(bytes: &[u8], at: usize) -> usize {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    //Names and contents of the entries as the central directory lists them, checking
+    //they match their local headers
+    fn entries(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let end = bytes.len() - 22;
+        assert_eq!(u32_at(bytes, end), 0x06054b50);
+        let count = u16_at(bytes, end + 10);
+        let mut at = u32_at(bytes, end + 16) as usize;
+        assert_eq!(at + u32_at(bytes, end + 12) as usize, end);
+
+        let mut entries = vec![];
+        for _ in 0..count {
+            assert_eq!(u32_at(bytes, at), 0x02014b50);
+            //Stored
+            assert_eq!(u16_at(bytes, at + 10), 0);
+            let crc = u32_at(bytes, at + 16);
+            let size = u32_at(bytes, at + 20) as usize;
+            let name_length = u16_at(bytes, at + 28);
+            let offset = u32_at(bytes, at + 42) as usize;
+            let name = &bytes[at + 46..at + 46 + name_length];
+
+            assert_eq!(u32_at(bytes, offset), 0x04034b50);
+            assert_eq!(u32_at(bytes, offset + 14), crc);
+            assert_eq!(&bytes[offset + 30..offset + 30 + name_length], name);
+            let start = offset + 30 + name_length;
+            let data = bytes[start..start + size].to_vec();
+            assert_eq!(crc32(&data), crc);
+
+            entries.push((String::from_utf8(name.to_vec()).unwrap(), data));
+            at += 46 + name_length;
+        }
+        entries
+    }
+
+    #[test]
+    fn zip() {
+        let mut zip = Zip::default();
+        zip.add("mimetype", b"application/epub+zip");
+        zip.add("OEBPS/empty.txt", b"");
+        let bytes = zip.finish();
+
+        //Readers find the type at a fixed offset
+        assert_eq!(&bytes[30..38], b"mimetype");
+        assert_eq!(&bytes[38..58], b"application/epub+zip");
+        assert_eq!(
+            entries(&bytes),
+            [
+                ("mimetype".to_string(), b"application/epub+zip".to_vec()),
+                ("OEBPS/empty.txt".to_string(), vec![])
+            ]
+        );
+    }
+
+    fn page(book: BibleBook, chapters: &[u8]) -> Page {
+        Page {
+            book,
+            file: format!("{}.xhtml", book.osis_id()),
+            chapters: chapters
+                .iter()
+                .map(|chapter| (*chapter, vec!["Text".to_string()]))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn contents() {
+        let pages = [
+            page(BibleBook::FirstSamuel, &[1, 2]),
+            page(BibleBook::Jude, &[1]),
+        ];
+
+        //Ids may not start with a digit
+        let opf = package("Faith & Works", "verse:TEST", "test", "en", &pages);
+        assert!(opf.contains("<dc:title>Faith &amp; Works</dc:title>"));
+        assert!(opf.contains(
+            "<item id=\"b1Sam\" href=\"1Sam.xhtml\" media-type=\"application/xhtml+xml\"/>"
+        ));
+        assert!(opf.contains("<itemref idref=\"b1Sam\"/>\n    <itemref idref=\"bJude\"/>"));
+
+        let nav = navigation("Faith & Works", "en", &pages);
+        assert!(nav.contains("<li><a href=\"1Sam.xhtml#c2\">2</a></li>"));
+        assert!(nav.contains("<li><a href=\"Jude.xhtml\">Jude</a>"));
+
+        //Books and chapters are played in order
+        let ncx = ncx("Faith & Works", "verse:TEST", &pages);
+        for (order, label) in [
+            (1, "1 Samuel"),
+            (3, "1 Samuel 2"),
+            (4, "Jude"),
+            (5, "Jude 1"),
+        ] {
+            assert!(
+                ncx.contains(&format!(
+                    "playOrder=\"{}\"><navLabel><text>{}</text>",
+                    order, label
+                )),
+                "{}",
+                label
+            );
+        }
+    }
+
+    #[test]
+    fn annotated_page() {
+        let page = Page {
+            book: BibleBook::John,
+            file: "John.xhtml".to_string(),
+            chapters: vec![(3, vec!["For God <so> loved".to_string()])],
+        };
+        let annotations = Annotations {
+            highlights: HashMap::from([((BibleBook::John, 3, 1), "Love".to_string())]),
+            notes: HashMap::from([((BibleBook::John, 3, 1), "Nicodemus & Jesus".to_string())]),
+        };
+        let xhtml = book_page(&page, &annotations, "en", Versification::English);
+
+        assert!(xhtml.contains(
+            "<span id=\"v3-1\"><sup class=\"verse\">1</sup><mark title=\"Love\">For God &lt;so&gt; loved</mark></span>"
+        ));
+        assert!(xhtml.contains("href=\"#n3-1\">*</a>"));
+        assert!(xhtml.contains("<a href=\"#v3-1\">3:1</a> Nicodemus &amp; Jesus"));
+    }
+
+    #[test]
+    fn written_file() {
+        let verses = [
+            (BibleBook::Ruth, 1, 1, "In the days"),
+            (BibleBook::Ruth, 3, 1, "Then Naomi"),
+            (BibleBook::Jude, 1, 1, "Jude"),
+        ]
+        .map(|(book, chapter, verse, text)| (book, chapter, verse, text.to_string()));
+        let db =
+            Database::new(Memory::new("test", vec![BibleIndex::build("TEST", verses)])).unwrap();
+        let file = TempFile(env::temp_dir().join(format!("verse-{}-test.epub", process::id())));
+        let annotations = Annotations {
+            highlights: HashMap::new(),
+            notes: HashMap::new(),
+        };
+
+        let size = write(&db, &[BibleBook::Ruth], &annotations, &file.0).unwrap();
+        let bytes = fs::read(&file.0).unwrap();
+        assert_eq!(bytes.len(), size);
+
+        let entries = entries(&bytes);
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "mimetype",
+                "META-INF/container.xml",
+                "OEBPS/content.opf",
+                "OEBPS/nav.xhtml",
+                "OEBPS/toc.ncx",
+                "OEBPS/style.css",
+                "OEBPS/Ruth.xhtml"
+            ]
+        );
+
+        let opf = String::from_utf8(entries[2].1.clone()).unwrap();
+        assert!(opf.contains("<dc:title>Ruth (TEST)</dc:title>"));
+        assert!(opf.contains("<dc:identifier id=\"id\">verse:TEST:Ruth</dc:identifier>"));
+        //Chapters without verses are left out
+        let ruth = String::from_utf8(entries[6].1.clone()).unwrap();
+        assert!(ruth.contains("id=\"c3\""));
+        assert!(!ruth.contains("id=\"c2\""));
+    }
+
+    //Malachi 4 of the English numbering is 3:19-24 of the Hebrew
+    #[test]
+    fn numbered_as_the_translation() {
+        let page = Page {
+            book: BibleBook::Malachi,
+            file: "Mal.xhtml".to_string(),
+            chapters: vec![(4, vec!["The day".to_string(), "The sun".to_string()])],
+        };
+        let annotations = Annotations {
+            highlights: HashMap::new(),
+            notes: HashMap::from([((BibleBook::Malachi, 4, 2), "Healing".to_string())]),
+        };
+        let xhtml = book_page(&page, &annotations, "he", Versification::Hebrew);
+
+        assert!(xhtml.contains(
+            "<span id=\"v4-1\"><sup class=\"verse\">19</sup>The day</span> \
+             <span id=\"v4-2\"><sup class=\"verse\">20</sup>The sun</span>"
+        ));
+        assert!(xhtml.contains("<a href=\"#v4-2\">3:20</a> Healing"));
+    }
+}
@@ -0,0 +1,52 @@
+pub mod epub;
+pub mod study;
+
+use std::collections::HashMap;
+
+use crate::verse::{db::BibleBook, store::Store};
+
+/*
+Exports write passages of the current translation into documents read outside
+verse, optionally with the user's highlights and notes from the store.
+*/
+
+//Highlights and notes to put into an export, empty when they're left out
+#[derive(Default)]
+pub struct Annotations {
+    pub highlights: HashMap<(BibleBook, u8, u8), String>,
+    pub notes: HashMap<(BibleBook, u8, u8), String>,
+}
+
+impl Annotations {
+    pub fn from_store(store: Store) -> Self {
+        Annotations {
+            highlights: store.highlights,
+            notes: store.notes,
+        }
+    }
+
+    pub fn highlight(&self, book: BibleBook, chapter: u8, verse: u8) -> Option<&str> {
+        self.highlights
+            .get(&(book, chapter, verse))
+            .map(String::as_str)
+    }
+
+    pub fn note(&self, book: BibleBook, chapter: u8, verse: u8) -> Option<&str> {
+        self.notes.get(&(book, chapter, verse)).map(String::as_str)
+    }
+}
+
+//Escapes text for XML and HTML, in content and attribute values
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,276 @@
+use crate::verse::{
+    crossref::{CrossReference, CrossReferences},
+    db::{BibleBook, Verse},
+    export::Annotations,
+    versification::Versification,
+};
+
+/*
+Study documents, a passage with the user's notes after their verses, highlight
+categories as tags and the most voted cross references, in Markdown for Obsidian
+and Logseq or in Org mode:
+
+    ## John 3
+    **16** For God so loved the world, ... #promise ^john-3-16
+
+    > My note
+
+    See [[Romans 5#^rom-5-8|Romans 5:8]]
+
+    ** John 3:16                                 :promise:
+    :PROPERTIES:
+    :CUSTOM_ID: john-3-16
+    :END:
+
+Every verse gets an anchor from its OSIS id, and cross references link to the
+anchor in a page named after the chapter, so studies saved as "Romans 5.md" link up.
+Chapters, verses and anchors are in the translation's numbering, notes and cross
+references are looked up in the English one they're kept in.
+*/
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Markdown,
+    Org,
+}
+
+//What goes around the verses
+pub struct Study<'a> {
+    pub title: String,
+    pub annotations: &'a Annotations,
+    pub crossrefs: Option<&'a CrossReferences>,
+    //Most cross references per verse
+    pub crossref_limit: usize,
+    //Numbering of the translation the verses come from
+    pub versification: Versification,
+}
+
+impl Study<'_> {
+    pub fn render(&self, format: Format, verses: &[Verse]) -> String {
+        let mut document = match format {
+            Format::Markdown => format!("# {}\n", self.title),
+            Format::Org => format!("#+TITLE: {}\n", self.title),
+        };
+
+        let mut chapter = None;
+        for verse in verses {
+            let (book, native_chapter, number) =
+                self.versification
+                    .from_english(verse.book, verse.chapter, verse.verse);
+            if chapter != Some((book, native_chapter)) {
+                chapter = Some((book, native_chapter));
+                let heading = page(book, native_chapter);
+                document.push_str(&match format {
+                    Format::Markdown => format!("\n## {}\n", heading),
+                    Format::Org => format!("\n* {}\n", heading),
+                });
+            }
+
+            let highlight = self
+                .annotations
+                .highlight(verse.book, verse.chapter, verse.verse);
+            let note = self
+                .annotations
+                .note(verse.book, verse.chapter, verse.verse);
+            let crossrefs = self.crossrefs(verse);
+            let id = anchor(book, native_chapter, number);
+
+            match format {
+                Format::Markdown => {
+                    let tag = highlight
+                        .map(|tag| format!(" #{}", tag))
+                        .unwrap_or_default();
+                    document.push_str(&format!("\n**{}** {}{} ^{}\n", number, verse.text, tag, id));
+                    if let Some(note) = note {
+                        document.push_str(&format!("\n> {}\n", note));
+                    }
+                }
+                Format::Org => {
+                    let tag = highlight
+                        .map(|tag| format!(" :{}:", tag))
+                        .unwrap_or_default();
+                    document.push_str(&format!(
+                        "** {}{}\n:PROPERTIES:\n:CUSTOM_ID: {}\n:END:\n{}\n",
+                        self.versification
+                            .reference(verse.book, verse.chapter, verse.verse),
+                        tag,
+                        id,
+                        verse.text
+                    ));
+                    if let Some(note) = note {
+                        document.push_str(&format!("#+begin_quote\n{}\n#+end_quote\n", note));
+                    }
+                }
+            }
+
+            if !crossrefs.is_empty() {
+                let links: Vec<String> = crossrefs
+                    .iter()
+                    .map(|crossref| link(format, self.versification, crossref))
+                    .collect();
+                document.push_str(&format!(
+                    "{}See {}\n",
+                    match format {
+                        Format::Markdown => "\n",
+                        Format::Org => "",
+                    },
+                    links.join(", ")
+                ));
+            }
+        }
+
+        document
+    }
+
+    fn crossrefs(&self, verse: &Verse) -> &[CrossReference] {
+        let Some(crossrefs) = self.crossrefs else {
+            return &[];
+        };
+        //Sorted by votes, voted down ones are disputed and left out
+        let all = crossrefs.get(verse.book, verse.chapter, verse.verse);
+        let agreed = all.partition_point(|crossref| crossref.votes >= 0);
+        &all[..agreed.min(self.crossref_limit)]
+    }
+}
+
+//"john-3-16", from the OSIS id as block ids take only letters, digits and dashes
+fn anchor(book: BibleBook, chapter: u8, verse: u8) -> String {
+    format!("{}-{}-{}", book.osis_id().to_lowercase(), chapter, verse)
+}
+
+//"Romans 5", the page a chapter's study is saved as
+fn page(book: BibleBook, chapter: u8) -> String {
+    format!("{} {}", book.as_str(), chapter)
+}
+
+fn link(format: Format, versification: Versification, crossref: &CrossReference) -> String {
+    let native = |(book, chapter, verse)| versification.from_english(book, chapter, verse);
+    let crossref = CrossReference {
+        first: native(crossref.first),
+        last: native(crossref.last),
+        votes: crossref.votes,
+    };
+    let (book, chapter, verse) = crossref.first;
+    let (page, id) = (page(book, chapter), anchor(book, chapter, verse));
+
+    match format {
+        Format::Markdown => format!("[[{}#^{}|{}]]", page, id, crossref.reference()),
+        Format::Org => format!("[[file:{}.org::#{}][{}]]", page, id, crossref.reference()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    //Malachi 4 of the English numbering is 3:19-24 of the Hebrew
+    #[test]
+    fn numbered_as_the_translation() {
+        let annotations = Annotations {
+            highlights: HashMap::new(),
+            notes: HashMap::from([((BibleBook::Malachi, 4, 1), "The day".to_string())]),
+        };
+        let study = Study {
+            title: "Malachi 3:19-20 (WLC)".to_string(),
+            annotations: &annotations,
+            crossrefs: None,
+            crossref_limit: 5,
+            versification: Versification::Hebrew,
+        };
+        let verses = [1, 2].map(|verse| Verse {
+            book: BibleBook::Malachi,
+            chapter: 4,
+            verse,
+            text: format!("Verse {}", verse),
+        });
+
+        assert_eq!(
+            study.render(Format::Markdown, &verses),
+            "# Malachi 3:19-20 (WLC)\n\n## Malachi 3\n\n**19** Verse 1 ^mal-3-19\n\n> The day\n\n**20** Verse 2 ^mal-3-20\n"
+        );
+        assert!(
+            study
+                .render(Format::Org, &verses)
+                .contains("** Malachi 3:20\n:PROPERTIES:\n:CUSTOM_ID: mal-3-20\n")
+        );
+    }
+
+    fn verse(book: BibleBook, chapter: u8, verse: u8) -> Verse {
+        Verse {
+            book,
+            chapter,
+            verse,
+            text: format!("{} {}:{}", book.as_str(), chapter, verse),
+        }
+    }
+
+    //Most voted first, the disputed one left out
+    const CROSSREFS: &str = "From Verse\tTo Verse\tVotes
+John.3.16\tGen.1.1\t-3
+John.3.16\t1John.4.9-1John.4.10\t50
+John.3.16\tRom.5.8\t100
+John.3.16\tMal.4.5\t10
+John.3.17\tJohn.12.47\t20
+";
+
+    #[test]
+    fn links_and_tags() {
+        let crossrefs = CrossReferences::parse(CROSSREFS);
+        let annotations = Annotations {
+            highlights: HashMap::from([((BibleBook::John, 3, 16), "promise".to_string())]),
+            notes: HashMap::new(),
+        };
+        let study = Study {
+            title: "John 3:16-17".to_string(),
+            annotations: &annotations,
+            crossrefs: Some(&crossrefs),
+            crossref_limit: 2,
+            versification: Versification::English,
+        };
+        let verses = [verse(BibleBook::John, 3, 16), verse(BibleBook::John, 3, 17)];
+
+        assert_eq!(
+            study.render(Format::Markdown, &verses),
+            "# John 3:16-17\n\n## John 3\n\n\
+             **16** John 3:16 #promise ^john-3-16\n\n\
+             See [[Romans 5#^rom-5-8|Romans 5:8]], [[1 John 4#^1john-4-9|1 John 4:9-10]]\n\n\
+             **17** John 3:17 ^john-3-17\n\n\
+             See [[John 12#^john-12-47|John 12:47]]\n"
+        );
+        assert_eq!(
+            study.render(Format::Org, &verses[..1]),
+            "#+TITLE: John 3:16-17\n\n* John 3\n\
+             ** John 3:16 :promise:\n:PROPERTIES:\n:CUSTOM_ID: john-3-16\n:END:\nJohn 3:16\n\
+             See [[file:Romans 5.org::#rom-5-8][Romans 5:8]], \
+             [[file:1 John 4.org::#1john-4-9][1 John 4:9-10]]\n"
+        );
+    }
+
+    #[test]
+    fn headings_and_native_links() {
+        let crossrefs = CrossReferences::parse(CROSSREFS);
+        let annotations = Annotations {
+            highlights: HashMap::new(),
+            notes: HashMap::new(),
+        };
+        let study = Study {
+            title: "Study".to_string(),
+            annotations: &annotations,
+            crossrefs: Some(&crossrefs),
+            crossref_limit: 5,
+            versification: Versification::Hebrew,
+        };
+
+        //A heading for each chapter, links in the translation's numbering
+        let markdown = study.render(
+            Format::Markdown,
+            &[verse(BibleBook::John, 3, 16), verse(BibleBook::John, 4, 1)],
+        );
+        assert!(markdown.contains("\n## John 3\n"));
+        assert!(markdown.contains("\n## John 4\n"));
+        assert!(markdown.contains("[[Malachi 3#^mal-3-23|Malachi 3:23]]"));
+        assert!(!markdown.contains("Genesis"));
+    }
+}
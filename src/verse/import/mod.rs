@@ -0,0 +1,466 @@
+pub mod osis;
+pub mod scrollmapper;
+pub mod theword;
+pub mod usfm;
+pub mod usx;
+pub mod zefania;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::verse::{
+    db::{BibleBook, Testament},
+    index::BibleIndex,
+    strongs::Strong,
+    versification::Versification,
+};
+
+/*
+Importers read Bibles in other formats into a BibleIndex, to be written out as
+a .vbin file. They collect verses as they come, finish maps them from the file's
+numbering to the English one, sorts them and checks the versification against the
+books and chapters verse knows.
+*/
+
+//Verses read from a file, and what didn't fit
+#[derive(Default)]
+pub struct Import {
+    //Name the file gives the translation, if any
+    pub translation: Option<String>,
+    //Numbering the file's verses are in
+    pub versification: Versification,
+    //Language the file says it's in, see language_tag
+    pub language: Option<String>,
+    verses: Vec<(BibleBook, u8, u8, String)>,
+    //Book ids the file uses that verse doesn't know, with their verse count
+    unmapped: BTreeMap<String, usize>,
+    pub warnings: Vec<String>,
+    //Features kept as plain text, with the number of verses they're in
+    pub flattened: BTreeMap<&'static str, usize>,
+}
+
+//What came out of an import
+pub struct Report {
+    pub index: BibleIndex,
+    pub books: usize,
+    pub warnings: Vec<String>,
+}
+
+impl Import {
+    pub fn verse(&mut self, book: BibleBook, chapter: u8, verse: u8, text: String) {
+        let (book, chapter) = deuterocanonical_chapter(book, chapter);
+        self.verses.push((book, chapter, verse, text));
+    }
+
+    //A verse of a book verse doesn't know
+    pub fn unmapped(&mut self, book: &str) {
+        *self.unmapped.entry(book.to_string()).or_default() += 1;
+    }
+
+    pub fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    //Notes a verse that had e.g. red letter text, which is imported as plain text
+    pub fn flatten(&mut self, feature: &'static str) {
+        *self.flattened.entry(feature).or_default() += 1;
+    }
+
+    //Orders the verses and fits them to verse's versification, reporting what didn't fit
+    pub fn finish(mut self, translation: &str) -> Report {
+        let mut warnings = std::mem::take(&mut self.warnings);
+        for (book, count) in &self.unmapped {
+            warnings.push(format!("Unknown book {}, {} verses skipped", book, count));
+        }
+
+        //Verses the numbering moved, others may land on them like psalm titles on verse 1
+        let versification = self.versification;
+        let mut moved = HashSet::new();
+        for (book, chapter, verse, _) in &mut self.verses {
+            let english = versification.to_english(*book, *chapter, *verse);
+            if english != (*book, *chapter, *verse) {
+                moved.insert(english);
+            }
+            (*book, *chapter, *verse) = english;
+        }
+
+        //Stable, so repeated verses stay in file order
+        self.verses
+            .sort_by_key(|(book, chapter, verse, _)| (*book as u8, *chapter, *verse));
+
+        let mut verses: Vec<(BibleBook, u8, u8, String)> = Vec::with_capacity(self.verses.len());
+        let mut previous: Option<(BibleBook, u8, u8)> = None;
+        for (book, chapter, verse, text) in self.verses {
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            let reference = format!("{} {}:{}", book.as_str(), chapter, verse);
+
+            if chapter < 1 || chapter > book.max_chapter_count() {
+                warnings.push(format!(
+                    "{} is past the {} chapters of {}, skipped",
+                    reference,
+                    book.max_chapter_count(),
+                    book.as_str()
+                ));
+                continue;
+            }
+            if verse < 1 {
+                warnings.push(format!("{} skipped, verses count from 1", reference));
+                continue;
+            }
+
+            let expected = match previous {
+                Some((b, c, v)) if b == book && c == chapter => v + 1,
+                _ => 1,
+            };
+            if verse < expected {
+                if !moved.contains(&(book, chapter, verse)) {
+                    warnings.push(format!("{} appears twice, the texts are joined", reference));
+                }
+                if let Some((_, _, _, last)) = verses.last_mut() {
+                    last.push(' ');
+                    last.push_str(&text);
+                }
+                continue;
+            }
+            //The index keeps gaps as empty verses, they're only reported here
+            if verse > expected {
+                warnings.push(if verse == expected + 1 {
+                    format!("{} {}:{} is missing", book.as_str(), chapter, expected)
+                } else {
+                    format!(
+                        "{} {}:{}-{} are missing",
+                        book.as_str(),
+                        chapter,
+                        expected,
+                        verse - 1
+                    )
+                });
+            }
+
+            verses.push((book, chapter, verse, text));
+            previous = Some((book, chapter, verse));
+        }
+
+        let mut books: Vec<BibleBook> = verses.iter().map(|(book, _, _, _)| *book).collect();
+        books.dedup();
+        for book in &books {
+            let missing: Vec<u8> = (1..=book.max_chapter_count())
+                .filter(|chapter| !verses.iter().any(|(b, c, _, _)| b == book && c == chapter))
+                .collect();
+            if !missing.is_empty() {
+                warnings.push(format!(
+                    "{} has no chapter {}",
+                    book.as_str(),
+                    ranges(&missing)
+                ));
+            }
+        }
+
+        let mut index = BibleIndex::build(translation, verses);
+        index.versification = versification;
+        index.language = self.language.take();
+        Report {
+            index,
+            books: books.len(),
+            warnings,
+        }
+    }
+}
+
+//A language code as files give it, "en", "de-CH" or Zefania's "GER", in lower case
+pub fn language_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+    let valid = !tag.is_empty()
+        && tag.len() <= 35
+        && tag
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_');
+    valid.then(|| tag.to_lowercase().replace('_', "-"))
+}
+
+//Strong's numbers as files give them, "H7225", "strong:H0430 strong:H853" or Zefania's
+//bare "7225", as the tags verse keeps after the word, "{H7225}". Bare numbers are
+//Hebrew in the Old Testament and Greek in the New
+pub fn strongs_tags(numbers: &str, book: BibleBook) -> String {
+    let letter = match book.testament() {
+        Testament::Old => 'H',
+        Testament::New => 'G',
+    };
+
+    numbers
+        .split(|char: char| char.is_whitespace() || char == ',')
+        .filter_map(|number| {
+            let number = number.strip_prefix("strong:").unwrap_or(number);
+            match number.starts_with(|char: char| char.is_ascii_digit()) {
+                true => Strong::parse(&format!("{}{}", letter, number)),
+                false => Strong::parse(number),
+            }
+        })
+        .map(Strong::tag)
+        .collect()
+}
+
+//Catholic Bibles go on with Daniel 13 (Susanna) and 14 (Bel and the Dragon), Esther
+//to 16 with the Greek additions and Orthodox ones with Psalm 151, verse keeps those
+//as books of their own
+pub fn deuterocanonical_chapter(book: BibleBook, chapter: u8) -> (BibleBook, u8) {
+    match (book, chapter) {
+        (BibleBook::Daniel, 13) => (BibleBook::Susanna, 1),
+        (BibleBook::Daniel, 14) => (BibleBook::BelAndTheDragon, 1),
+        (BibleBook::Esther, 11..=16) => (BibleBook::AdditionsToEsther, chapter),
+        (BibleBook::Psalms, 151) => (BibleBook::Psalm151, 1),
+        _ => (book, chapter),
+    }
+}
+
+//Where those Bibles have a chapter deuterocanonical_chapter moved
+pub fn deuterocanonical_origin(book: BibleBook, chapter: u8) -> Option<(BibleBook, u8)> {
+    match (book, chapter) {
+        (BibleBook::Susanna, 1) => Some((BibleBook::Daniel, 13)),
+        (BibleBook::BelAndTheDragon, 1) => Some((BibleBook::Daniel, 14)),
+        (BibleBook::AdditionsToEsther, 11..=16) => Some((BibleBook::Esther, chapter)),
+        (BibleBook::Psalm151, 1) => Some((BibleBook::Psalms, 151)),
+        _ => None,
+    }
+}
+
+//"1-3, 7" for 1, 2, 3, 7
+fn ranges(numbers: &[u8]) -> String {
+    let mut ranges: Vec<(u8, u8)> = vec![];
+    for number in numbers {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == *number => *last = *number,
+            _ => ranges.push((*number, *number)),
+        }
+    }
+
+    ranges
+        .iter()
+        .map(|(first, last)| match first == last {
+            true => first.to_string(),
+            false => format!("{}-{}", first, last),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+//Reads a Bible in any format verse knows, picked by its content. A directory is read
+//file by file, for sources with one file per book
+pub fn read(path: &Path) -> Result<Import, String> {
+    let mut import = Import::default();
+
+    if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(path)
+            .and_then(|entries| entries.map(|entry| Ok(entry?.path())).collect())
+            .map_err(|err| format!("Couldn't read {}: {}", path.display(), err))?;
+        files.retain(|file| {
+            file.extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    ["usfm", "sfm", "usx", "xml"].contains(&extension.to_lowercase().as_str())
+                })
+        });
+        files.sort();
+
+        if files.is_empty() {
+            return Err(format!(
+                "No .usfm, .sfm, .usx or .xml files in {}",
+                path.display()
+            ));
+        }
+        for file in files {
+            read_file(&file, &mut import)?;
+        }
+    } else {
+        read_file(path, &mut import)?;
+    }
+
+    Ok(import)
+}
+
+fn read_file(path: &Path, import: &mut Import) -> Result<(), String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Couldn't read {}: {}", path.display(), err))?;
+    let start: String = content.chars().take(4096).collect();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    //TheWord and CSV files are only told apart by their extension
+    let result = if let Some(testaments) = theword::Testaments::from_extension(&extension) {
+        theword::parse(&content, testaments, import)
+    } else if extension == "csv" {
+        scrollmapper::parse_csv(&content, import)
+    } else if extension == "json"
+        || start
+            .trim_start_matches('\u{feff}')
+            .trim_start()
+            .starts_with('{')
+    {
+        scrollmapper::parse_json(&content, import)
+    } else if start.contains("<osis") {
+        osis::parse(&content, import)
+    } else if start.contains("<usx") {
+        usx::parse(&content, import)
+    } else if start.to_uppercase().contains("<XMLBIBLE") {
+        zefania::parse(&content, import)
+    } else if start
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .starts_with("\\id")
+    {
+        usfm::parse(&content, import)
+    } else {
+        return Err(format!(
+            "{} isn't in a format verse can import (OSIS, USFM, USX, Zefania, TheWord, CSV, JSON)",
+            path.display()
+        ));
+    };
+
+    result.map_err(|err| format!("Couldn't import {}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(verses: &[(BibleBook, u8, u8, &str)]) -> Import {
+        let mut import = Import::default();
+        for (book, chapter, verse, text) in verses {
+            import.verse(*book, *chapter, *verse, text.to_string());
+        }
+        import
+    }
+
+    fn has(report: &Report, warning: &str) -> bool {
+        report.warnings.iter().any(|found| found == warning)
+    }
+
+    #[test]
+    fn gaps_kept_as_empty_verses() {
+        use BibleBook::Genesis;
+        let report = import(&[
+            (Genesis, 1, 1, "one"),
+            (Genesis, 1, 3, "three"),
+            (Genesis, 1, 7, "seven"),
+        ])
+        .finish("TEST");
+
+        assert_eq!(
+            report.index.chapter(Genesis, 1),
+            ["one", "", "three", "", "", "", "seven"]
+        );
+        assert!(has(&report, "Genesis 1:2 is missing"));
+        assert!(has(&report, "Genesis 1:4-6 are missing"));
+        assert!(has(&report, "Genesis has no chapter 2-50"));
+        assert_eq!(report.books, 1);
+    }
+
+    #[test]
+    fn duplicates_joined() {
+        use BibleBook::Ruth;
+        let report = import(&[
+            (Ruth, 1, 2, "second"),
+            (Ruth, 1, 1, "first"),
+            (Ruth, 1, 1, "  again\n here "),
+        ])
+        .finish("TEST");
+
+        //Sorted, and the repeated verse joined in file order with its spaces evened out
+        assert_eq!(
+            report.index.chapter(Ruth, 1),
+            ["first again here", "second"]
+        );
+        assert!(has(&report, "Ruth 1:1 appears twice, the texts are joined"));
+    }
+
+    #[test]
+    fn what_does_not_fit_is_skipped() {
+        use BibleBook::Jude;
+        let mut import = import(&[
+            (Jude, 1, 1, "Jude"),
+            (Jude, 2, 1, "none"),
+            (Jude, 1, 0, "zero"),
+        ]);
+        import.unmapped("XYZ");
+        import.unmapped("XYZ");
+        let report = import.finish("TEST");
+
+        assert_eq!(report.index.chapter(Jude, 1), ["Jude"]);
+        assert_eq!(report.index.verse_count(), 1);
+        assert!(has(
+            &report,
+            "Jude 2:1 is past the 1 chapters of Jude, skipped"
+        ));
+        assert!(has(&report, "Jude 1:0 skipped, verses count from 1"));
+        assert!(has(&report, "Unknown book XYZ, 2 verses skipped"));
+    }
+
+    #[test]
+    fn numbering_mapped_to_english() {
+        use BibleBook::{Malachi, Psalms};
+        let mut import = import(&[
+            (Psalms, 3, 1, "A Psalm of David"),
+            (Psalms, 3, 2, "LORD, how are they increased"),
+            (Psalms, 3, 3, "Many there be"),
+            (Malachi, 3, 18, "Then shall ye return"),
+            (Malachi, 3, 19, "For, behold, the day cometh"),
+        ]);
+        import.versification = Versification::Hebrew;
+        let report = import.finish("TEST");
+
+        //The title has no verse of its own in English and joins the first, unreported
+        assert_eq!(
+            report.index.chapter(Psalms, 3),
+            [
+                "A Psalm of David LORD, how are they increased",
+                "Many there be"
+            ]
+        );
+        assert!(
+            !report
+                .warnings
+                .iter()
+                .any(|warning| warning.contains("twice"))
+        );
+        assert_eq!(
+            report.index.verse(Malachi, 4, 1),
+            Some("For, behold, the day cometh")
+        );
+        assert_eq!(report.index.versification, Versification::Hebrew);
+    }
+
+    #[test]
+    fn deuterocanonical_chapters_moved() {
+        let report = import(&[
+            (BibleBook::Daniel, 13, 1, "Susanna"),
+            (BibleBook::Psalms, 151, 1, "I was small"),
+        ])
+        .finish("TEST");
+
+        assert_eq!(
+            report.index.verse(BibleBook::Susanna, 1, 1),
+            Some("Susanna")
+        );
+        assert_eq!(
+            report.index.verse(BibleBook::Psalm151, 1, 1),
+            Some("I was small")
+        );
+    }
+
+    #[test]
+    fn strongs_numbers() {
+        assert_eq!(strongs_tags("7225", BibleBook::Genesis), "{H7225}");
+        assert_eq!(strongs_tags("25", BibleBook::John), "{G25}");
+        assert_eq!(
+            strongs_tags("strong:H0430 strong:H853", BibleBook::Genesis),
+            "{H430}{H853}"
+        );
+        assert_eq!(strongs_tags("G25, x, 0", BibleBook::John), "{G25}");
+    }
+}
@@ -0,0 +1,324 @@
+use crate::verse::{
+    db::BibleBook,
+    import::{Import, language_tag, strongs_tags},
+    xml::{Event, Reader},
+};
+
+/*
+OSIS XML, as published by CrossWire and many Bible societies. Verses come either as
+containers, <verse osisID="Gen.1.1">...</verse>, or as milestones,
+<verse sID="Gen.1.1" osisID="Gen.1.1"/>...<verse eID="Gen.1.1"/>, and are found by
+their osisID alone, so books and chapters can be nested any way. Section titles and
+notes aren't part of the text and are left out, canonical titles (Psalm
+superscriptions) inside a verse are kept. Words of Jesus and poetry lines are kept
+as plain text, Strong's numbers of <w lemma="strong:H7225"> as tags.
+*/
+
+//Elements whose content is never verse text
+const SKIPPED: [&str; 4] = ["note", "header", "rdg", "figure"];
+
+pub fn parse(xml: &str, import: &mut Import) -> Result<(), String> {
+    let mut reader = Reader::new(xml);
+
+    //Verse being read, its osisID and text
+    let mut verse: Option<(String, String)> = None;
+    //Depth inside an element that is skipped
+    let mut skipping = 0;
+    //Open <q> containers, true for words of Jesus, and <q>, <l> or <lg> milestones
+    let mut quotes: Vec<bool> = vec![];
+    let mut poetry_depth = 0;
+    let (mut red_letter_milestone, mut poetry_milestone) = (false, false);
+    //Whether the verse being read has any of them
+    let (mut red_letter, mut poetry) = (false, false);
+    //Lemmas of the open <w> elements, their Strong's numbers go after the word
+    let mut lemmas: Vec<String> = vec![];
+
+    while let Some(event) = reader.next() {
+        let event = event?;
+
+        if skipping > 0 {
+            match event {
+                Event::Start { empty: false, .. } => skipping += 1,
+                Event::End(_) => skipping -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Text(text) => {
+                if let Some((_, content)) = &mut verse {
+                    content.push_str(&text);
+                    if !text.trim().is_empty() {
+                        red_letter |= red_letter_milestone || quotes.contains(&true);
+                        poetry |= poetry_milestone || poetry_depth > 0;
+                    }
+                }
+            }
+
+            Event::Start {
+                name: "verse",
+                empty,
+                ..
+            } => {
+                if let Some(id) = event.attribute("eID") {
+                    if let Some((osis_id, text)) = verse.take() {
+                        add_verse(import, &osis_id, text, red_letter, poetry);
+                    } else {
+                        import.warn(format!("End of verse {} that never started", id));
+                    }
+                    (red_letter, poetry) = (false, false);
+                    continue;
+                }
+
+                let Some(osis_id) = event.attribute("osisID").or(event.attribute("sID")) else {
+                    import.warn(format!("Verse without an osisID on line {}", reader.line()));
+                    continue;
+                };
+                //A new verse before the last one ended, milestones may be left open
+                if let Some((osis_id, text)) = verse.take() {
+                    add_verse(import, &osis_id, text, red_letter, poetry);
+                }
+                verse = Some((osis_id.to_string(), String::new()));
+                (red_letter, poetry) = (false, false);
+
+                //<verse osisID="..."/> without an sID holds no text
+                if empty && event.attribute("sID").is_none() {
+                    verse = None;
+                }
+            }
+
+            Event::End("verse") => {
+                if let Some((osis_id, text)) = verse.take() {
+                    add_verse(import, &osis_id, text, red_letter, poetry);
+                }
+                (red_letter, poetry) = (false, false);
+            }
+
+            Event::Start { name, empty, .. } => {
+                if name == "osisText" {
+                    import.translation = event.attribute("osisIDWork").map(String::from);
+                    import.language = event.attribute("xml:lang").and_then(language_tag);
+                }
+
+                let canonical = event.attribute("canonical") == Some("true");
+                let heading = name == "title" && !(canonical && verse.is_some());
+                if SKIPPED.contains(&name) || heading {
+                    if !empty {
+                        skipping = 1;
+                    }
+                    continue;
+                }
+
+                let milestone_start = event.attribute("sID").is_some();
+                match (name, empty) {
+                    ("q", false) => quotes.push(event.attribute("who") == Some("Jesus")),
+                    ("q", true) => {
+                        red_letter_milestone =
+                            milestone_start && event.attribute("who") == Some("Jesus")
+                    }
+                    ("l" | "lg", false) => poetry_depth += 1,
+                    ("l" | "lg", true) => poetry_milestone = milestone_start,
+                    ("w", false) => {
+                        lemmas.push(event.attribute("lemma").unwrap_or_default().to_string())
+                    }
+                    _ => {}
+                }
+
+                //Line and paragraph breaks separate words
+                if let Some((_, content)) = &mut verse
+                    && matches!(
+                        name,
+                        "l" | "lb" | "lg" | "p" | "milestone" | "div" | "chapter"
+                    )
+                {
+                    content.push(' ');
+                }
+            }
+
+            Event::End(name) => {
+                match name {
+                    "q" => {
+                        quotes.pop();
+                    }
+                    "l" | "lg" => poetry_depth -= 1,
+                    "w" => {
+                        let lemma = lemmas.pop().unwrap_or_default();
+                        if let Some((osis_id, content)) = &mut verse {
+                            let book = osis_id
+                                .split('.')
+                                .next()
+                                .and_then(BibleBook::from_osis_id)
+                                .unwrap_or(BibleBook::Genesis);
+                            content.push_str(&strongs_tags(&lemma, book));
+                        }
+                    }
+                    _ => {}
+                }
+                if let Some((_, content)) = &mut verse
+                    && matches!(name, "l" | "lg" | "p")
+                {
+                    content.push(' ');
+                }
+            }
+        }
+    }
+
+    if let Some((osis_id, _)) = verse {
+        import.warn(format!("Verse {} never ends", osis_id));
+    }
+    Ok(())
+}
+
+//Adds the text under the first of the ids, "Gen.1.1 Gen.1.2" when verses are combined
+fn add_verse(import: &mut Import, osis_id: &str, text: String, red_letter: bool, poetry: bool) {
+    let mut ids = osis_id.split_whitespace();
+    let Some(first) = ids.next() else {
+        return;
+    };
+
+    let mut parts = first.split('.');
+    let (Some(book), Some(chapter), Some(verse)) = (parts.next(), parts.next(), parts.next())
+    else {
+        import.warn(format!("Verse {} isn't book.chapter.verse", first));
+        return;
+    };
+    let Some(book) = BibleBook::from_osis_id(book) else {
+        import.unmapped(book);
+        return;
+    };
+    let (Ok(chapter), Ok(verse)) = (chapter.parse::<u8>(), verse.parse::<u8>()) else {
+        import.warn(format!("Verse {} has a bad chapter or verse number", first));
+        return;
+    };
+
+    if ids.next().is_some() {
+        import.warn(format!(
+            "Verses {} are combined, the text is kept under {}",
+            osis_id, first
+        ));
+    }
+    if red_letter {
+        import.flatten("words of Jesus");
+    }
+    if poetry {
+        import.flatten("poetry lines");
+    }
+    import.verse(book, chapter, verse, text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(body: &str) -> Import {
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<osis><osisText osisIDWork="TEST" xml:lang="en">{}</osisText></osis>"#,
+            body
+        );
+        let mut import = Import::default();
+        parse(&xml, &mut import).unwrap();
+        import
+    }
+
+    #[test]
+    fn containers() {
+        let report = read(
+            r#"<div type="book" osisID="Gen"><chapter osisID="Gen.1">
+<title>The Creation</title>
+<verse osisID="Gen.1.1">In the beginning<note>Or, at first</note> God created</verse>
+<verse osisID="Gen.1.2">And the earth was <w lemma="strong:H8414">without form</w>.</verse>
+</chapter></div>"#,
+        )
+        .finish("TEST");
+
+        assert_eq!(report.index.translation, "TEST");
+        assert_eq!(
+            report.index.chapter(BibleBook::Genesis, 1),
+            [
+                "In the beginning God created",
+                "And the earth was without form{H8414}."
+            ]
+        );
+    }
+
+    #[test]
+    fn milestones() {
+        let report = read(
+            r#"<div type="book" osisID="Ps"><chapter sID="Ps.3"/>
+<verse sID="Ps.3.1" osisID="Ps.3.1"/><title canonical="true">A Psalm of David.</title>
+<l>LORD, how are they increased</l><l>that trouble me!</l><verse eID="Ps.3.1"/>
+<title>Trust in trouble</title>
+<verse sID="Ps.3.2" osisID="Ps.3.2"/>Many there be<verse eID="Ps.3.2"/>
+<chapter eID="Ps.3"/></div>"#,
+        )
+        .finish("TEST");
+
+        //Canonical titles are text, the others aren't, and lines keep their words apart
+        assert_eq!(
+            report.index.chapter(BibleBook::Psalms, 3),
+            [
+                "A Psalm of David. LORD, how are they increased that trouble me!",
+                "Many there be"
+            ]
+        );
+    }
+
+    #[test]
+    fn flattened_features() {
+        let import = read(
+            r#"<verse osisID="John.3.16"><q who="Jesus">For God so loved the world</q></verse>
+<verse sID="John.3.17" osisID="John.3.17"/><q who="Jesus" sID="q1"/>For God sent not<q eID="q1"/><verse eID="John.3.17"/>
+<verse osisID="John.3.18">He that believeth</verse>"#,
+        );
+
+        assert_eq!(import.flattened.get("words of Jesus"), Some(&2));
+        let report = import.finish("TEST");
+        assert_eq!(
+            report.index.verse(BibleBook::John, 3, 17),
+            Some("For God sent not")
+        );
+    }
+
+    #[test]
+    fn warnings() {
+        let report = read(
+            r#"<verse osisID="Gen.1.1 Gen.1.2">In the beginning</verse>
+<verse osisID="Xyz.1.1">Unknown</verse>
+<verse osisID="Gen.1">Too short</verse>
+<verse>No id</verse>
+<verse eID="Gen.1.3"/>
+<verse sID="Gen.1.4" osisID="Gen.1.4"/>Never ends"#,
+        )
+        .finish("TEST");
+
+        let warnings = report.warnings.join("\n");
+        for warning in [
+            "Verses Gen.1.1 Gen.1.2 are combined, the text is kept under Gen.1.1",
+            "Unknown book Xyz, 1 verses skipped",
+            "Verse Gen.1 isn't book.chapter.verse",
+            "Verse without an osisID on line 5",
+            "End of verse Gen.1.3 that never started",
+            "Verse Gen.1.4 never ends",
+        ] {
+            assert!(warnings.contains(warning), "{}", warning);
+        }
+        assert_eq!(
+            report.index.chapter(BibleBook::Genesis, 1),
+            ["In the beginning"]
+        );
+    }
+
+    #[test]
+    fn work_and_language() {
+        let mut import = Import::default();
+        parse(
+            r#"<osis><osisText osisIDWork="KJV" xml:lang="en-GB"></osisText></osis>"#,
+            &mut import,
+        )
+        .unwrap();
+        assert_eq!(import.translation.as_deref(), Some("KJV"));
+        assert_eq!(import.language.as_deref(), Some("en-gb"));
+    }
+}
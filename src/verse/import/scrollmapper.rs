@@ -0,0 +1,440 @@
+use crate::verse::{
+    db::BibleBook,
+    import::Import,
+    json::{self, Value},
+    parse::parse_book,
+};
+
+/*
+The CSV and JSON exports of scrollmapper's Bible Databases, where verse's own SQLite
+database comes from. The CSV has a header naming its columns, older exports number
+the books, newer ones name them:
+
+    "id","b","c","v","t"
+    1001001,1,1,1,"In the beginning God created the heaven and the earth."
+
+    Book,Chapter,Verse,Text
+    Genesis,1,1,"In the beginning God created the heaven and the earth."
+
+The JSON is either rows of the same fields, or nested books, chapters and verses:
+
+    {"resultset":{"row":[{"field":[1001001,1,1,1,"In the beginning..."]}, ...]}}
+    {"translation":"KJV: King James Version","books":[{"name":"Genesis",
+        "chapters":[{"chapter":1,"verses":[{"verse":1,"text":"In the beginning..."}]}]}]}
+*/
+
+//Header names of the columns, in lower case
+const BOOK_NUMBER: [&str; 4] = ["b", "book_id", "book number", "book_number"];
+const BOOK_NAME: [&str; 3] = ["book", "book name", "book_name"];
+const CHAPTER: [&str; 2] = ["c", "chapter"];
+const VERSE: [&str; 2] = ["v", "verse"];
+const TEXT: [&str; 2] = ["t", "text"];
+
+pub fn parse_csv(csv: &str, import: &mut Import) -> Result<(), String> {
+    let mut rows = csv_rows(csv.strip_prefix('\u{feff}').unwrap_or(csv));
+    let header = rows.next().ok_or("the file is empty")??;
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|name| names.contains(&name.trim().to_lowercase().as_str()))
+    };
+
+    let book_column = match (column(&BOOK_NUMBER), column(&BOOK_NAME)) {
+        (Some(number), _) => Ok(number),
+        (None, Some(name)) => Err(name),
+        (None, None) => return Err("no book column in the header".to_string()),
+    };
+    let (Some(chapter), Some(verse), Some(text)) =
+        (column(&CHAPTER), column(&VERSE), column(&TEXT))
+    else {
+        return Err(format!(
+            "the header {} doesn't name the chapter, verse and text columns",
+            header.join(",")
+        ));
+    };
+
+    let rows = rows.collect::<Result<Vec<_>, _>>()?;
+    let douay = match book_column {
+        Ok(_) => false,
+        Err(name) => douay_kings(
+            rows.iter()
+                .filter_map(|row| row.get(name))
+                .map(String::as_str),
+        ),
+    };
+
+    for (line, row) in (2..).zip(rows) {
+        let field = |i: usize| row.get(i).map_or("", |field| field.trim());
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+
+        let book = match book_column {
+            Ok(number) => book_from_number(field(number)),
+            Err(name) => book_from_name(field(name), douay),
+        };
+        add_verse(
+            import,
+            book,
+            field(chapter).parse().ok(),
+            field(verse).parse().ok(),
+            field(text),
+            &format!("row {}", line),
+        );
+    }
+    Ok(())
+}
+
+pub fn parse_json(text: &str, import: &mut Import) -> Result<(), String> {
+    let json = json::parse(text)?;
+
+    //[id, book, chapter, verse, text]
+    if let Some(rows) = json
+        .get("resultset")
+        .and_then(|resultset| resultset.get("row"))
+        .and_then(Value::as_array)
+    {
+        for (i, row) in rows.iter().enumerate() {
+            let fields = row.get("field").and_then(Value::as_array).unwrap_or(&[]);
+            let number = |i: usize| fields.get(i).and_then(Value::as_u64);
+            add_verse(
+                import,
+                book_from_number(&number(1).unwrap_or_default().to_string()),
+                number(2).and_then(|number| number.try_into().ok()),
+                number(3).and_then(|number| number.try_into().ok()),
+                fields.get(4).and_then(Value::as_str).unwrap_or_default(),
+                &format!("row {}", i + 1),
+            );
+        }
+        return Ok(());
+    }
+
+    let books = json
+        .get("books")
+        .and_then(Value::as_array)
+        .ok_or("neither a resultset nor books")?;
+    let douay = douay_kings(
+        books
+            .iter()
+            .filter_map(|book| book.get("name").and_then(Value::as_str)),
+    );
+    //"KJV: King James Version (1769) with ..."
+    if let Some((name, _)) = json
+        .get("translation")
+        .and_then(Value::as_str)
+        .and_then(|translation| translation.split_once(':'))
+    {
+        import.translation = Some(name.trim().to_string());
+    }
+
+    for book in books {
+        let name = book.get("name").and_then(Value::as_str).unwrap_or_default();
+        let chapters = book
+            .get("chapters")
+            .and_then(Value::as_array)
+            .unwrap_or(&[]);
+        for chapter in chapters {
+            let number = chapter.get("chapter").and_then(Value::as_u64);
+            let verses = chapter
+                .get("verses")
+                .and_then(Value::as_array)
+                .unwrap_or(&[]);
+            for verse in verses {
+                add_verse(
+                    import,
+                    book_from_name(name, douay),
+                    number.and_then(|number| number.try_into().ok()),
+                    verse
+                        .get("verse")
+                        .and_then(Value::as_u64)
+                        .and_then(|number| number.try_into().ok()),
+                    verse
+                        .get("text")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default(),
+                    name,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn add_verse(
+    import: &mut Import,
+    book: Result<BibleBook, String>,
+    chapter: Option<u8>,
+    verse: Option<u8>,
+    text: &str,
+    location: &str,
+) {
+    match (book, chapter, verse) {
+        (Ok(book), Some(chapter), Some(verse)) => {
+            import.verse(book, chapter, verse, text.to_string())
+        }
+        (Err(book), _, _) => import.unmapped(&book),
+        _ => import.warn(format!(
+            "Bad chapter or verse number in {}, skipped",
+            location
+        )),
+    }
+}
+
+//1 Genesis to 66 Revelation, other numbers aren't verse's
+fn book_from_number(number: &str) -> Result<BibleBook, String> {
+    number
+        .parse()
+        .ok()
+        .filter(|number: &u8| *number <= BibleBook::Revelation as u8)
+        .and_then(|number| BibleBook::try_from(number).ok())
+        .ok_or_else(|| number.to_string())
+}
+
+//Douay-Rheims names verse doesn't know, in lower case
+const DOUAY: [(&str, BibleBook); 22] = [
+    ("josue", BibleBook::Joshua),
+    ("1 paralipomenon", BibleBook::FirstChronicles),
+    ("2 paralipomenon", BibleBook::SecondChronicles),
+    ("tobias", BibleBook::Tobit),
+    ("canticle of canticles", BibleBook::SongOfSolomon),
+    ("canticles", BibleBook::SongOfSolomon),
+    ("ecclesiasticus", BibleBook::Sirach),
+    ("isaias", BibleBook::Isaiah),
+    ("jeremias", BibleBook::Jeremiah),
+    ("ezechiel", BibleBook::Ezekiel),
+    ("osee", BibleBook::Hosea),
+    ("abdias", BibleBook::Obadiah),
+    ("jonas", BibleBook::Jonah),
+    ("micheas", BibleBook::Micah),
+    ("habacuc", BibleBook::Habakkuk),
+    ("sophonias", BibleBook::Zephaniah),
+    ("aggeus", BibleBook::Haggai),
+    ("zacharias", BibleBook::Zechariah),
+    ("malachias", BibleBook::Malachi),
+    ("1 machabees", BibleBook::FirstMaccabees),
+    ("2 machabees", BibleBook::SecondMaccabees),
+    ("apocalypse", BibleBook::Revelation),
+];
+
+//Whether the names count Samuel and Kings as 1-4 Kings like Douay-Rheims does
+pub fn douay_kings<'a>(mut names: impl Iterator<Item = &'a str>) -> bool {
+    names.any(|name| arabic(name).eq_ignore_ascii_case("4 kings"))
+}
+
+//"I Samuel" as "1 Samuel"
+fn arabic(name: &str) -> String {
+    let name = name.trim();
+    match name.split_once(' ') {
+        Some(("I", rest)) => format!("1 {}", rest),
+        Some(("II", rest)) => format!("2 {}", rest),
+        Some(("III", rest)) => format!("3 {}", rest),
+        Some(("IV", rest)) => format!("4 {}", rest),
+        _ => name.to_string(),
+    }
+}
+
+//"Genesis", "I Samuel", "Revelation of John", "Tobias". With douay_kings, 1-4 Kings
+//are Samuel and Kings and 1-2 Esdras Ezra and Nehemiah
+pub fn book_from_name(name: &str, douay_kings: bool) -> Result<BibleBook, String> {
+    let arabic = arabic(name).to_lowercase();
+    let douay = |name: &str| match name {
+        "1 kings" if douay_kings => Some(BibleBook::FirstSamuel),
+        "2 kings" if douay_kings => Some(BibleBook::SecondSamuel),
+        "3 kings" if douay_kings => Some(BibleBook::FirstKings),
+        "4 kings" if douay_kings => Some(BibleBook::SecondKings),
+        "1 esdras" if douay_kings => Some(BibleBook::Ezra),
+        "2 esdras" if douay_kings => Some(BibleBook::Nehemiah),
+        _ => DOUAY
+            .iter()
+            .find(|(douay, _)| *douay == name)
+            .map(|(_, book)| *book),
+    };
+
+    //Dropping words from the end, for longer names than verse uses
+    let words: Vec<&str> = arabic.split_whitespace().collect();
+    (1..=words.len())
+        .rev()
+        .map(|count| words[..count].join(" "))
+        .find_map(|name| douay(&name).or_else(|| parse_book(&name)))
+        .ok_or_else(|| name.trim().to_string())
+}
+
+//Rows of fields, fields may be quoted to hold commas, newlines and "" for a quote
+fn csv_rows(csv: &str) -> impl Iterator<Item = Result<Vec<String>, String>> + '_ {
+    let mut chars = csv.chars().peekable();
+    let mut line = 1;
+
+    std::iter::from_fn(move || {
+        chars.peek()?;
+        let mut row = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+
+        while let Some(char) = chars.next() {
+            match (quoted, char) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (false, '"') if field.trim().is_empty() => {
+                    field.clear();
+                    quoted = true;
+                }
+                (false, ',') => row.push(std::mem::take(&mut field)),
+                (false, '\n') => {
+                    line += 1;
+                    break;
+                }
+                (false, '\r') => {}
+                (_, char) => {
+                    if char == '\n' {
+                        line += 1;
+                    }
+                    field.push(char);
+                }
+            }
+        }
+
+        if quoted {
+            return Some(Err(format!("line {}: unterminated quote", line)));
+        }
+        row.push(field);
+        Some(Ok(row))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn book_names() {
+        assert_eq!(book_from_name("Genesis", false), Ok(BibleBook::Genesis));
+        assert_eq!(
+            book_from_name("I Samuel", false),
+            Ok(BibleBook::FirstSamuel)
+        );
+        assert_eq!(book_from_name("III John", false), Ok(BibleBook::ThirdJohn));
+        assert_eq!(
+            book_from_name("Revelation of John", false),
+            Ok(BibleBook::Revelation)
+        );
+        assert_eq!(book_from_name("Tobias", false), Ok(BibleBook::Tobit));
+        assert_eq!(
+            book_from_name("Apocalypse", false),
+            Ok(BibleBook::Revelation)
+        );
+        assert_eq!(
+            book_from_name(" Nothing ", false),
+            Err("Nothing".to_string())
+        );
+
+        //Douay-Rheims counts Samuel and Kings as four books of Kings
+        assert_eq!(book_from_name("1 Kings", false), Ok(BibleBook::FirstKings));
+        assert_eq!(book_from_name("1 Kings", true), Ok(BibleBook::FirstSamuel));
+        assert_eq!(book_from_name("IV Kings", true), Ok(BibleBook::SecondKings));
+        assert_eq!(book_from_name("2 Esdras", true), Ok(BibleBook::Nehemiah));
+        assert!(douay_kings(["Genesis", "IV Kings"].into_iter()));
+        assert!(!douay_kings(["Genesis", "2 Kings"].into_iter()));
+    }
+
+    #[test]
+    fn csv() {
+        let mut import = Import::default();
+        parse_csv(
+            "\u{feff}\"id\",\"b\",\"c\",\"v\",\"t\"\r\n\
+             1001001,1,1,1,\"In the beginning, God said \"\"Let there be\"\"\"\r\n\
+             1001002,1,1,2,\"And the earth\nwas without form\"\r\n\
+             ,,,,\r\n\
+             70001001,70,1,1,Unknown\r\n\
+             1001003,1,x,3,Bad\r\n",
+            &mut import,
+        )
+        .unwrap();
+        let report = import.finish("TEST");
+
+        assert_eq!(
+            report.index.chapter(BibleBook::Genesis, 1),
+            [
+                "In the beginning, God said \"Let there be\"",
+                "And the earth was without form"
+            ]
+        );
+        let warnings = report.warnings.join("\n");
+        assert!(warnings.contains("Bad chapter or verse number in row 6, skipped"));
+        assert!(warnings.contains("Unknown book 70, 1 verses skipped"));
+    }
+
+    #[test]
+    fn csv_with_book_names() {
+        let mut import = Import::default();
+        parse_csv(
+            "Book,Chapter,Verse,Text\n\
+             1 Kings,1,1,Now there was a certain man\n\
+             4 Kings,1,1,And Moab rebelled\n",
+            &mut import,
+        )
+        .unwrap();
+        let report = import.finish("TEST");
+
+        assert_eq!(
+            report.index.verse(BibleBook::FirstSamuel, 1, 1),
+            Some("Now there was a certain man")
+        );
+        assert_eq!(
+            report.index.verse(BibleBook::SecondKings, 1, 1),
+            Some("And Moab rebelled")
+        );
+    }
+
+    #[test]
+    fn csv_errors() {
+        let mut import = Import::default();
+        assert_eq!(parse_csv("", &mut import).unwrap_err(), "the file is empty");
+        assert_eq!(
+            parse_csv("c,v,t\n", &mut import).unwrap_err(),
+            "no book column in the header"
+        );
+        assert!(parse_csv("b,c,t\n", &mut import).is_err());
+        assert_eq!(
+            parse_csv("b,c,v,t\n1,1,1,\"open\n", &mut import).unwrap_err(),
+            "line 3: unterminated quote"
+        );
+    }
+
+    #[test]
+    fn json() {
+        let mut import = Import::default();
+        parse_json(
+            r#"{"resultset":{"row":[{"field":[1001001,1,1,1,"In the beginning"]},
+                {"field":[66022021,66,22,21,"The grace"]}]}}"#,
+            &mut import,
+        )
+        .unwrap();
+        let report = import.finish("TEST");
+        assert_eq!(
+            report.index.verse(BibleBook::Revelation, 22, 21),
+            Some("The grace")
+        );
+
+        let mut import = Import::default();
+        parse_json(
+            r#"{"translation":"DRC: Douay-Rheims 1899","books":[
+                {"name":"IV Kings","chapters":[{"chapter":1,"verses":[{"verse":1,"text":"And Moab"}]}]},
+                {"name":"Tobias","chapters":[{"chapter":1,"verses":[{"verse":1,"text":"Tobias"}]}]}]}"#,
+            &mut import,
+        )
+        .unwrap();
+        assert_eq!(import.translation.as_deref(), Some("DRC"));
+        let report = import.finish("TEST");
+        assert_eq!(
+            report.index.verse(BibleBook::SecondKings, 1, 1),
+            Some("And Moab")
+        );
+        assert_eq!(report.index.verse(BibleBook::Tobit, 1, 1), Some("Tobias"));
+
+        let mut import = Import::default();
+        assert!(parse_json("{}", &mut import).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::verse::{
+    db::BibleBook,
+    import::{Import, language_tag, strongs_tags},
+};
+
+/*
+TheWord modules, plain text with one verse per line and no references. A .ont file
+holds the whole Bible, .ot and .nt one testament, in the KJV versification, so the
+line number alone says which verse it is. Lines past the last verse are settings:
+
+    In the beginning God created the heaven and the earth.
+    ...
+    short.title=KJV
+    lang=en
+    title=King James Version
+
+The text has tags like <FR>words of Jesus<Fr>, <RF>a footnote<Rf>, <TS>a title<Ts>,
+<CM> for a paragraph and <WH7225> for Strong's numbers.
+*/
+
+//Which books a file holds, from its extension
+#[derive(Clone, Copy)]
+pub enum Testaments {
+    Both,
+    Old,
+    New,
+}
+
+impl Testaments {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "ont" => Some(Testaments::Both),
+            "ot" => Some(Testaments::Old),
+            "nt" => Some(Testaments::New),
+            _ => None,
+        }
+    }
+
+    //TheWord Bibles have the 66 books, the Apocrypha come in separate modules
+    fn books(self) -> &'static [BibleBook] {
+        match self {
+            Testaments::Both => &BibleBook::ALL[..66],
+            Testaments::Old => &BibleBook::ALL[..39],
+            Testaments::New => &BibleBook::ALL[39..66],
+        }
+    }
+}
+
+//Tags whose content up to the closing tag isn't verse text: notes, titles, cross references
+const SKIPPED: [(&str, &str); 3] = [("<RF", "<Rf>"), ("<TS", "<Ts>"), ("<RX", "<Rx>")];
+
+pub fn parse(text: &str, testaments: Testaments, import: &mut Import) -> Result<(), String> {
+    let mut lines = text.strip_prefix('\u{feff}').unwrap_or(text).lines();
+
+    let mut expected = 0;
+    let mut read = 0;
+    for book in testaments.books() {
+        for chapter in 1..=book.max_chapter_count() {
+            for verse in 1..=book.kjv_verse_count(chapter) {
+                expected += 1;
+                let Some(line) = lines.next() else {
+                    continue;
+                };
+                read += 1;
+                add_verse(import, *book, chapter, verse, line);
+            }
+        }
+    }
+    if read < expected {
+        return Err(format!(
+            "only {} lines, the books of this file have {} verses",
+            read, expected
+        ));
+    }
+
+    for line in lines {
+        if let Some(name) = line.trim().strip_prefix("short.title=") {
+            import.translation = Some(name.trim().to_string());
+        }
+        if let Some(language) = line.trim().strip_prefix("lang=") {
+            import.language = language_tag(language);
+        }
+    }
+    Ok(())
+}
+
+fn add_verse(import: &mut Import, book: BibleBook, chapter: u8, verse: u8, line: &str) {
+    let mut text = String::with_capacity(line.len());
+    let mut red_letter = false;
+    let mut rest = line;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some((_, end)) = SKIPPED.iter().find(|(open, _)| rest.starts_with(open)) {
+            rest = rest.find(end).map_or("", |i| &rest[i + end.len()..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            //A lone <, part of the text
+            text.push_str(rest);
+            rest = "";
+            break;
+        };
+        match &rest[..end + 1] {
+            "<FR>" => red_letter = true,
+            "<CM>" | "<CL>" | "<br>" | "<br/>" => text.push(' '),
+            //Strong's numbers like <WH7225> are kept, morphology like <WTH8804> isn't
+            tag => {
+                if let Some(number) = tag.strip_prefix("<W").and_then(|tag| tag.strip_suffix('>')) {
+                    text.push_str(&strongs_tags(number, book));
+                }
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+
+    if red_letter {
+        import.flatten("words of Jesus");
+    }
+    import.verse(book, chapter, verse, text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //A line for every New Testament verse, then the settings
+    fn new_testament(verse: impl Fn(BibleBook, u8, u8) -> String) -> String {
+        let mut lines = vec![];
+        for book in Testaments::New.books() {
+            for chapter in 1..=book.max_chapter_count() {
+                for number in 1..=book.kjv_verse_count(chapter) {
+                    lines.push(verse(*book, chapter, number));
+                }
+            }
+        }
+        lines.extend(["short.title=KJV".to_string(), "lang=en".to_string()]);
+        lines.join("\r\n")
+    }
+
+    #[test]
+    fn verses_by_line() {
+        let text = new_testament(|book, chapter, verse| match (book, chapter, verse) {
+            (BibleBook::John, 3, 16) => {
+                "<FR>For God<WG2316><WTG5656> so loved<Fr><RF>Or, cared for<Rf> the world<CM>"
+                    .to_string()
+            }
+            (BibleBook::John, 3, 17) => "<TS>Sent<Ts>For God sent not 1 < 2".to_string(),
+            _ => format!("{} {}:{}", book.as_str(), chapter, verse),
+        });
+        let mut import = Import::default();
+        parse(&text, Testaments::New, &mut import).unwrap();
+
+        assert_eq!(import.translation.as_deref(), Some("KJV"));
+        assert_eq!(import.language.as_deref(), Some("en"));
+        assert_eq!(import.flattened.get("words of Jesus"), Some(&1));
+
+        let report = import.finish("TEST");
+        assert_eq!(report.warnings, Vec::<String>::new());
+        assert_eq!(report.books, 27);
+        assert_eq!(
+            report.index.chapter(BibleBook::John, 3)[15..17],
+            [
+                "For God{G2316} so loved the world",
+                "For God sent not 1 < 2"
+            ]
+        );
+        assert_eq!(
+            report.index.verse(BibleBook::Revelation, 22, 21),
+            Some("Revelation 22:21")
+        );
+    }
+
+    #[test]
+    fn too_few_lines() {
+        let mut import = Import::default();
+        let error = parse("In the beginning", Testaments::Old, &mut import).unwrap_err();
+        assert_eq!(
+            error,
+            "only 1 lines, the books of this file have 23145 verses"
+        );
+
+        assert!(Testaments::from_extension("ONT").is_some());
+        assert!(Testaments::from_extension("txt").is_none());
+    }
+}
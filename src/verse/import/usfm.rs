@@ -0,0 +1,397 @@
+use crate::verse::{
+    db::BibleBook,
+    import::{Import, strongs_tags},
+};
+
+/*
+USFM, plain text with backslash markers, usually one book per file:
+
+    \id GEN
+    \c 1
+    \s1 The Creation
+    \p
+    \v 1 In the beginning \nd God\nd* created\f + \fr 1:1 \ft Or made\f* ...
+    \q1
+    \v 2 \wj Blessed are the poor\wj* ...
+
+Paragraph markers like \p and \q1 hold verse text, the ones in HEADINGS don't.
+Character markers like \wj ... \wj* only style the text, notes are left out. The
+Strong's numbers of \w words|strong="H1697"\w* are kept as tags.
+*/
+
+//Paragraphs whose text isn't verse text: ids, titles, headings, introductions, remarks
+pub const HEADINGS: [&str; 42] = [
+    "id", "ide", "h", "toc", "toca", "mt", "mte", "ms", "mr", "s", "sr", "r", "d", "sp", "sd",
+    "rem", "sts", "restore", "cl", "cp", "cd", "imt", "is", "ip", "ipi", "im", "imi", "ipq", "imq",
+    "ipr", "iq", "ib", "ili", "iot", "io", "iex", "imte", "ie", "periph", "usfm", "lit", "qa",
+];
+
+//Spans whose text isn't verse text, up to their end marker: notes, cross references,
+//figures and alternate numbers
+pub const NOTES: [&str; 11] = [
+    "f", "fe", "ef", "x", "ex", "fig", "ca", "va", "vp", "rq", "cat",
+];
+
+//Markers that only style text, inside a paragraph
+const CHARACTERS: [&str; 38] = [
+    "add", "bk", "dc", "k", "nd", "ord", "pn", "png", "addpn", "qt", "sig", "sls", "tl", "wj",
+    "em", "bd", "it", "bdit", "no", "sc", "sup", "w", "rb", "pro", "wg", "wh", "wa", "jmp", "qs",
+    "qac", "litl", "lik", "liv", "ior", "iqt", "ndx", "xt", "fv",
+];
+
+//Marker name without its level or nesting, \+wj and \q2 are wj and q
+pub fn base_marker(marker: &str) -> &str {
+    marker
+        .trim_start_matches('+')
+        .trim_end_matches(|char: char| char.is_ascii_digit())
+}
+
+enum Token<'a> {
+    Marker(&'a str),
+    End(&'a str),
+    Text(&'a str),
+}
+
+fn tokens(usfm: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut rest = usfm;
+
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        let Some(marker) = rest.strip_prefix('\\') else {
+            let end = rest.find('\\').unwrap_or(rest.len());
+            let text = &rest[..end];
+            rest = &rest[end..];
+            return Some(Token::Text(text));
+        };
+
+        let end = marker
+            .find(|char: char| char.is_whitespace() || char == '\\' || char == '*')
+            .unwrap_or(marker.len());
+        let name = &marker[..end];
+        rest = &marker[end..];
+
+        if let Some(after) = rest.strip_prefix('*') {
+            rest = after;
+            return Some(Token::End(name));
+        }
+        //One space separates the marker from its text
+        let mut chars = rest.chars();
+        if chars.next().is_some_and(char::is_whitespace) {
+            rest = chars.as_str();
+        }
+        Some(Token::Marker(name))
+    })
+}
+
+//What the text after \id, \c or \v starts with
+#[derive(Clone, Copy, PartialEq)]
+enum Awaiting {
+    Nothing,
+    Book,
+    Chapter,
+    Verse,
+}
+
+//A verse being read, with whether it has words of Jesus or poetry
+pub struct Verse {
+    pub number: u8,
+    pub text: String,
+    pub red_letter: bool,
+    pub poetry: bool,
+}
+
+impl Verse {
+    pub fn new(number: u8) -> Self {
+        Verse {
+            number,
+            text: String::new(),
+            red_letter: false,
+            poetry: false,
+        }
+    }
+}
+
+pub fn parse(usfm: &str, import: &mut Import) -> Result<(), String> {
+    //Ok with the book, or Err with a code verse doesn't know
+    let mut book: Option<Result<BibleBook, String>> = None;
+    let mut chapter = 0;
+    let mut verse: Option<Verse> = None;
+    let mut awaiting = Awaiting::Nothing;
+
+    let mut heading = false;
+    let mut poetry = false;
+    let mut notes: Vec<&str> = vec![];
+    let mut words_of_jesus: u32 = 0;
+    //Inside the attributes of e.g. \w grace|strong="H2580"\w*
+    let mut attributes = false;
+
+    for token in tokens(usfm.strip_prefix('\u{feff}').unwrap_or(usfm)) {
+        match token {
+            Token::Marker(name) => {
+                let marker = base_marker(name);
+                attributes = false;
+
+                if !notes.is_empty() {
+                    if NOTES.contains(&marker) {
+                        notes.push(marker);
+                    }
+                    continue;
+                }
+
+                match marker {
+                    "id" | "c" | "v" => {
+                        finish_verse(import, &book, chapter, verse.take());
+                        awaiting = match marker {
+                            "id" => Awaiting::Book,
+                            "c" => Awaiting::Chapter,
+                            _ => Awaiting::Verse,
+                        };
+                        heading = marker == "id";
+                    }
+                    _ if NOTES.contains(&marker) => notes.push(marker),
+                    "wj" => words_of_jesus += 1,
+                    _ if CHARACTERS.contains(&marker) => {}
+                    _ if HEADINGS.contains(&marker) => {
+                        (heading, poetry) = (true, false);
+                    }
+                    //Any other marker starts a paragraph of verse text
+                    _ => {
+                        (heading, poetry) = (false, marker.starts_with('q'));
+                        if let Some(verse) = &mut verse {
+                            verse.text.push(' ');
+                        }
+                    }
+                }
+            }
+
+            Token::End(name) => {
+                let marker = base_marker(name);
+                attributes = false;
+
+                if !notes.is_empty() {
+                    if notes.last() == Some(&marker) {
+                        notes.pop();
+                    }
+                } else if marker == "wj" {
+                    words_of_jesus = words_of_jesus.saturating_sub(1);
+                }
+            }
+
+            Token::Text(mut text) => {
+                if awaiting != Awaiting::Nothing {
+                    let trimmed = text.trim_start();
+                    let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+                    let (word, rest) = trimmed.split_at(end);
+                    text = rest;
+
+                    match awaiting {
+                        Awaiting::Book => {
+                            book = Some(BibleBook::from_usfm_code(word).ok_or(word.to_string()));
+                            chapter = 0;
+                        }
+                        Awaiting::Chapter => {
+                            chapter = word.parse().map_err(|_| {
+                                format!("bad chapter number \\c {} in {}", word, book_code(&book))
+                            })?;
+                        }
+                        Awaiting::Verse => {
+                            verse = verse_number(import, &book, chapter, word).map(Verse::new);
+                        }
+                        Awaiting::Nothing => {}
+                    }
+                    awaiting = Awaiting::Nothing;
+                }
+
+                if !notes.is_empty() || heading || attributes {
+                    continue;
+                }
+                let mut numbers = "";
+                if let Some((before, after)) = text.split_once('|') {
+                    text = before;
+                    attributes = true;
+                    numbers = attribute(after, "strong").unwrap_or_default();
+                }
+
+                if let Some(verse) = &mut verse {
+                    verse.text.push_str(text);
+                    if !text.trim().is_empty() {
+                        verse.red_letter |= words_of_jesus > 0;
+                        verse.poetry |= poetry;
+                    }
+                    if !numbers.is_empty()
+                        && let Some(Ok(book)) = &book
+                    {
+                        verse.text.push_str(&strongs_tags(numbers, *book));
+                    }
+                }
+            }
+        }
+    }
+
+    finish_verse(import, &book, chapter, verse.take());
+    if book.is_none() {
+        return Err("no \\id marker naming the book".to_string());
+    }
+    Ok(())
+}
+
+//Value of an attribute of a word, strong in \w grace|strong="H2580"\w*
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let start = attributes.find(&format!("{}=\"", name))? + name.len() + 2;
+    let length = attributes[start..].find('"')?;
+    Some(&attributes[start..start + length])
+}
+
+pub fn book_code(book: &Option<Result<BibleBook, String>>) -> String {
+    match book {
+        Some(Ok(book)) => book.usfm_code().to_string(),
+        Some(Err(code)) => code.clone(),
+        None => "a file without \\id".to_string(),
+    }
+}
+
+//Number of "\v 3", "\v 3-4" are combined verses kept under 3, "\v 3a" a part of 3
+pub fn verse_number(
+    import: &mut Import,
+    book: &Option<Result<BibleBook, String>>,
+    chapter: u8,
+    word: &str,
+) -> Option<u8> {
+    let digits = word
+        .find(|char: char| !char.is_ascii_digit())
+        .unwrap_or(word.len());
+    let Ok(number) = word[..digits].parse() else {
+        import.warn(format!(
+            "Bad verse number \\v {} in {} {}",
+            word,
+            book_code(book),
+            chapter
+        ));
+        return None;
+    };
+
+    if word[digits..].starts_with('-') {
+        import.warn(format!(
+            "Verses {} {}:{} are combined, the text is kept under {}",
+            book_code(book),
+            chapter,
+            word,
+            number
+        ));
+    }
+    Some(number)
+}
+
+pub fn finish_verse(
+    import: &mut Import,
+    book: &Option<Result<BibleBook, String>>,
+    chapter: u8,
+    verse: Option<Verse>,
+) {
+    let Some(verse) = verse else {
+        return;
+    };
+
+    match book {
+        Some(Ok(book)) => {
+            if verse.red_letter {
+                import.flatten("words of Jesus");
+            }
+            if verse.poetry {
+                import.flatten("poetry lines");
+            }
+            import.verse(*book, chapter, verse.number, verse.text);
+        }
+        Some(Err(code)) => import.unmapped(code),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(usfm: &str) -> Import {
+        let mut import = Import::default();
+        parse(usfm, &mut import).unwrap();
+        import
+    }
+
+    #[test]
+    fn verse_text() {
+        let report = read(
+            "\u{feff}\\id GEN World English Bible\n\
+             \\h Genesis\n\
+             \\mt1 The First Book of Moses\n\
+             \\c 1\n\
+             \\s1 The Creation\n\
+             \\p\n\
+             \\v 1 In the beginning \\nd God\\nd* created\\f + \\fr 1:1 \\ft Or made\\f* the heavens.\n\
+             \\v 2 The earth was \\w formless|strong=\"H8414\"\\w* and empty.\n\
+             \\c 2\n\
+             \\p \\v 1 The heavens were finished.\n",
+        )
+        .finish("TEST");
+
+        assert_eq!(
+            report.index.chapter(BibleBook::Genesis, 1),
+            [
+                "In the beginning God created the heavens.",
+                "The earth was formless{H8414} and empty."
+            ]
+        );
+        assert_eq!(
+            report.index.verse(BibleBook::Genesis, 2, 1),
+            Some("The heavens were finished.")
+        );
+    }
+
+    #[test]
+    fn flattened_features() {
+        let import = read(
+            "\\id MAT\n\\c 5\n\\p\n\
+             \\v 3 \\wj Blessed are the poor in spirit\\wj*\n\
+             \\q1\n\\v 4 Blessed are they that mourn\n\\q2 for they shall be comforted.\n",
+        );
+
+        assert_eq!(import.flattened.get("words of Jesus"), Some(&1));
+        assert_eq!(import.flattened.get("poetry lines"), Some(&1));
+        let report = import.finish("TEST");
+        assert_eq!(
+            report.index.verse(BibleBook::Matthew, 5, 4),
+            Some("Blessed are they that mourn for they shall be comforted.")
+        );
+    }
+
+    #[test]
+    fn warnings() {
+        let report = read(
+            "\\id GEN\n\\c 1\n\\p\n\\v 1-2 In the beginning\n\\v x Nothing\n\
+             \\id XYZ\n\\c 1\n\\p\n\\v 1 Unknown\n",
+        )
+        .finish("TEST");
+
+        let warnings = report.warnings.join("\n");
+        for warning in [
+            "Verses GEN 1:1-2 are combined, the text is kept under 1",
+            "Bad verse number \\v x in GEN 1",
+            "Unknown book XYZ, 1 verses skipped",
+        ] {
+            assert!(warnings.contains(warning), "{}", warning);
+        }
+        assert_eq!(
+            report.index.chapter(BibleBook::Genesis, 1),
+            ["In the beginning"]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut import = Import::default();
+        assert!(parse("\\c 1\n\\v 1 No book", &mut import).is_err());
+        assert!(parse("\\id GEN\n\\c one\n", &mut import).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use crate::verse::{
+    db::BibleBook,
+    import::{
+        Import, strongs_tags,
+        usfm::{self, HEADINGS, NOTES, Verse, base_marker},
+    },
+    xml::{Event, Reader},
+};
+
+/*
+USX, USFM as XML. The markers become style attributes:
+
+    <book code="GEN" style="id"/>
+    <chapter number="1" style="c" sid="GEN 1"/>
+    <para style="p"><verse number="1" style="v" sid="GEN 1:1"/>In the beginning
+        <char style="wj">...</char><note style="f" caller="+">...</note><verse eid="GEN 1:1"/></para>
+
+USX 2 has no eid, a verse then runs up to the next verse or chapter. Strong's numbers
+of <char style="w" strong="H7225"> are kept as tags.
+*/
+
+pub fn parse(xml: &str, import: &mut Import) -> Result<(), String> {
+    let mut book: Option<Result<BibleBook, String>> = None;
+    let mut chapter = 0;
+    let mut verse: Option<Verse> = None;
+
+    //Depth inside an element that is skipped
+    let mut skipping = 0;
+    //Styles of the open para and char elements
+    let mut styles: Vec<String> = vec![];
+    //Strong's numbers of open char elements, with how many styles were open before them
+    let mut numbers: Vec<(usize, String)> = vec![];
+
+    let mut reader = Reader::new(xml);
+    while let Some(event) = reader.next() {
+        let event = event?;
+
+        if skipping > 0 {
+            match event {
+                Event::Start { empty: false, .. } => skipping += 1,
+                Event::End(_) => skipping -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Text(text) => {
+                if let Some(verse) = &mut verse {
+                    verse.text.push_str(&text);
+                    if !text.trim().is_empty() {
+                        verse.red_letter |= styles.iter().any(|style| style == "wj");
+                        verse.poetry |= styles.iter().any(|style| style.starts_with('q'));
+                    }
+                }
+            }
+
+            Event::Start {
+                name: "book",
+                empty,
+                ..
+            } => {
+                usfm::finish_verse(import, &book, chapter, verse.take());
+                let code = event.attribute("code").unwrap_or_default();
+                book = Some(BibleBook::from_usfm_code(code).ok_or(code.to_string()));
+                chapter = 0;
+                //The rest of the \id line
+                if !empty {
+                    skipping = 1;
+                }
+            }
+
+            Event::Start {
+                name: "chapter",
+                empty,
+                ..
+            } => {
+                if !empty {
+                    styles.push(String::new());
+                }
+                usfm::finish_verse(import, &book, chapter, verse.take());
+                if let Some(number) = event.attribute("number") {
+                    chapter = number.parse().map_err(|_| {
+                        format!(
+                            "line {}: bad chapter number {} in {}",
+                            reader.line(),
+                            number,
+                            usfm::book_code(&book)
+                        )
+                    })?;
+                }
+            }
+
+            Event::Start {
+                name: "verse",
+                empty,
+                ..
+            } => {
+                if !empty {
+                    styles.push(String::new());
+                }
+                usfm::finish_verse(import, &book, chapter, verse.take());
+                if let Some(number) = event.attribute("number") {
+                    verse = usfm::verse_number(import, &book, chapter, number).map(Verse::new);
+                }
+            }
+
+            Event::Start { name, empty, .. } => {
+                let style = base_marker(event.attribute("style").unwrap_or_default()).to_string();
+                let skipped = match name {
+                    "note" | "figure" | "sidebar" => true,
+                    "para" => HEADINGS.contains(&style.as_str()),
+                    "char" => NOTES.contains(&style.as_str()),
+                    _ => false,
+                };
+                if skipped {
+                    if !empty {
+                        skipping = 1;
+                    }
+                    continue;
+                }
+
+                if name == "para"
+                    && let Some(verse) = &mut verse
+                {
+                    verse.text.push(' ');
+                }
+                if !empty {
+                    if let Some(number) = event.attribute("strong") {
+                        numbers.push((styles.len(), number.to_string()));
+                    }
+                    styles.push(style);
+                }
+            }
+
+            Event::End(name) => {
+                styles.pop();
+                if numbers
+                    .last()
+                    .is_some_and(|(depth, _)| *depth == styles.len())
+                {
+                    let (_, number) = numbers.pop().unwrap_or_default();
+                    if let (Some(verse), Some(Ok(book))) = (&mut verse, &book) {
+                        verse.text.push_str(&strongs_tags(&number, *book));
+                    }
+                }
+                if name == "para"
+                    && let Some(verse) = &mut verse
+                {
+                    verse.text.push(' ');
+                }
+            }
+        }
+    }
+
+    usfm::finish_verse(import, &book, chapter, verse.take());
+    if book.is_none() {
+        return Err("no <book> element naming the book".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(body: &str) -> Import {
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<usx version="3.0">{}</usx>"#,
+            body
+        );
+        let mut import = Import::default();
+        parse(&xml, &mut import).unwrap();
+        import
+    }
+
+    #[test]
+    fn verse_text() {
+        let report = read(
+            r#"<book code="GEN" style="id">World English Bible</book>
+<para style="mt1">The First Book of Moses</para>
+<chapter number="1" style="c" sid="GEN 1"/>
+<para style="s1">The Creation</para>
+<para style="p"><verse number="1" style="v" sid="GEN 1:1"/>In the beginning <char style="nd">God</char> created<note style="f" caller="+"><char style="ft">Or made</char></note> the heavens.<verse eid="GEN 1:1"/>
+<verse number="2" style="v" sid="GEN 1:2"/>The earth was <char style="w" strong="H8414">formless</char> and empty.<verse eid="GEN 1:2"/></para>
+<chapter eid="GEN 1"/>"#,
+        )
+        .finish("TEST");
+
+        assert_eq!(
+            report.index.chapter(BibleBook::Genesis, 1),
+            [
+                "In the beginning God created the heavens.",
+                "The earth was formless{H8414} and empty."
+            ]
+        );
+    }
+
+    #[test]
+    fn usx2_and_flattened_features() {
+        //No eid, verses run up to the next one
+        let import = read(
+            r#"<book code="MAT" style="id"/>
+<chapter number="5" style="c"/>
+<para style="p"><verse number="3" style="v"/><char style="wj">Blessed are the poor in spirit</char></para>
+<para style="q1"><verse number="4" style="v"/>Blessed are they that mourn</para>
+<para style="q2">for they shall be comforted.</para>"#,
+        );
+
+        assert_eq!(import.flattened.get("words of Jesus"), Some(&1));
+        assert_eq!(import.flattened.get("poetry lines"), Some(&1));
+        let report = import.finish("TEST");
+        assert_eq!(
+            report.index.chapter(BibleBook::Matthew, 5)[2..],
+            [
+                "Blessed are the poor in spirit",
+                "Blessed are they that mourn for they shall be comforted."
+            ]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut import = Import::default();
+        assert!(parse(r#"<usx><chapter number="1"/></usx>"#, &mut import).is_err());
+        assert!(
+            parse(
+                r#"<usx><book code="GEN"/><chapter number="one"/></usx>"#,
+                &mut import
+            )
+            .is_err()
+        );
+
+        let report = read(r#"<book code="XYZ"/><chapter number="1"/><verse number="1"/>Text"#)
+            .finish("TEST");
+        assert_eq!(report.warnings, ["Unknown book XYZ, 1 verses skipped"]);
+    }
+}
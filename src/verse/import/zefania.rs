@@ -0,0 +1,259 @@
+use crate::verse::{
+    db::BibleBook,
+    import::{Import, language_tag, strongs_tags},
+    parse::parse_book,
+    xml::{Event, Reader},
+};
+
+/*
+Zefania XML, books numbered in canonical order, 1 Genesis to 66 Revelation. The
+Apocrypha are numbered differently from verse, they're found by their name instead:
+
+    <XMLBIBLE biblename="King James Version">
+      <INFORMATION><identifier>KJV</identifier><language>ENG</language>...</INFORMATION>
+      <BIBLEBOOK bnumber="1" bname="Genesis">
+        <CHAPTER cnumber="1">
+          <CAPTION>The Creation</CAPTION>
+          <VERS vnumber="1">In the <gr str="7225">beginning</gr> ...<NOTE>...</NOTE></VERS>
+
+Element names differ in case between files, so they're compared ignoring it.
+*/
+
+//Elements whose content is never verse text
+const SKIPPED: [&str; 5] = ["note", "caption", "xref", "remark", "media"];
+
+pub fn parse(xml: &str, import: &mut Import) -> Result<(), String> {
+    let mut reader = Reader::new(xml);
+
+    //Ok with the book, or Err with a number verse doesn't know
+    let mut book: Option<Result<BibleBook, String>> = None;
+    let mut chapter = 0;
+    //Verse being read, its number and text
+    let mut verse: Option<(u8, String)> = None;
+    let mut red_letter = false;
+
+    //Depth inside an element that is skipped
+    let mut skipping = 0;
+    //Inside <identifier>, which names the translation
+    let mut identifier = false;
+    //Inside <language>, the language it's in
+    let mut language = false;
+    //Open <STYLE> elements, true for red letter ones
+    let mut styles: Vec<bool> = vec![];
+    //Strong's numbers of the open <gr> elements, put after their word
+    let mut numbers: Vec<String> = vec![];
+
+    while let Some(event) = reader.next() {
+        let event = event?;
+
+        if skipping > 0 {
+            match event {
+                Event::Start { empty: false, .. } => skipping += 1,
+                Event::End(_) => skipping -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Text(text) => {
+                if identifier && import.translation.is_none() {
+                    import.translation = Some(text.trim().to_string());
+                }
+                if language && import.language.is_none() {
+                    import.language = language_tag(&text);
+                }
+                if let Some((_, content)) = &mut verse {
+                    content.push_str(&text);
+                    if !text.trim().is_empty() {
+                        red_letter |= styles.contains(&true);
+                    }
+                }
+            }
+
+            Event::Start { name, empty, .. } => {
+                let name = name.to_lowercase();
+                if SKIPPED.contains(&name.as_str()) {
+                    if !empty {
+                        skipping = 1;
+                    }
+                    continue;
+                }
+
+                match name.as_str() {
+                    "identifier" => identifier = !empty,
+                    "language" => language = !empty,
+                    "biblebook" => {
+                        let number = event.attribute("bnumber").unwrap_or_default();
+                        let name = event.attribute("bname");
+                        book = Some(
+                            number
+                                .parse()
+                                .ok()
+                                .filter(|number: &u8| *number <= BibleBook::Revelation as u8)
+                                .and_then(|number| BibleBook::try_from(number).ok())
+                                .or_else(|| name.and_then(parse_book))
+                                .ok_or(match name {
+                                    Some(name) => format!("{} ({})", number, name),
+                                    None => number.to_string(),
+                                }),
+                        );
+                        chapter = 0;
+                    }
+                    "chapter" => {
+                        let number = event.attribute("cnumber").unwrap_or_default();
+                        chapter = number.parse().map_err(|_| {
+                            format!("line {}: bad chapter number {}", reader.line(), number)
+                        })?;
+                    }
+                    "vers" => {
+                        let number = event.attribute("vnumber").unwrap_or_default();
+                        match number.parse() {
+                            Ok(number) if !empty => verse = Some((number, String::new())),
+                            Ok(_) => {}
+                            Err(_) => import.warn(format!(
+                                "Bad verse number {} on line {}",
+                                number,
+                                reader.line()
+                            )),
+                        }
+                        red_letter = false;
+                    }
+                    "gr" if !empty => {
+                        numbers.push(event.attribute("str").unwrap_or_default().to_string())
+                    }
+                    "style" if !empty => {
+                        let css = event.attribute("css").unwrap_or_default().to_lowercase();
+                        styles.push(css.contains("red") || css.contains("#ff0000"))
+                    }
+                    "br" => {
+                        if let Some((_, content)) = &mut verse {
+                            content.push(' ');
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            Event::End(name) => match name.to_lowercase().as_str() {
+                "identifier" => identifier = false,
+                "language" => language = false,
+                "style" => {
+                    styles.pop();
+                }
+                "gr" => {
+                    let number = numbers.pop().unwrap_or_default();
+                    if let (Some((_, content)), Some(Ok(book))) = (&mut verse, &book) {
+                        content.push_str(&strongs_tags(&number, *book));
+                    }
+                }
+                "vers" => {
+                    if let Some((number, text)) = verse.take() {
+                        match &book {
+                            Some(Ok(book)) => {
+                                if red_letter {
+                                    import.flatten("words of Jesus");
+                                }
+                                import.verse(*book, chapter, number, text);
+                            }
+                            Some(Err(book)) => import.unmapped(book),
+                            None => import.warn(format!(
+                                "Verse {} outside a BIBLEBOOK on line {}",
+                                number,
+                                reader.line()
+                            )),
+                        }
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    if book.is_none() {
+        return Err("no BIBLEBOOK elements".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(books: &str) -> Import {
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<XMLBIBLE biblename="King James Version">
+<INFORMATION><identifier>KJV</identifier><language>ENG</language></INFORMATION>
+{}
+</XMLBIBLE>"#,
+            books
+        );
+        let mut import = Import::default();
+        parse(&xml, &mut import).unwrap();
+        import
+    }
+
+    #[test]
+    fn verse_text() {
+        let import = read(
+            r#"<BIBLEBOOK bnumber="1" bname="Genesis"><CHAPTER cnumber="1">
+<CAPTION>The Creation</CAPTION>
+<VERS vnumber="1">In the <gr str="7225">beginning</gr> God created<NOTE>Or made</NOTE> the heaven.</VERS>
+<vers vnumber="2">And the earth<BR/>was without form.</vers>
+</CHAPTER></BIBLEBOOK>
+<BIBLEBOOK bnumber="43" bname="John"><CHAPTER cnumber="3">
+<VERS vnumber="3"><STYLE css="color:#ff0000">Verily, verily</STYLE>, I say unto thee</VERS>
+</CHAPTER></BIBLEBOOK>"#,
+        );
+
+        assert_eq!(import.translation.as_deref(), Some("KJV"));
+        assert_eq!(import.language.as_deref(), Some("eng"));
+        assert_eq!(import.flattened.get("words of Jesus"), Some(&1));
+
+        let report = import.finish("TEST");
+        assert_eq!(
+            report.index.chapter(BibleBook::Genesis, 1),
+            [
+                "In the beginning{H7225} God created the heaven.",
+                "And the earth was without form."
+            ]
+        );
+        assert_eq!(
+            report.index.verse(BibleBook::John, 3, 3),
+            Some("Verily, verily, I say unto thee")
+        );
+    }
+
+    #[test]
+    fn apocrypha_by_name() {
+        let report = read(
+            r#"<BIBLEBOOK bnumber="67" bname="Tobit"><CHAPTER cnumber="1"><VERS vnumber="1">Tobit</VERS></CHAPTER></BIBLEBOOK>
+<BIBLEBOOK bnumber="90" bname="Unknown"><CHAPTER cnumber="1"><VERS vnumber="1">?</VERS></CHAPTER></BIBLEBOOK>
+<BIBLEBOOK bnumber="91"><CHAPTER cnumber="1"><VERS vnumber="x">?</VERS></CHAPTER></BIBLEBOOK>"#,
+        )
+        .finish("TEST");
+
+        assert_eq!(report.index.verse(BibleBook::Tobit, 1, 1), Some("Tobit"));
+        let warnings = report.warnings.join("\n");
+        for warning in [
+            "Bad verse number x on line 6",
+            "Unknown book 90 (Unknown), 1 verses skipped",
+        ] {
+            assert!(warnings.contains(warning), "{}", warning);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let mut import = Import::default();
+        assert!(parse("<XMLBIBLE></XMLBIBLE>", &mut import).is_err());
+        assert!(
+            parse(
+                r#"<XMLBIBLE><BIBLEBOOK bnumber="1"><CHAPTER cnumber="one"/></BIBLEBOOK></XMLBIBLE>"#,
+                &mut import
+            )
+            .is_err()
+        );
+    }
+}
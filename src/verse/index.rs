@@ -0,0 +1,416 @@
+use std::{fmt, mem};
+
+use crate::verse::{
+    db::{BibleBook, Verse},
+    strongs::{self, Strong},
+    versification::Versification,
+};
+
+/*
+A whole translation held in memory. All verse texts sit back to back in one string,
+verse_starts holds where each verse begins (plus the end of the last one) and
+chapter_starts the first verse of every chapter in canonical order (plus the verse
+count). A verse is found with two lookups and no allocation:
+
+    chapter = book.chapter_offset() + chapter - 1
+    verse   = chapter_starts[chapter] + verse - 1
+    text    = text[verse_starts[verse]..verse_starts[verse + 1]]
+*/
+pub struct BibleIndex {
+    pub translation: String,
+    //Numbering the translation was in, the verses are kept in the English one
+    pub versification: Versification,
+    //Language the translation is in, a code like "es" if it's known
+    pub language: Option<String>,
+    text: Text,
+    verse_starts: Vec<u32>,
+    chapter_starts: Vec<u32>,
+}
+
+//The verse texts, read into memory or mapped from a compiled file
+pub enum Text {
+    Owned(String),
+    #[cfg(feature = "mmap")]
+    Mapped {
+        map: memmap2::Mmap,
+        start: usize,
+        end: usize,
+    },
+}
+
+impl Text {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Text::Owned(text) => text,
+            //SAFETY: checked to be UTF-8 when the file was mapped, and the map is read only
+            #[cfg(feature = "mmap")]
+            Text::Mapped { map, start, end } => unsafe {
+                std::str::from_utf8_unchecked(&map[*start..*end])
+            },
+        }
+    }
+}
+
+impl BibleIndex {
+    //Builds the index from numbered verses in canonical order. Verses are found by their
+    //place in the chapter, so ones missing from the source are kept as empty texts and a
+    //number that's already taken is skipped
+    pub fn build(
+        translation: &str,
+        verses: impl IntoIterator<Item = (BibleBook, u8, u8, String)>,
+    ) -> BibleIndex {
+        let mut text = String::new();
+        let mut index = BibleIndex {
+            translation: translation.to_string(),
+            versification: Versification::English,
+            language: None,
+            text: Text::Owned(String::new()),
+            verse_starts: vec![],
+            chapter_starts: Vec::with_capacity(BibleBook::TOTAL_CHAPTERS as usize + 1),
+        };
+
+        for (book, chapter, verse, verse_text) in verses {
+            //Chapters the book doesn't have would land in the next book
+            if chapter < 1 || chapter > book.max_chapter_count() {
+                continue;
+            }
+
+            //Chapters up to this one start here, any skipped ones stay empty
+            let position = book.chapter_offset() as usize + chapter as usize - 1;
+            if position + 1 < index.chapter_starts.len() {
+                continue;
+            }
+            while index.chapter_starts.len() <= position {
+                index.chapter_starts.push(index.verse_starts.len() as u32);
+            }
+
+            let count = index.verse_starts.len() - index.chapter_starts[position] as usize;
+            if (verse as usize) <= count {
+                continue;
+            }
+            for _ in count + 1..verse as usize {
+                index.verse_starts.push(text.len() as u32);
+            }
+            index.verse_starts.push(text.len() as u32);
+            text.push_str(&verse_text);
+        }
+
+        index.chapter_starts.resize(
+            BibleBook::TOTAL_CHAPTERS as usize + 1,
+            index.verse_starts.len() as u32,
+        );
+        index.verse_starts.push(text.len() as u32);
+
+        text.shrink_to_fit();
+        index.text = Text::Owned(text);
+        index.verse_starts.shrink_to_fit();
+        index
+    }
+
+    //Index over text and tables read back from a file, checked so no lookup can go out of bounds
+    pub fn from_parts(
+        translation: &str,
+        text: Text,
+        verse_starts: Vec<u32>,
+        chapter_starts: Vec<u32>,
+    ) -> Result<BibleIndex, String> {
+        let str = text.as_str();
+        let ascending = |starts: &[u32]| starts.windows(2).all(|pair| pair[0] <= pair[1]);
+
+        if chapter_starts.len() != BibleBook::TOTAL_CHAPTERS as usize + 1
+            || chapter_starts.first() != Some(&0)
+            || !ascending(&chapter_starts)
+            || chapter_starts.last().map(|last| *last as usize + 1) != Some(verse_starts.len())
+        {
+            return Err("chapter table doesn't match the verses".to_string());
+        }
+        if verse_starts.first() != Some(&0)
+            || !ascending(&verse_starts)
+            || verse_starts.last().map(|last| *last as usize) != Some(str.len())
+            || !verse_starts
+                .iter()
+                .all(|start| str.is_char_boundary(*start as usize))
+        {
+            return Err("verse table doesn't match the text".to_string());
+        }
+
+        Ok(BibleIndex {
+            translation: translation.to_string(),
+            versification: Versification::English,
+            language: None,
+            text,
+            verse_starts,
+            chapter_starts,
+        })
+    }
+
+    //Text and tables, for writing the index to a file
+    pub fn parts(&self) -> (&str, &[u32], &[u32]) {
+        (self.text.as_str(), &self.verse_starts, &self.chapter_starts)
+    }
+
+    //Range of verse numbers (from 0) the chapter covers
+    fn chapter_range(&self, book: BibleBook, chapter: u8) -> Option<(usize, usize)> {
+        if chapter < 1 || chapter > book.max_chapter_count() {
+            return None;
+        }
+        let position = book.chapter_offset() as usize + chapter as usize - 1;
+
+        Some((
+            self.chapter_starts[position] as usize,
+            self.chapter_starts[position + 1] as usize,
+        ))
+    }
+
+    fn text_of(&self, verse: usize) -> &str {
+        &self.text.as_str()
+            [self.verse_starts[verse] as usize..self.verse_starts[verse + 1] as usize]
+    }
+
+    //None for verses the chapter doesn't have, or that were missing from the source
+    pub fn verse(&self, book: BibleBook, chapter: u8, verse: u8) -> Option<&str> {
+        let (first, end) = self.chapter_range(book, chapter)?;
+        let verse = first + (verse as usize).checked_sub(1)?;
+
+        (verse < end)
+            .then(|| self.text_of(verse))
+            .filter(|text| !text.is_empty())
+    }
+
+    pub fn chapter(&self, book: BibleBook, chapter: u8) -> Vec<&str> {
+        match self.chapter_range(book, chapter) {
+            Some((first, end)) => (first..end).map(|verse| self.text_of(verse)).collect(),
+            None => vec![],
+        }
+    }
+
+    //Range of verses (from 0 over the whole text) the books from first to last cover
+    fn books_range(&self, first: BibleBook, last: BibleBook) -> (usize, usize) {
+        let start = first.chapter_offset() as usize;
+        let end = last.chapter_offset() as usize + last.max_chapter_count() as usize;
+
+        (
+            self.chapter_starts[start] as usize,
+            self.chapter_starts[end] as usize,
+        )
+    }
+
+    //Book, chapter and verse number of a verse counted from 0 over the whole text
+    fn locate(&self, verse: usize) -> Verse {
+        //Last chapter starting at or before the verse, skipped empty ones start at the same verse
+        let position = self
+            .chapter_starts
+            .partition_point(|start| *start as usize <= verse)
+            - 1;
+        let book = *BibleBook::ALL
+            .iter()
+            .rev()
+            .find(|book| book.chapter_offset() as usize <= position)
+            .expect("Genesis starts at 0");
+
+        Verse {
+            book,
+            chapter: (position - book.chapter_offset() as usize + 1) as u8,
+            verse: (verse - self.chapter_starts[position] as usize + 1) as u8,
+            text: self.text_of(verse).to_string(),
+        }
+    }
+
+    //Number of verses in the books from first to last
+    pub fn count_verses(&self, first: BibleBook, last: BibleBook) -> u32 {
+        let (start, end) = self.books_range(first, last);
+        (end - start) as u32
+    }
+
+    //The nth verse (from 0) of the books from first to last
+    pub fn nth_verse(&self, first: BibleBook, last: BibleBook, n: u32) -> Option<Verse> {
+        let (start, end) = self.books_range(first, last);
+        let verse = start + n as usize;
+
+        (verse < end).then(|| self.locate(verse))
+    }
+
+    //Verses of the books from first to last containing the words, ignoring case and
+    //Strong's tags
+    pub fn search(&self, words: &str, first: BibleBook, last: BibleBook, limit: u32) -> Vec<Verse> {
+        let words = words.to_lowercase();
+        let (start, end) = self.books_range(first, last);
+
+        (start..end)
+            .filter(|verse| {
+                strongs::strip(self.text_of(*verse))
+                    .to_lowercase()
+                    .contains(&words)
+            })
+            .take(limit as usize)
+            .map(|verse| self.locate(verse))
+            .collect()
+    }
+
+    //Verses of the books from first to last with a word tagged with the number
+    pub fn tagged(&self, strong: Strong, first: BibleBook, last: BibleBook) -> Vec<Verse> {
+        let tag = strong.tag();
+        let (start, end) = self.books_range(first, last);
+
+        (start..end)
+            .filter(|verse| self.text_of(*verse).contains(&tag))
+            .map(|verse| self.locate(verse))
+            .collect()
+    }
+
+    pub fn verse_count(&self) -> usize {
+        self.verse_starts.len() - 1
+    }
+
+    //Bytes held by the text and the offset tables, mapped text counts as well
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            text: self.text.as_str().len(),
+            offsets: (self.verse_starts.capacity() + self.chapter_starts.capacity())
+                * mem::size_of::<u32>(),
+        }
+    }
+}
+
+pub struct MemoryUsage {
+    pub text: usize,
+    pub offsets: usize,
+}
+
+impl MemoryUsage {
+    pub fn mib(&self) -> f64 {
+        (self.text + self.offsets) as f64 / (1024.0 * 1024.0)
+    }
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mib = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+        write!(
+            f,
+            "{:.1} MiB ({:.1} MiB text, {:.1} MiB offsets)",
+            mib(self.text + self.offsets),
+            mib(self.text),
+            mib(self.offsets)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Matthew 17 without verse 21, as modern translations print it
+    fn index() -> BibleIndex {
+        BibleIndex::build(
+            "TEST",
+            [
+                (BibleBook::Genesis, 1, 1, "In the beginning".to_string()),
+                (BibleBook::Genesis, 1, 2, "And the earth".to_string()),
+                (BibleBook::Genesis, 3, 1, "Now the serpent".to_string()),
+                (
+                    BibleBook::Matthew,
+                    17,
+                    20,
+                    "Because of your unbelief".to_string(),
+                ),
+                (
+                    BibleBook::Matthew,
+                    17,
+                    22,
+                    "While they were staying".to_string(),
+                ),
+                (BibleBook::Matthew, 17, 22, "A second verse 22".to_string()),
+                (
+                    BibleBook::Matthew,
+                    17,
+                    23,
+                    "And they will kill him".to_string(),
+                ),
+                (
+                    BibleBook::Matthew,
+                    40,
+                    1,
+                    "Past the last chapter".to_string(),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn verses_keep_their_numbers() {
+        let index = index();
+        assert_eq!(index.verse(BibleBook::Genesis, 1, 2), Some("And the earth"));
+        assert_eq!(
+            index.verse(BibleBook::Matthew, 17, 20),
+            Some("Because of your unbelief")
+        );
+        assert_eq!(index.verse(BibleBook::Matthew, 17, 21), None);
+        assert_eq!(
+            index.verse(BibleBook::Matthew, 17, 22),
+            Some("While they were staying")
+        );
+        assert_eq!(
+            index.verse(BibleBook::Matthew, 17, 23),
+            Some("And they will kill him")
+        );
+        assert_eq!(index.verse(BibleBook::Matthew, 17, 24), None);
+        assert_eq!(index.verse(BibleBook::Matthew, 17, 0), None);
+        assert_eq!(index.chapter(BibleBook::Matthew, 17).len(), 23);
+        assert!(index.chapter(BibleBook::Genesis, 2).is_empty());
+        assert!(index.chapter(BibleBook::Matthew, 29).is_empty());
+    }
+
+    #[test]
+    fn search_locates_verses() {
+        let index = index();
+        let found = index.search("KILL", BibleBook::Genesis, BibleBook::Revelation, 10);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            (found[0].book, found[0].chapter, found[0].verse),
+            (BibleBook::Matthew, 17, 23)
+        );
+        assert_eq!(
+            index
+                .search("the", BibleBook::Genesis, BibleBook::Malachi, 10)
+                .len(),
+            3
+        );
+        assert_eq!(
+            index
+                .search("the", BibleBook::Genesis, BibleBook::Malachi, 1)
+                .len(),
+            1
+        );
+
+        let verse = index
+            .nth_verse(BibleBook::Genesis, BibleBook::Genesis, 2)
+            .unwrap();
+        assert_eq!((verse.chapter, verse.verse), (3, 1));
+        assert_eq!(
+            index.count_verses(BibleBook::Genesis, BibleBook::Genesis),
+            3
+        );
+    }
+
+    #[test]
+    fn from_parts_checks_tables() {
+        let chapters = |verses: u32| {
+            let mut starts = vec![0; BibleBook::TOTAL_CHAPTERS as usize + 1];
+            starts[1..].fill(verses);
+            starts
+        };
+        let text = || Text::Owned("In the beginningAnd the earth".to_string());
+
+        let index = BibleIndex::from_parts("TEST", text(), vec![0, 16, 29], chapters(2)).unwrap();
+        assert_eq!(index.verse(BibleBook::Genesis, 1, 2), Some("And the earth"));
+
+        assert!(BibleIndex::from_parts("TEST", text(), vec![0, 16, 29], chapters(3)).is_err());
+        assert!(BibleIndex::from_parts("TEST", text(), vec![0, 16, 28], chapters(2)).is_err());
+        assert!(BibleIndex::from_parts("TEST", text(), vec![0, 17, 16, 29], chapters(3)).is_err());
+        assert!(BibleIndex::from_parts("TEST", text(), vec![0, 16, 29], vec![0, 2]).is_err());
+
+        let text = Text::Owned("Güte".to_string());
+        assert!(BibleIndex::from_parts("TEST", text, vec![0, 2, 5], chapters(2)).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+/*
+Just enough of a JSON parser for importing Bibles: the whole document is read into
+Values, objects keep their keys in file order.
+*/
+
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    //Member of an object
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    //A whole number, also from a string like "3"
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(number) if number.fract() == 0.0 && *number >= 0.0 => {
+                Some(*number as u64)
+            }
+            Value::String(string) => string.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse(json: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        json: json.strip_prefix('\u{feff}').unwrap_or(json),
+        position: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.position < parser.json.len() {
+        return Err(parser.error("text after the end"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    json: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        let line = self.json[..self.position].matches('\n').count() + 1;
+        format!("line {}: {}", line, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.json[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    //Moves past the next character if it's the one expected
+    fn eat(&mut self, char: char) -> bool {
+        self.skip_whitespace();
+        if self.json[self.position..].starts_with(char) {
+            self.position += char.len_utf8();
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, char: char) -> Result<(), String> {
+        match self.eat(char) {
+            true => Ok(()),
+            false => Err(self.error(&format!("expected {}", char))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        let rest = &self.json[self.position..];
+
+        for (word, value) in [
+            ("null", Value::Null),
+            ("true", Value::Bool(true)),
+            ("false", Value::Bool(false)),
+        ] {
+            if rest.starts_with(word) {
+                self.position += word.len();
+                return Ok(value);
+            }
+        }
+
+        match rest.chars().next() {
+            Some('"') => self.string().map(Value::String),
+            Some('[') => {
+                self.position += 1;
+                let mut values = vec![];
+                if !self.eat(']') {
+                    loop {
+                        values.push(self.value()?);
+                        if self.eat(']') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                Ok(Value::Array(values))
+            }
+            Some('{') => {
+                self.position += 1;
+                let mut members = vec![];
+                if !self.eat('}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string()?;
+                        self.expect(':')?;
+                        members.push((key, self.value()?));
+                        if self.eat('}') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                Ok(Value::Object(members))
+            }
+            Some('-' | '0'..='9') => {
+                let end = rest
+                    .find(|char: char| !matches!(char, '-' | '+' | '.' | 'e' | 'E' | '0'..='9'))
+                    .unwrap_or(rest.len());
+                let number = rest[..end]
+                    .parse()
+                    .map_err(|_| self.error(&format!("bad number {}", &rest[..end])))?;
+                self.position += end;
+                Ok(Value::Number(number))
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if !self.json[self.position..].starts_with('"') {
+            return Err(self.error("expected a string"));
+        }
+        self.position += 1;
+
+        let mut string = String::new();
+        loop {
+            let rest = &self.json[self.position..];
+            let end = rest
+                .find(['"', '\\'])
+                .ok_or_else(|| self.error("unterminated string"))?;
+            string.push_str(&rest[..end]);
+            self.position += end + 1;
+            if rest[end..].starts_with('"') {
+                return Ok(string);
+            }
+
+            let escape = self.json[self.position..]
+                .chars()
+                .next()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.position += escape.len_utf8();
+            match escape {
+                '"' | '\\' | '/' => string.push(escape),
+                'b' => string.push('\u{8}'),
+                'f' => string.push('\u{c}'),
+                'n' => string.push('\n'),
+                'r' => string.push('\r'),
+                't' => string.push('\t'),
+                'u' => {
+                    let mut code = self.hex()?;
+                    //Characters past U+FFFF come as a pair, \ud83d\ude00
+                    if (0xd800..0xdc00).contains(&code)
+                        && self.json[self.position..].starts_with("\\u")
+                    {
+                        self.position += 2;
+                        let low = self.hex()?;
+                        code =
+                            0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                    }
+                    string.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                _ => return Err(self.error(&format!("bad escape \\{}", escape))),
+            }
+        }
+    }
+
+    //The four digits of \u
+    fn hex(&mut self) -> Result<u32, String> {
+        let digits = self.json[self.position..]
+            .get(..4)
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("bad \\u escape"))?;
+        self.position += 4;
+        Ok(digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document() {
+        let value = parse(
+            "\u{feff} {\"books\": [{\"name\": \"Genesis\", \"chapters\": 50}, \"x\"],
+                \"ok\": true, \"none\": null, \"scale\": -1.5e2}",
+        )
+        .unwrap();
+
+        let books = value.get("books").and_then(Value::as_array).unwrap();
+        assert_eq!(books.len(), 2);
+        assert_eq!(
+            books[0].get("name").and_then(Value::as_str),
+            Some("Genesis")
+        );
+        assert_eq!(books[0].get("chapters").and_then(Value::as_u64), Some(50));
+        assert_eq!(books[1].as_str(), Some("x"));
+        assert!(matches!(value.get("ok"), Some(Value::Bool(true))));
+        assert!(matches!(value.get("none"), Some(Value::Null)));
+        assert!(matches!(value.get("scale"), Some(Value::Number(n)) if *n == -150.0));
+        assert!(value.get("missing").is_none());
+    }
+
+    #[test]
+    fn keys_in_file_order() {
+        let Value::Object(members) = parse("{\"b\": 1, \"a\": 2, \"c\": {}}").unwrap() else {
+            panic!("not an object");
+        };
+        let keys: Vec<&str> = members.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn escapes() {
+        let value = parse(r#""a\"b\\c\/d\n\t\u00e9\ud83d\ude00""#).unwrap();
+        assert_eq!(value.as_str(), Some("a\"b\\c/d\n\té😀"));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse("\"3\"").unwrap().as_u64(), Some(3));
+        assert_eq!(parse("3").unwrap().as_u64(), Some(3));
+        assert_eq!(parse("3.5").unwrap().as_u64(), None);
+        assert_eq!(parse("-3").unwrap().as_u64(), None);
+    }
+
+    #[test]
+    fn errors() {
+        for json in [
+            "",
+            "{",
+            "[1, 2",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{a: 1}",
+            "\"unterminated",
+            "\"\\x\"",
+            "\"\\u12\"",
+            "1 2",
+            "--1",
+        ] {
+            assert!(parse(json).is_err(), "{:?}", json);
+        }
+        assert_eq!(
+            parse("[1,\n  x]").err().unwrap(),
+            "line 2: expected a value"
+        );
+    }
+}
@@ -0,0 +1,704 @@
+use std::{fmt, fs, io};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::verse::store::data_dir;
+
+//What has the keyboard, decides which bindings apply
+#[derive(Copy, Clone, PartialEq)]
+pub enum Mode {
+    Reader,
+    BookMenu,
+    ChapterMenu,
+    Plan,
+    Progress,
+    //A cursor on the words with Strong's numbers
+    Words,
+    Lexicon,
+    //Typing the words to search for
+    Search,
+    //Moving the end of a selection of verses
+    Selection,
+    Help,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 10] = [
+        Mode::Reader,
+        Mode::BookMenu,
+        Mode::ChapterMenu,
+        Mode::Plan,
+        Mode::Progress,
+        Mode::Words,
+        Mode::Lexicon,
+        Mode::Search,
+        Mode::Selection,
+        Mode::Help,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Mode::Reader => "Reader",
+            Mode::BookMenu => "Book menu",
+            Mode::ChapterMenu => "Chapter menu",
+            Mode::Plan => "Reading plan",
+            Mode::Progress => "Reading progress",
+            Mode::Words => "Words",
+            Mode::Lexicon => "Lexicon",
+            Mode::Search => "Search",
+            Mode::Selection => "Selection",
+            Mode::Help => "Help",
+        }
+    }
+
+    //Shown in the status bar
+    pub fn indicator(&self) -> &'static str {
+        match self {
+            Mode::Reader => "NORMAL",
+            Mode::BookMenu => "BOOK",
+            Mode::ChapterMenu => "CHAPTER",
+            Mode::Plan => "PLAN",
+            Mode::Progress => "PROGRESS",
+            Mode::Words => "WORD",
+            Mode::Lexicon => "LEXICON",
+            Mode::Search => "SEARCH",
+            Mode::Selection => "SELECT",
+            Mode::Help => "HELP",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Action {
+    Quit,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    Top,
+    Bottom,
+    PrevChapter,
+    NextChapter,
+    PrevBook,
+    NextBook,
+    CycleOrder,
+    OpenBookMenu,
+    OpenChapterMenu,
+    ToggleContinuous,
+    OpenPlan,
+    ToggleRead,
+    OpenProgress,
+    OpenWords,
+    //Opens the search prompt, and searches from it
+    Search,
+    NextMatch,
+    PrevMatch,
+    SelectVerses,
+    OpenHelp,
+
+    //Menus
+    Close,
+    Confirm,
+    Erase,
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    CursorFirst,
+    CursorLast,
+    PrevDay,
+    NextDay,
+    ToggleDone,
+    PrevNumber,
+    NextNumber,
+    KeepSelection,
+}
+
+impl Action {
+    pub const ALL: [Action; 41] = [
+        Action::Quit,
+        Action::ScrollUp,
+        Action::ScrollDown,
+        Action::PageUp,
+        Action::PageDown,
+        Action::HalfPageUp,
+        Action::HalfPageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::PrevChapter,
+        Action::NextChapter,
+        Action::PrevBook,
+        Action::NextBook,
+        Action::CycleOrder,
+        Action::OpenBookMenu,
+        Action::OpenChapterMenu,
+        Action::ToggleContinuous,
+        Action::OpenPlan,
+        Action::ToggleRead,
+        Action::OpenProgress,
+        Action::OpenWords,
+        Action::Search,
+        Action::NextMatch,
+        Action::PrevMatch,
+        Action::SelectVerses,
+        Action::OpenHelp,
+        Action::Close,
+        Action::Confirm,
+        Action::Erase,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::CursorUp,
+        Action::CursorDown,
+        Action::CursorFirst,
+        Action::CursorLast,
+        Action::PrevDay,
+        Action::NextDay,
+        Action::ToggleDone,
+        Action::PrevNumber,
+        Action::NextNumber,
+        Action::KeepSelection,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::ScrollUp => "Scroll up",
+            Action::ScrollDown => "Scroll down",
+            Action::PageUp => "Page up",
+            Action::PageDown => "Page down",
+            Action::HalfPageUp => "Half page up",
+            Action::HalfPageDown => "Half page down",
+            Action::Top => "Start of chapter",
+            Action::Bottom => "End of chapter",
+            Action::PrevChapter => "Previous chapter",
+            Action::NextChapter => "Next chapter",
+            Action::PrevBook => "Previous book",
+            Action::NextBook => "Next book",
+            Action::CycleOrder => "Switch book order",
+            Action::OpenBookMenu => "Pick a book",
+            Action::OpenChapterMenu => "Pick a chapter",
+            Action::ToggleContinuous => "Toggle continuous reading",
+            Action::OpenPlan => "Today's reading plan",
+            Action::ToggleRead => "Mark chapter as read",
+            Action::OpenProgress => "Reading progress",
+            Action::OpenWords => "Look up a word",
+            Action::Search => "Search",
+            Action::NextMatch => "Next match",
+            Action::PrevMatch => "Previous match",
+            Action::SelectVerses => "Select verses",
+            Action::OpenHelp => "Show this help",
+            Action::Close => "Close",
+            Action::Confirm => "Open selection",
+            Action::Erase => "Erase last character",
+            Action::CursorLeft => "Move left",
+            Action::CursorRight => "Move right",
+            Action::CursorUp => "Move up",
+            Action::CursorDown => "Move down",
+            Action::CursorFirst => "Move to first",
+            Action::CursorLast => "Move to last",
+            Action::PrevDay => "Previous day",
+            Action::NextDay => "Next day",
+            Action::ToggleDone => "Check off passage",
+            Action::PrevNumber => "Previous Strong's number",
+            Action::NextNumber => "Next Strong's number",
+            Action::KeepSelection => "Keep selection",
+        }
+    }
+}
+
+//A single key press, shift is folded into the character itself
+#[derive(Copy, Clone, PartialEq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub const fn new(code: KeyCode) -> Self {
+        Key {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub const fn ctrl(c: char) -> Self {
+        Key {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    //A key as the help shows it, "g", "Ctrl-d" or "PgDn", names in any case. "Comma"
+    //stands for the comma that separates keys in keys.txt
+    pub fn parse(name: &str) -> Option<Key> {
+        let prefixed = |prefix: &str| {
+            name.get(..prefix.len())
+                .filter(|start| start.eq_ignore_ascii_case(prefix) && name.len() > prefix.len())
+                .map(|_| &name[prefix.len()..])
+        };
+        let (modifiers, name) = match (prefixed("ctrl-"), prefixed("alt-")) {
+            (Some(name), _) => (KeyModifiers::CONTROL, name),
+            (_, Some(name)) => (KeyModifiers::ALT, name),
+            _ => (KeyModifiers::NONE, name),
+        };
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match name.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "comma" => KeyCode::Char(','),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pgup" | "pageup" => KeyCode::PageUp,
+                "pgdn" | "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "tab" => KeyCode::Tab,
+                _ => return None,
+            },
+        };
+
+        Some(Key { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Up => "Up".to_string(),
+            KeyCode::Down => "Down".to_string(),
+            KeyCode::Left => "Left".to_string(),
+            KeyCode::Right => "Right".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            code => format!("{:?}", code),
+        };
+
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            return write!(f, "Ctrl-{}", name);
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            return write!(f, "Alt-{}", name);
+        }
+        write!(f, "{}", name)
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let code = match event.code {
+            //Terminals differ in whether shift is reported for upper case letters
+            KeyCode::Char(c) => KeyCode::Char(c),
+            code => {
+                modifiers |= event.modifiers & KeyModifiers::SHIFT;
+                code
+            }
+        };
+
+        Key { code, modifiers }
+    }
+}
+
+pub struct Binding {
+    pub mode: Mode,
+    pub keys: Vec<Key>,
+    pub action: Action,
+}
+
+impl Binding {
+    pub fn keys_to_string(&self) -> String {
+        self.keys
+            .iter()
+            .map(|key| key.to_string())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+pub enum Lookup {
+    Action(Action),
+    //The keys so far are the start of a longer binding
+    Pending,
+    None,
+}
+
+/*
+The keys are the built-in ones below with the user's changes from data_dir()/keys.txt,
+a section per mode named as in the help and a line per action with the keys that run
+it, separated by commas:
+
+    # Comments and blank lines are ignored
+    [Reader]
+    Scroll down: j, Down
+    Scroll up: k, Up
+    Start of chapter: g g, Home
+    Mark chapter as read:
+
+Keys are written as the help shows them. The keys given replace the ones the action had
+in the mode and whatever else they did there, none after the colon unbind the action.
+*/
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    //The keys with the user's changes, and the lines of keys.txt that couldn't be read
+    pub fn load() -> (Keymap, Vec<String>) {
+        let mut keymap = Keymap::default();
+        let path = data_dir().join("keys.txt");
+
+        let errors = match fs::read_to_string(&path) {
+            Ok(contents) => keymap
+                .read(&contents)
+                .into_iter()
+                .map(|error| format!("{}:{}", path.display(), error))
+                .collect(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+            Err(err) => vec![format!("{}: {}", path.display(), err)],
+        };
+        (keymap, errors)
+    }
+
+    //Applies a keys.txt to the keymap, returns the lines that were bad
+    fn read(&mut self, contents: &str) -> Vec<String> {
+        let mut mode = None;
+        let mut section = false;
+        let mut errors = vec![];
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                section = true;
+                mode = Mode::ALL
+                    .into_iter()
+                    .find(|mode| mode.as_str().eq_ignore_ascii_case(name.trim()));
+                if mode.is_none() {
+                    errors.push(format!("{}: unknown mode {}", i + 1, name));
+                }
+                continue;
+            }
+
+            //The lines of an unknown mode were reported with it
+            let Some(mode) = mode else {
+                if !section {
+                    errors.push(format!("{}: keys before a [mode]", i + 1));
+                }
+                continue;
+            };
+            let Some((name, keys)) = line.split_once(':') else {
+                errors.push(format!("{}: expected \"action: keys\"", i + 1));
+                continue;
+            };
+            let Some(action) = Action::ALL
+                .into_iter()
+                .find(|action| action.description().eq_ignore_ascii_case(name.trim()))
+            else {
+                errors.push(format!("{}: unknown action {}", i + 1, name.trim()));
+                continue;
+            };
+
+            let sequences = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|keys| !keys.is_empty())
+                .map(|keys| {
+                    keys.split_whitespace()
+                        .map(Key::parse)
+                        .collect::<Option<Vec<Key>>>()
+                        .ok_or(keys)
+                })
+                .collect::<Result<Vec<Vec<Key>>, &str>>();
+            match sequences {
+                Ok(sequences) => {
+                    //The new keys go where the action's were so the help keeps its order
+                    let at = self
+                        .bindings
+                        .iter()
+                        .position(|binding| binding.mode == mode && binding.action == action)
+                        .unwrap_or(self.bindings.len());
+                    let bindings = sequences.iter().map(|keys| Binding {
+                        mode,
+                        keys: keys.clone(),
+                        action,
+                    });
+                    self.bindings.splice(at..at, bindings);
+
+                    //A sequence that starts another would never be finished
+                    let added = at..at + sequences.len();
+                    let mut i = 0;
+                    self.bindings.retain(|binding| {
+                        i += 1;
+                        added.contains(&(i - 1))
+                            || binding.mode != mode
+                            || (binding.action != action
+                                && !sequences.iter().any(|keys| {
+                                    keys.starts_with(&binding.keys)
+                                        || binding.keys.starts_with(keys)
+                                }))
+                    });
+                }
+                Err(keys) => errors.push(format!("{}: unknown key in {}", i + 1, keys)),
+            }
+        }
+
+        errors
+    }
+
+    pub fn lookup(&self, mode: Mode, keys: &[Key]) -> Lookup {
+        let mut pending = false;
+
+        for binding in self.bindings.iter().filter(|b| b.mode == mode) {
+            if binding.keys == keys {
+                return Lookup::Action(binding.action);
+            }
+            if binding.keys.starts_with(keys) {
+                pending = true;
+            }
+        }
+
+        if pending {
+            Lookup::Pending
+        } else {
+            Lookup::None
+        }
+    }
+
+    pub fn bindings(&self, mode: Mode) -> impl Iterator<Item = &Binding> {
+        self.bindings.iter().filter(move |b| b.mode == mode)
+    }
+
+    //Bindings that the pending keys are a prefix of
+    pub fn continuations(&self, mode: Mode, keys: &[Key]) -> impl Iterator<Item = &Binding> {
+        self.bindings(mode)
+            .filter(move |b| b.keys.len() > keys.len() && b.keys.starts_with(keys))
+    }
+
+    //First key sequence bound to the action, for hints
+    pub fn keys_for(&self, mode: Mode, action: Action) -> Option<String> {
+        self.bindings(mode)
+            .find(|b| b.action == action)
+            .map(Binding::keys_to_string)
+    }
+
+    fn bind(&mut self, mode: Mode, keys: &[Key], action: Action) {
+        self.bindings.push(Binding {
+            mode,
+            keys: keys.to_vec(),
+            action,
+        });
+    }
+}
+
+impl Default for Keymap {
+    #[rustfmt::skip]
+    fn default() -> Self {
+        use KeyCode::*;
+
+        let mut map = Keymap { bindings: vec![] };
+
+        map.bind(Mode::Reader, &[Key::new(Char('q'))], Action::Quit);
+        map.bind(Mode::Reader, &[Key::new(Esc)], Action::Quit);
+        map.bind(Mode::Reader, &[Key::new(Up)], Action::ScrollUp);
+        map.bind(Mode::Reader, &[Key::new(Down)], Action::ScrollDown);
+        map.bind(Mode::Reader, &[Key::new(PageUp)], Action::PageUp);
+        map.bind(Mode::Reader, &[Key::new(PageDown)], Action::PageDown);
+        map.bind(Mode::Reader, &[Key::ctrl('u')], Action::HalfPageUp);
+        map.bind(Mode::Reader, &[Key::ctrl('d')], Action::HalfPageDown);
+        map.bind(Mode::Reader, &[Key::new(Home)], Action::Top);
+        map.bind(Mode::Reader, &[Key::new(End)], Action::Bottom);
+        map.bind(Mode::Reader, &[Key::new(Char('g')), Key::new(Char('g'))], Action::Top);
+        map.bind(Mode::Reader, &[Key::new(Char('g')), Key::new(Char('e'))], Action::Bottom);
+        map.bind(Mode::Reader, &[Key::new(Left)], Action::PrevChapter);
+        map.bind(Mode::Reader, &[Key::new(Right)], Action::NextChapter);
+        map.bind(Mode::Reader, &[Key::new(Char('g')), Key::new(Char('p'))], Action::PrevBook);
+        map.bind(Mode::Reader, &[Key::new(Char('g')), Key::new(Char('n'))], Action::NextBook);
+        map.bind(Mode::Reader, &[Key::new(Char('g')), Key::new(Char('o'))], Action::CycleOrder);
+        map.bind(Mode::Reader, &[Key::new(Char('B'))], Action::OpenBookMenu);
+        map.bind(Mode::Reader, &[Key::new(Char('C'))], Action::OpenChapterMenu);
+        map.bind(Mode::Reader, &[Key::new(Char('M'))], Action::ToggleContinuous);
+        map.bind(Mode::Reader, &[Key::new(Char('P'))], Action::OpenPlan);
+        map.bind(Mode::Reader, &[Key::new(Char('R'))], Action::ToggleRead);
+        map.bind(Mode::Reader, &[Key::new(Char('O'))], Action::OpenProgress);
+        map.bind(Mode::Reader, &[Key::new(Char('W'))], Action::OpenWords);
+        map.bind(Mode::Reader, &[Key::new(Char('/'))], Action::Search);
+        map.bind(Mode::Reader, &[Key::new(Char('n'))], Action::NextMatch);
+        map.bind(Mode::Reader, &[Key::new(Char('N'))], Action::PrevMatch);
+        map.bind(Mode::Reader, &[Key::new(Char('v'))], Action::SelectVerses);
+        map.bind(Mode::Reader, &[Key::new(Char('?'))], Action::OpenHelp);
+
+        map.bind(Mode::BookMenu, &[Key::new(Char('B'))], Action::Close);
+        map.bind(Mode::BookMenu, &[Key::new(Enter)], Action::Confirm);
+        map.bind(Mode::BookMenu, &[Key::new(Esc)], Action::Close);
+        map.bind(Mode::BookMenu, &[Key::new(Backspace)], Action::Erase);
+
+        map.bind(Mode::ChapterMenu, &[Key::new(Left)], Action::CursorLeft);
+        map.bind(Mode::ChapterMenu, &[Key::new(Right)], Action::CursorRight);
+        map.bind(Mode::ChapterMenu, &[Key::new(Up)], Action::CursorUp);
+        map.bind(Mode::ChapterMenu, &[Key::new(Down)], Action::CursorDown);
+        map.bind(Mode::ChapterMenu, &[Key::new(Home)], Action::CursorFirst);
+        map.bind(Mode::ChapterMenu, &[Key::new(End)], Action::CursorLast);
+        map.bind(Mode::ChapterMenu, &[Key::new(Enter)], Action::Confirm);
+        map.bind(Mode::ChapterMenu, &[Key::new(Backspace)], Action::Erase);
+        map.bind(Mode::ChapterMenu, &[Key::new(Char('C'))], Action::Close);
+        map.bind(Mode::ChapterMenu, &[Key::new(Esc)], Action::Close);
+
+        map.bind(Mode::Plan, &[Key::new(Up)], Action::CursorUp);
+        map.bind(Mode::Plan, &[Key::new(Down)], Action::CursorDown);
+        map.bind(Mode::Plan, &[Key::new(Left)], Action::PrevDay);
+        map.bind(Mode::Plan, &[Key::new(Right)], Action::NextDay);
+        map.bind(Mode::Plan, &[Key::new(Char(' '))], Action::ToggleDone);
+        map.bind(Mode::Plan, &[Key::new(Enter)], Action::Confirm);
+        map.bind(Mode::Plan, &[Key::new(Char('P'))], Action::Close);
+        map.bind(Mode::Plan, &[Key::new(Esc)], Action::Close);
+
+        map.bind(Mode::Progress, &[Key::new(Up)], Action::CursorUp);
+        map.bind(Mode::Progress, &[Key::new(Down)], Action::CursorDown);
+        map.bind(Mode::Progress, &[Key::new(Home)], Action::CursorFirst);
+        map.bind(Mode::Progress, &[Key::new(End)], Action::CursorLast);
+        map.bind(Mode::Progress, &[Key::new(Enter)], Action::Confirm);
+        map.bind(Mode::Progress, &[Key::new(Char('O'))], Action::Close);
+        map.bind(Mode::Progress, &[Key::new(Esc)], Action::Close);
+
+        map.bind(Mode::Words, &[Key::new(Left)], Action::CursorLeft);
+        map.bind(Mode::Words, &[Key::new(Right)], Action::CursorRight);
+        map.bind(Mode::Words, &[Key::new(Up)], Action::CursorUp);
+        map.bind(Mode::Words, &[Key::new(Down)], Action::CursorDown);
+        map.bind(Mode::Words, &[Key::new(Home)], Action::CursorFirst);
+        map.bind(Mode::Words, &[Key::new(End)], Action::CursorLast);
+        map.bind(Mode::Words, &[Key::new(Enter)], Action::Confirm);
+        map.bind(Mode::Words, &[Key::new(Char('W'))], Action::Close);
+        map.bind(Mode::Words, &[Key::new(Esc)], Action::Close);
+
+        map.bind(Mode::Lexicon, &[Key::new(Up)], Action::CursorUp);
+        map.bind(Mode::Lexicon, &[Key::new(Down)], Action::CursorDown);
+        map.bind(Mode::Lexicon, &[Key::new(Left)], Action::PrevNumber);
+        map.bind(Mode::Lexicon, &[Key::new(Right)], Action::NextNumber);
+        map.bind(Mode::Lexicon, &[Key::new(Home)], Action::CursorFirst);
+        map.bind(Mode::Lexicon, &[Key::new(End)], Action::CursorLast);
+        map.bind(Mode::Lexicon, &[Key::new(Enter)], Action::Confirm);
+        map.bind(Mode::Lexicon, &[Key::new(Esc)], Action::Close);
+
+        map.bind(Mode::Search, &[Key::new(Enter)], Action::Search);
+        map.bind(Mode::Search, &[Key::new(Backspace)], Action::Erase);
+        map.bind(Mode::Search, &[Key::new(Esc)], Action::Close);
+
+        map.bind(Mode::Selection, &[Key::new(Up)], Action::CursorUp);
+        map.bind(Mode::Selection, &[Key::new(Down)], Action::CursorDown);
+        map.bind(Mode::Selection, &[Key::new(Enter)], Action::KeepSelection);
+        map.bind(Mode::Selection, &[Key::new(Char('v'))], Action::KeepSelection);
+        map.bind(Mode::Selection, &[Key::new(Esc)], Action::Close);
+
+        map.bind(Mode::Help, &[Key::new(Up)], Action::ScrollUp);
+        map.bind(Mode::Help, &[Key::new(Down)], Action::ScrollDown);
+        map.bind(Mode::Help, &[Key::new(Backspace)], Action::Erase);
+        map.bind(Mode::Help, &[Key::new(Esc)], Action::Close);
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(names: &str) -> Vec<Key> {
+        names
+            .split_whitespace()
+            .map(|name| Key::parse(name).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parse_shown_keys() {
+        let keymap = Keymap::default();
+        for binding in &keymap.bindings {
+            for key in &binding.keys {
+                assert!(Key::parse(&key.to_string()) == Some(*key), "{}", key);
+            }
+        }
+        assert!(Key::parse("ctrl-d") == Some(Key::ctrl('d')));
+        assert!(Key::parse("pagedown") == Some(Key::new(KeyCode::PageDown)));
+        assert!(Key::parse("Comma") == Some(Key::new(KeyCode::Char(','))));
+        assert!(Key::parse("Ctrl-").is_none());
+    }
+
+    #[test]
+    fn remap() {
+        let mut keymap = Keymap::default();
+        let errors = keymap.read(
+            "# Vim users stay, everyone else gets arrows\n\
+             [reader]\n\
+             Scroll down: n, Down\n\
+             Start of chapter: g g\n\
+             Quit:\n",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let reader = |names| keymap.lookup(Mode::Reader, &keys(names));
+        assert!(matches!(reader("n"), Lookup::Action(Action::ScrollDown)));
+        assert!(matches!(reader("Down"), Lookup::Action(Action::ScrollDown)));
+        assert!(matches!(reader("j"), Lookup::None));
+        assert!(matches!(reader("g"), Lookup::Pending));
+        let order: Vec<String> = keymap
+            .bindings(Mode::Reader)
+            .take(3)
+            .map(Binding::keys_to_string)
+            .collect();
+        assert_eq!(order, ["Up", "n", "Down"]);
+        assert!(keymap.keys_for(Mode::Reader, Action::Quit).is_none());
+        //Other modes keep their keys
+        assert!(keymap.keys_for(Mode::Help, Action::Close).is_some());
+    }
+
+    #[test]
+    fn bad_lines() {
+        let mut keymap = Keymap::default();
+        let errors = keymap.read(
+            "Quit: q\n\
+             [Nowhere]\n\
+             Quit: q\n\
+             [Reader]\n\
+             Quit q\n\
+             Fly: f\n\
+             Quit: Hyper-q\n",
+        );
+        assert_eq!(
+            errors,
+            [
+                "1: keys before a [mode]",
+                "2: unknown mode Nowhere",
+                "5: expected \"action: keys\"",
+                "6: unknown action Fly",
+                "7: unknown key in Hyper-q",
+            ]
+        );
+        assert!(matches!(
+            keymap.lookup(Mode::Reader, &keys("q")),
+            Lookup::Action(Action::Quit)
+        ));
+    }
+}
@@ -0,0 +1,337 @@
+use std::{collections::HashMap, fs, path::PathBuf, sync::OnceLock};
+
+use crate::verse::{db::BibleBook, store::data_dir};
+
+/*
+Book names, abbreviations and interface text in other languages. A language is a
+file named by its code, the bundled ones in locales/ are built in and the user's in
+data_dir()/locales/<code>.txt add to them, or override a bundled one entry by entry:
+
+    # Comments and blank lines are ignored
+    name: Español
+    aliases: spa, esl
+
+    [books]
+    John: Juan, Jn
+
+    [text]
+    Reading progress: Progreso de lectura
+
+Books are keyed by OSIS id, with the name shown first and then abbreviations that
+references may also use. Text is keyed by the English, which has no colon, and {}
+stands for what's filled in, in the same order. What a file leaves out stays English.
+*/
+
+const BUNDLED: [(&str, &str); 3] = [
+    ("de", include_str!("../../locales/de.txt")),
+    ("es", include_str!("../../locales/es.txt")),
+    ("ko", include_str!("../../locales/ko.txt")),
+];
+
+pub struct Locale {
+    //Language code the file is named by, "es"
+    pub code: String,
+    //Name of the language in itself, "Español"
+    pub name: String,
+    //Other codes the language goes by, like Zefania's three letter ones
+    aliases: Vec<String>,
+    //Name and abbreviations of each book
+    books: HashMap<BibleBook, Vec<String>>,
+    text: HashMap<String, String>,
+}
+
+//What goes where in a locale file
+enum Section {
+    Header,
+    Books,
+    Text,
+}
+
+//The locales with the problems found in their files
+static LOCALES: OnceLock<(Vec<Locale>, Vec<String>)> = OnceLock::new();
+
+impl Locale {
+    fn new(code: &str, name: &str) -> Locale {
+        Locale {
+            code: code.to_string(),
+            name: name.to_string(),
+            aliases: vec![],
+            books: HashMap::new(),
+            text: HashMap::new(),
+        }
+    }
+
+    //English first, then the other languages by code
+    pub fn all() -> &'static [Locale] {
+        &LOCALES.get_or_init(load).0
+    }
+
+    //Lines of the locale files that couldn't be read, they're left out
+    pub fn errors() -> &'static [String] {
+        &LOCALES.get_or_init(load).1
+    }
+
+    pub fn english() -> &'static Locale {
+        &Locale::all()[0]
+    }
+
+    //Locale of a language tag like "es", "es-MX" or "spa", ignoring case
+    pub fn find(language: &str) -> Option<&'static Locale> {
+        let language = language.trim().to_lowercase().replace('_', "-");
+        let primary = language.split('-').next().unwrap_or_default();
+
+        Locale::all()
+            .iter()
+            .find(|locale| locale.code == language)
+            .or_else(|| {
+                Locale::all().iter().find(|locale| {
+                    locale.code == primary
+                        || locale
+                            .aliases
+                            .iter()
+                            .any(|alias| *alias == language || alias == primary)
+                })
+            })
+    }
+
+    //The language chosen with verse language, else the translation's, else English
+    pub fn pick(setting: Option<&str>, translation: Option<&str>) -> &'static Locale {
+        setting
+            .and_then(Locale::find)
+            .or_else(|| translation.and_then(Locale::find))
+            .unwrap_or_else(Locale::english)
+    }
+
+    //Name of the book in the language
+    pub fn book(&self, book: BibleBook) -> &str {
+        self.books
+            .get(&book)
+            .and_then(|names| names.first())
+            .map_or(book.as_str(), String::as_str)
+    }
+
+    //Books the language names in canonical order, with the name first and then the
+    //abbreviations
+    pub fn books(&self) -> impl Iterator<Item = (BibleBook, &[String])> {
+        BibleBook::ALL
+            .into_iter()
+            .filter_map(|book| Some((book, self.books.get(&book)?.as_slice())))
+    }
+
+    //The text in the language, the English if it has none
+    pub fn text<'a>(&'a self, english: &'a str) -> &'a str {
+        self.text.get(english).map_or(english, String::as_str)
+    }
+
+    //Text with each {} replaced by the next value, "{} marked as read"
+    pub fn fill(&self, english: &str, values: &[&str]) -> String {
+        let mut parts = self.text(english).split("{}");
+        let mut filled = parts.next().unwrap_or_default().to_string();
+        for (part, value) in parts.zip(values.iter().chain(std::iter::repeat(&""))) {
+            filled.push_str(value);
+            filled.push_str(part);
+        }
+        filled
+    }
+
+    //Reads a locale file into the locale, returns the lines that were bad
+    fn read(&mut self, contents: &str) -> Vec<String> {
+        let mut section = Section::Header;
+        let mut errors = vec![];
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                match name {
+                    "books" => section = Section::Books,
+                    "text" => section = Section::Text,
+                    _ => errors.push(format!("{}: unknown section {}", i + 1, name)),
+                }
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                errors.push(format!("{}: expected \"key: value\"", i + 1));
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            match section {
+                Section::Header => match key {
+                    "name" => self.name = value.to_string(),
+                    "aliases" => {
+                        self.aliases = value
+                            .split(',')
+                            .map(|alias| alias.trim().to_lowercase())
+                            .filter(|alias| !alias.is_empty())
+                            .collect()
+                    }
+                    _ => errors.push(format!("{}: unknown setting {}", i + 1, key)),
+                },
+                Section::Books => {
+                    let names: Vec<String> = value
+                        .split(',')
+                        .map(|name| name.trim().to_string())
+                        .filter(|name| !name.is_empty())
+                        .collect();
+                    match BibleBook::from_osis_id(key) {
+                        Some(book) if !names.is_empty() => {
+                            self.books.insert(book, names);
+                        }
+                        Some(_) => errors.push(format!("{}: no name for {}", i + 1, key)),
+                        None => errors.push(format!("{}: unknown book {}", i + 1, key)),
+                    }
+                }
+                Section::Text => {
+                    self.text.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+fn load() -> (Vec<Locale>, Vec<String>) {
+    let mut locales = vec![Locale::new("en", "English")];
+    let mut errors = vec![];
+
+    for (code, contents) in BUNDLED {
+        let mut locale = Locale::new(code, code);
+        for error in locale.read(contents) {
+            errors.push(format!("locales/{}.txt:{}", code, error));
+        }
+        locales.push(locale);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(data_dir().join("locales"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .collect();
+    files.sort();
+
+    for path in files {
+        let code = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                errors.push(format!("{}: {}", path.display(), err));
+                continue;
+            }
+        };
+
+        let locale = match locales.iter().position(|locale| locale.code == code) {
+            Some(i) => &mut locales[i],
+            None => {
+                locales.push(Locale::new(&code, &code));
+                locales.last_mut().expect("just pushed")
+            }
+        };
+        for error in locale.read(&contents) {
+            errors.push(format!("{}:{}", path.display(), error));
+        }
+    }
+
+    locales[1..].sort_by(|a, b| a.code.cmp(&b.code));
+    (locales, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verse::db::Canon;
+
+    #[test]
+    fn bundled_files_read() {
+        for (code, contents) in BUNDLED {
+            let mut locale = Locale::new(code, code);
+            assert_eq!(locale.read(contents), Vec::<String>::new(), "{}", code);
+            for book in Canon::Protestant.books() {
+                assert!(
+                    locale.books.contains_key(&book),
+                    "{} {}",
+                    code,
+                    book.as_str()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn languages() {
+        let code = |language| Locale::find(language).map(|locale| locale.code.as_str());
+        assert_eq!(code("es"), Some("es"));
+        assert_eq!(code("ES_mx"), Some("es"));
+        assert_eq!(code("spa"), Some("es"));
+        assert_eq!(code("de-CH"), Some("de"));
+        assert_eq!(code("en"), Some("en"));
+        assert_eq!(code("xx"), None);
+
+        assert_eq!(Locale::pick(Some("de"), Some("es")).code, "de");
+        assert_eq!(Locale::pick(Some("xx"), Some("es")).code, "es");
+        assert_eq!(Locale::pick(None, None).code, "en");
+    }
+
+    #[test]
+    fn names_and_text() {
+        let spanish = Locale::find("es").unwrap();
+        assert_eq!(spanish.book(BibleBook::John), "Juan");
+        assert_eq!(spanish.text("Quit"), "Salir");
+        assert_eq!(spanish.text("Not translated"), "Not translated");
+
+        let english = Locale::english();
+        assert_eq!(english.book(BibleBook::John), "John");
+        assert_eq!(english.books().count(), 0);
+        assert_eq!(english.fill("Switched to {}", &["KJV"]), "Switched to KJV");
+        //Missing values are left empty
+        assert_eq!(english.fill("Day {} of {}", &["3"]), "Day 3 of ");
+
+        let german = Locale::find("de").unwrap();
+        assert_eq!(german.fill("Day {} of {}", &["3", "365"]), "Tag 3 von 365");
+    }
+
+    #[test]
+    fn bad_lines() {
+        let mut locale = Locale::new("xx", "xx");
+        let errors = locale.read(
+            "name: Test\n\
+             colour: red\n\
+             [books]\n\
+             John: Jean, Jn\n\
+             Jhn: Jean\n\
+             Mark: ,\n\
+             no colon\n\
+             [maps]\n\
+             [text]\n\
+             Quit: Quitter\n",
+        );
+        assert_eq!(
+            errors,
+            [
+                "2: unknown setting colour",
+                "5: unknown book Jhn",
+                "6: no name for Mark",
+                "7: expected \"key: value\"",
+                "8: unknown section maps",
+            ]
+        );
+        assert_eq!(locale.name, "Test");
+        assert_eq!(locale.book(BibleBook::John), "Jean");
+        assert_eq!(locale.book(BibleBook::Mark), "Mark");
+        assert_eq!(locale.text("Quit"), "Quitter");
+    }
+}
@@ -1,3 +1,25 @@
-//pub mod parse;
+pub mod parse;
 pub mod tui;
-pub mod db;
\ No newline at end of file
+pub mod db;
+pub mod keymap;
+pub mod cli;
+pub mod date;
+pub mod plan;
+pub mod store;
+pub mod votd;
+pub mod crossref;
+pub mod serve;
+pub mod remote;
+pub mod event;
+pub mod index;
+pub mod compiled;
+pub mod source;
+pub mod xml;
+pub mod json;
+pub mod import;
+pub mod export;
+pub mod versification;
+pub mod locale;
+pub mod strongs;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
\ No newline at end of file
@@ -0,0 +1,278 @@
+use std::fmt;
+
+use crate::verse::{db::BibleBook, locale::Locale, versification::Versification};
+
+//Lower case with spaces and dots removed and accents dropped, "1. Kön" -> "1kon"
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace() && *c != '.')
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ñ' => 'n',
+            c => c,
+        })
+        .collect()
+}
+
+//Book from its full name or any prefix that only one book starts with. Prefixes are
+//first matched against the Protestant canon, so "ps" stays Psalms beside Psalm 151.
+//Names and abbreviations in the other languages come after the English names,
+//"Juan" or "1. Mose", and prefixes of their names after the English prefixes
+pub fn parse_book(input: &str) -> Option<BibleBook> {
+    let input = normalize(input);
+    if input.is_empty() {
+        return None;
+    }
+
+    if let Some(book) = BibleBook::ALL
+        .iter()
+        .find(|book| normalize(book.as_str()) == input)
+    {
+        return Some(*book);
+    }
+
+    let localized = || Locale::all().iter().flat_map(|locale| locale.books());
+    if let Some((book, _)) =
+        localized().find(|(_, names)| names.iter().any(|name| normalize(name) == input))
+    {
+        return Some(book);
+    }
+
+    for deuterocanon in [false, true] {
+        let mut matches = BibleBook::ALL.iter().filter(|book| {
+            (deuterocanon || !book.is_deuterocanonical())
+                && normalize(book.as_str()).starts_with(&input)
+        });
+
+        match (matches.next(), matches.next()) {
+            (Some(book), None) => return Some(*book),
+            (Some(_), Some(_)) => return None,
+            _ => {}
+        }
+    }
+
+    let mut books: Vec<BibleBook> = localized()
+        .filter(|(_, names)| normalize(&names[0]).starts_with(&input))
+        .map(|(book, _)| book)
+        .collect();
+    books.sort_by_key(|book| *book as u8);
+    books.dedup();
+    match books.as_slice() {
+        [book] => Some(*book),
+        _ => None,
+    }
+}
+
+//"Genesis 1", "Genesis 1-3" or just "Jude", as book and chapter range
+pub fn parse_chapters(input: &str) -> Option<(BibleBook, u8, u8)> {
+    let input = input.trim();
+
+    //A book on its own means all of it
+    if let Some(book) = parse_book(input) {
+        return Some((book, 1, book.max_chapter_count()));
+    }
+
+    let (name, chapters) = input.rsplit_once(char::is_whitespace)?;
+    let book = parse_book(name)?;
+
+    let (first, last) = match chapters.split_once('-') {
+        Some((first, last)) => (first.trim().parse().ok()?, last.trim().parse().ok()?),
+        None => {
+            let chapter = chapters.parse().ok()?;
+            (chapter, chapter)
+        }
+    };
+
+    if first < 1 || first > last || last > book.max_chapter_count() {
+        return None;
+    }
+
+    Some((book, first, last))
+}
+
+//A chapter, or a range of verses in it
+#[derive(Copy, Clone, PartialEq)]
+pub struct Reference {
+    pub book: BibleBook,
+    pub chapter: u8,
+    pub verses: Option<(u8, u8)>,
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.book.as_str(), self.chapter)?;
+        match self.verses {
+            Some((first, last)) if first == last => write!(f, ":{}", first),
+            Some((first, last)) => write!(f, ":{}-{}", first, last),
+            None => Ok(()),
+        }
+    }
+}
+
+//"Genesis-Deuteronomy" or just "Ruth", as first and last book. Whether they're in
+//order depends on the canon
+pub fn parse_books(input: &str) -> Option<(BibleBook, BibleBook)> {
+    match input.split_once('-') {
+        Some((first, last)) => Some((parse_book(first)?, parse_book(last)?)),
+        None => {
+            let book = parse_book(input)?;
+            Some((book, book))
+        }
+    }
+}
+
+//"John 3", "John 3:16" or "John 3:16-18", single chapter books also take "Jude 4"
+pub fn parse_reference(input: &str) -> Option<Reference> {
+    parse_location(input, |book| book.max_chapter_count())
+}
+
+//A reference as a translation in the numbering has it, as the English verses. Verse
+//ranges running into another English chapter are cut to their first verse
+pub fn parse_reference_in(input: &str, versification: Versification) -> Option<Reference> {
+    let reference = parse_location(input, |book| versification.max_chapter_count(book))?;
+    let (first, last) = reference.verses.unwrap_or((1, 1));
+
+    let (book, chapter, first) = versification.to_english(reference.book, reference.chapter, first);
+    let (_, last_chapter, last) = versification.to_english(reference.book, reference.chapter, last);
+    Some(Reference {
+        book,
+        chapter,
+        verses: reference
+            .verses
+            .map(|_| match last_chapter == chapter && last >= first {
+                true => (first, last),
+                false => (first, first),
+            }),
+    })
+}
+
+fn parse_location(input: &str, max_chapter_count: impl Fn(BibleBook) -> u8) -> Option<Reference> {
+    let input = input.trim();
+    let (name, location) = input.rsplit_once(char::is_whitespace)?;
+    let book = parse_book(name)?;
+
+    let (chapter, verses) = match location.split_once(':') {
+        Some((chapter, verses)) => (chapter.parse().ok()?, Some(verses)),
+        None if max_chapter_count(book) == 1 => (1, Some(location)),
+        None => (location.parse().ok()?, None),
+    };
+    if chapter < 1 || chapter > max_chapter_count(book) {
+        return None;
+    }
+
+    let verses = match verses {
+        Some(verses) => {
+            let (first, last) = match verses.split_once('-') {
+                Some((first, last)) => (first.trim().parse().ok()?, last.trim().parse().ok()?),
+                None => {
+                    let verse = verses.parse().ok()?;
+                    (verse, verse)
+                }
+            };
+            if first < 1 || first > last {
+                return None;
+            }
+            Some((first, last))
+        }
+        None => None,
+    };
+
+    Some(Reference {
+        book,
+        chapter,
+        verses,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //References printed back in English, None as ""
+    fn reference(input: &str) -> String {
+        parse_reference(input).map_or(String::new(), |reference| reference.to_string())
+    }
+
+    #[test]
+    fn english_books() {
+        assert_eq!(parse_book("Genesis"), Some(BibleBook::Genesis));
+        assert_eq!(parse_book("gen"), Some(BibleBook::Genesis));
+        assert_eq!(parse_book("1 john"), Some(BibleBook::FirstJohn));
+        assert_eq!(parse_book("ps"), Some(BibleBook::Psalms));
+        //Judges, Jude, John and more
+        assert_eq!(parse_book("j"), None);
+        assert_eq!(parse_book(""), None);
+    }
+
+    #[test]
+    fn localized_books() {
+        assert_eq!(parse_book("Juan"), Some(BibleBook::John));
+        assert_eq!(parse_book("1. Mose"), Some(BibleBook::Genesis));
+        assert_eq!(parse_book("1Mo"), Some(BibleBook::Genesis));
+        assert_eq!(parse_book("요한복음"), Some(BibleBook::John));
+        //Accents may be left out, or put where there are none
+        assert_eq!(parse_book("1. Konige"), Some(BibleBook::FirstKings));
+        assert_eq!(parse_book("Genésis"), Some(BibleBook::Genesis));
+        //Prefixes of the names in other languages
+        assert_eq!(parse_book("Johan"), Some(BibleBook::John));
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(reference("John 3"), "John 3");
+        assert_eq!(reference("John 3:16"), "John 3:16");
+        assert_eq!(reference("John 3:16-18"), "John 3:16-18");
+        assert_eq!(reference("Jude 4"), "Jude 1:4");
+        assert_eq!(reference("Juan 3:16"), "John 3:16");
+        assert_eq!(reference("요 3:16"), "John 3:16");
+        assert_eq!(reference("1. Mose 1:1"), "Genesis 1:1");
+
+        assert_eq!(reference("John 22"), "");
+        assert_eq!(reference("John 3:18-16"), "");
+        assert_eq!(reference("John"), "");
+    }
+
+    #[test]
+    fn chapters_and_books() {
+        assert_eq!(
+            parse_chapters("Genesis 1-3"),
+            Some((BibleBook::Genesis, 1, 3))
+        );
+        assert_eq!(parse_chapters("Jude"), Some((BibleBook::Jude, 1, 1)));
+        assert_eq!(
+            parse_chapters("5. Mose 2"),
+            Some((BibleBook::Deuteronomy, 2, 2))
+        );
+        assert_eq!(parse_chapters("Genesis 3-1"), None);
+        assert_eq!(parse_chapters("Genesis 51"), None);
+
+        assert_eq!(
+            parse_books("Génesis-Deuteronomio"),
+            Some((BibleBook::Genesis, BibleBook::Deuteronomy))
+        );
+        assert_eq!(
+            parse_books("Ruth"),
+            Some((BibleBook::Ruth, BibleBook::Ruth))
+        );
+    }
+
+    #[test]
+    fn hebrew_references() {
+        let hebrew = |input| {
+            parse_reference_in(input, Versification::Hebrew)
+                .map_or(String::new(), |reference| reference.to_string())
+        };
+        assert_eq!(hebrew("Malachi 3:19-20"), "Malachi 4:1-2");
+        assert_eq!(hebrew("Joel 4:1"), "Joel 3:1");
+        //Joel has four chapters in Hebrew and three in English
+        assert_eq!(reference("Joel 4"), "");
+        //Running into another English chapter, cut to the first verse
+        assert_eq!(hebrew("Malachi 3:18-19"), "Malachi 3:18");
+        assert_eq!(hebrew("Maleachi 3"), "Malachi 3");
+    }
+}
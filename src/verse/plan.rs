@@ -0,0 +1,397 @@
+use std::{fmt, fs};
+
+use crate::verse::{
+    db::{BibleBook, Canon, Order},
+    parse::parse_chapters,
+    store::{PlanProgress, data_dir},
+};
+
+//A run of chapters in one book
+#[derive(Copy, Clone, PartialEq)]
+pub struct Passage {
+    pub book: BibleBook,
+    pub first: u8,
+    pub last: u8,
+}
+
+impl Passage {
+    //"3" or "1-3"
+    pub fn chapters(&self) -> String {
+        if self.first == self.last {
+            self.first.to_string()
+        } else {
+            format!("{}-{}", self.first, self.last)
+        }
+    }
+}
+
+impl fmt::Display for Passage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.book.as_str(), self.chapters())
+    }
+}
+
+pub struct Plan {
+    pub name: String,
+    pub title: String,
+    pub days: Vec<Vec<Passage>>,
+}
+
+pub const BUILT_IN: [(&str, &str); 4] = [
+    ("canonical", "Bible in a year, canonical order"),
+    ("chronological", "Bible in a year, chronological order"),
+    (
+        "four-streams",
+        "Four readings a day from four parts of the Bible",
+    ),
+    ("nt90", "New Testament in 90 days"),
+];
+
+impl Plan {
+    //Built-in plan or one of the user's plan files
+    pub fn find(name: &str) -> Result<Plan, String> {
+        let books = |first: BibleBook, last: BibleBook| {
+            BibleBook::ALL[first as usize - 1..last as usize].to_vec()
+        };
+
+        //The 66 books in an order
+        let ordered = |order: Order| chapters(order.arrange(Canon::Protestant).books());
+
+        match name {
+            "canonical" => Ok(Plan::spread(
+                name,
+                BUILT_IN[0].1,
+                &ordered(Order::Protestant),
+                365,
+            )),
+            "chronological" => Ok(Plan::spread(
+                name,
+                BUILT_IN[1].1,
+                &ordered(Order::Chronological),
+                365,
+            )),
+            "four-streams" => {
+                //Four streams read side by side, the shorter ones start over when done
+                let streams = [
+                    chapters(&books(BibleBook::Genesis, BibleBook::SecondChronicles)),
+                    cycle(
+                        &chapters(&books(BibleBook::Matthew, BibleBook::Revelation)),
+                        365,
+                    ),
+                    chapters(
+                        &[
+                            books(BibleBook::Ezra, BibleBook::Job),
+                            books(BibleBook::Proverbs, BibleBook::Malachi),
+                        ]
+                        .concat(),
+                    ),
+                    cycle(&chapters(&[BibleBook::Psalms]), 365),
+                ];
+
+                let mut plan = Plan::spread(name, BUILT_IN[2].1, &[], 365);
+                for stream in streams {
+                    let spread = Plan::spread(name, "", &stream, 365);
+                    for (day, passages) in plan.days.iter_mut().zip(spread.days) {
+                        day.extend(passages);
+                    }
+                }
+                Ok(plan)
+            }
+            "nt90" => Ok(Plan::spread(
+                name,
+                BUILT_IN[3].1,
+                &chapters(&books(BibleBook::Matthew, BibleBook::Revelation)),
+                90,
+            )),
+            _ => match name.strip_prefix("canonical-").and_then(Order::parse) {
+                Some(order) => Ok(Plan::spread(
+                    name,
+                    &format!("Bible in a year, {} order", order.as_str()),
+                    &ordered(order),
+                    365,
+                )),
+                None => Plan::load_file(name),
+            },
+        }
+    }
+
+    //The plan to start for the name, canonical reads the books in the chosen order.
+    //Orders that leave the 66 books as they are keep the plain plan
+    pub fn in_order(name: &str, order: Order) -> String {
+        let books = order.arrange(Canon::Protestant);
+        match name {
+            "canonical" if order == Order::Chronological => "chronological".to_string(),
+            "canonical" if books.books() != Canon::Protestant.books() => {
+                format!("canonical-{}", order.as_str().to_lowercase())
+            }
+            _ => name.to_string(),
+        }
+    }
+
+    //Built-in plans followed by the user's, as (name, title)
+    pub fn list() -> Vec<(String, String)> {
+        let mut plans: Vec<(String, String)> = BUILT_IN
+            .iter()
+            .map(|(name, title)| (name.to_string(), title.to_string()))
+            .collect();
+
+        let mut user: Vec<(String, String)> = fs::read_dir(data_dir().join("plans"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension()? != "plan" {
+                    return None;
+                }
+                let name = path.file_stem()?.to_string_lossy().to_string();
+                let title = Plan::load_file(&name)
+                    .map(|plan| plan.title)
+                    .unwrap_or_else(|err| err);
+                Some((name, title))
+            })
+            .collect();
+        user.sort();
+
+        plans.extend(user);
+        plans
+    }
+
+    /*
+    Plan files live in data_dir()/plans/<name>.plan, one day per line:
+
+        # Comments and blank lines are ignored
+        title: Gospels in a month
+        1: Matthew 1-3; Mark 1
+        2: Matthew 4-6
+    */
+    fn load_file(name: &str) -> Result<Plan, String> {
+        let path = data_dir().join("plans").join(format!("{}.plan", name));
+        let contents = fs::read_to_string(&path)
+            .map_err(|_| format!("No plan named {} ({} not found)", name, path.display()))?;
+
+        let mut plan = Plan {
+            name: name.to_string(),
+            title: name.to_string(),
+            days: vec![],
+        };
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("{}:{}: expected \"day: passages\"", name, i + 1))?;
+
+            if key.trim() == "title" {
+                plan.title = value.trim().to_string();
+                continue;
+            }
+
+            let day: usize = key
+                .trim()
+                .parse()
+                .ok()
+                .filter(|day| *day >= 1)
+                .ok_or_else(|| format!("{}:{}: bad day {}", name, i + 1, key.trim()))?;
+
+            let passages = value
+                .split(';')
+                .filter(|passage| !passage.trim().is_empty())
+                .map(|passage| {
+                    parse_chapters(passage)
+                        .map(|(book, first, last)| Passage { book, first, last })
+                        .ok_or_else(|| {
+                            format!("{}:{}: bad passage {}", name, i + 1, passage.trim())
+                        })
+                })
+                .collect::<Result<Vec<Passage>, String>>()?;
+
+            if plan.days.len() < day {
+                plan.days.resize(day, vec![]);
+            }
+            plan.days[day - 1].extend(passages);
+        }
+
+        if plan.days.is_empty() {
+            return Err(format!("{}: plan has no days", name));
+        }
+
+        Ok(plan)
+    }
+
+    //Spreads the chapters evenly over the days, in order
+    fn spread(name: &str, title: &str, chapters: &[(BibleBook, u8)], days: usize) -> Plan {
+        let days = (0..days)
+            .map(|day| {
+                let start = day * chapters.len() / days;
+                let end = (day + 1) * chapters.len() / days;
+
+                //Consecutive chapters of a book become one passage
+                let mut passages: Vec<Passage> = vec![];
+                for (book, chapter) in &chapters[start..end] {
+                    match passages.last_mut() {
+                        Some(last) if last.book == *book && last.last + 1 == *chapter => {
+                            last.last = *chapter
+                        }
+                        _ => passages.push(Passage {
+                            book: *book,
+                            first: *chapter,
+                            last: *chapter,
+                        }),
+                    }
+                }
+                passages
+            })
+            .collect();
+
+        Plan {
+            name: name.to_string(),
+            title: title.to_string(),
+            days,
+        }
+    }
+
+    //Days before day that aren't done, for catching up. None before the plan starts
+    pub fn behind(&self, progress: &PlanProgress, day: i32) -> Vec<u16> {
+        (1..day.clamp(1, self.days.len() as i32 + 1) as u16)
+            .filter(|day| !self.is_done(progress, *day))
+            .collect()
+    }
+
+    pub fn is_done(&self, progress: &PlanProgress, day: u16) -> bool {
+        self.days[day as usize - 1]
+            .iter()
+            .enumerate()
+            .all(|(i, _)| progress.done.contains(&(day, i as u8)))
+    }
+}
+
+fn chapters(books: &[BibleBook]) -> Vec<(BibleBook, u8)> {
+    books
+        .iter()
+        .flat_map(|book| (1..=book.max_chapter_count()).map(|chapter| (*book, chapter)))
+        .collect()
+}
+
+//Repeats the chapters from the start until there are len of them
+fn cycle(chapters: &[(BibleBook, u8)], len: usize) -> Vec<(BibleBook, u8)> {
+    chapters.iter().copied().cycle().take(len).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::verse::date::Date;
+
+    fn progress(start: Date) -> PlanProgress {
+        PlanProgress {
+            name: "nt90".to_string(),
+            start,
+            done: HashSet::new(),
+        }
+    }
+
+    //Every chapter of the plan's passages, in order
+    fn read(plan: &Plan) -> Vec<(BibleBook, u8)> {
+        plan.days
+            .iter()
+            .flatten()
+            .flat_map(|passage| {
+                (passage.first..=passage.last).map(|chapter| (passage.book, chapter))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn built_in() {
+        let all = chapters(&Canon::Protestant.books());
+        assert_eq!(all.len(), 1189);
+
+        let canonical = Plan::find("canonical").unwrap();
+        assert_eq!(canonical.days.len(), 365);
+        assert!(canonical.days.iter().all(|day| !day.is_empty()));
+        assert_eq!(read(&canonical), all);
+        assert_eq!(canonical.days[0][0].to_string(), "Genesis 1-3");
+
+        let chronological = Plan::find("chronological").unwrap();
+        let mut chronological = read(&chronological);
+        assert_ne!(chronological, all);
+        chronological.sort_by_key(|(book, chapter)| (*book as u8, *chapter));
+        assert_eq!(chronological, all);
+
+        let nt90 = Plan::find("nt90").unwrap();
+        assert_eq!(nt90.days.len(), 90);
+        assert_eq!(read(&nt90), chapters(&BibleBook::ALL[39..66]));
+
+        for (name, title) in BUILT_IN {
+            assert_eq!(Plan::find(name).unwrap().title, title);
+        }
+        assert!(Plan::find("no-such-plan").is_err());
+    }
+
+    //Four passages every day, the Old Testament read once and the New and the
+    //Psalms started over
+    #[test]
+    fn four_streams() {
+        let plan = Plan::find("four-streams").unwrap();
+        assert_eq!(plan.days.len(), 365);
+        assert!(plan.days.iter().all(|day| day.len() == 4));
+
+        let mut read = read(&plan);
+        read.sort_by_key(|(book, chapter)| (*book as u8, *chapter));
+        read.dedup();
+        assert_eq!(read, chapters(&Canon::Protestant.books()));
+        assert_eq!(plan.days[0][3].to_string(), "Psalms 1");
+        assert_eq!(plan.days[150][3].to_string(), "Psalms 1");
+    }
+
+    #[test]
+    fn in_order() {
+        assert_eq!(Plan::in_order("canonical", Order::Protestant), "canonical");
+        assert_eq!(
+            Plan::in_order("canonical", Order::Chronological),
+            "chronological"
+        );
+        assert_eq!(
+            Plan::in_order("canonical", Order::Tanakh),
+            "canonical-tanakh"
+        );
+        assert_eq!(Plan::in_order("nt90", Order::Tanakh), "nt90");
+
+        //Chronicles closes the Writings, after the Prophets
+        let tanakh = read(&Plan::find("canonical-tanakh").unwrap());
+        assert_eq!(tanakh.len(), 1189);
+        let position = |chapter| tanakh.iter().position(|read| *read == chapter);
+        assert!(position((BibleBook::SecondChronicles, 36)) > position((BibleBook::Malachi, 4)));
+    }
+
+    #[test]
+    fn behind_before_start() {
+        let plan = Plan::find("nt90").unwrap();
+        let start = Date::from_ymd(2027, 1, 1).unwrap();
+        let progress = progress(start);
+
+        let day = progress.day_on(Date::from_ymd(2026, 10, 19).unwrap());
+        assert!(day < 1);
+        assert!(plan.behind(&progress, day).is_empty());
+        assert!(plan.behind(&progress, 1).is_empty());
+    }
+
+    #[test]
+    fn behind_past_end() {
+        let plan = Plan::find("nt90").unwrap();
+        let mut progress = progress(Date::from_ymd(2026, 1, 1).unwrap());
+        progress
+            .done
+            .extend((0..plan.days[0].len()).map(|i| (1, i as u8)));
+
+        assert_eq!(plan.behind(&progress, 3), vec![2]);
+        assert_eq!(plan.behind(&progress, 1000).len(), plan.days.len() - 1);
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+/*
+Remote control of a running reader over a Unix socket. Clients write one command
+per line and get back "ok" or "error: <message>", any output lines, then an empty
+line:
+
+    goto John 3:16-18     Jump there and select the verses
+    search living water   Verses containing the words, jumps to the first
+    translation [name]    Switch translation, or list them
+    location              Verse at the top of the reader
+    selection             Text of the selected verses
+*/
+
+//Where the reader listens, $XDG_RUNTIME_DIR/verse.sock or in a private per user directory in /tmp
+pub fn socket_path() -> PathBuf {
+    if let Some(dir) = env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir).join("verse.sock");
+    }
+
+    env::temp_dir()
+        .join(format!("verse-{}", uid()))
+        .join("verse.sock")
+}
+
+fn uid() -> u32 {
+    //SAFETY: getuid has no preconditions and cannot fail
+    unsafe { libc::getuid() }
+}
+
+fn not_yours(path: &Path, what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("{} is not {} of yours", path.display(), what),
+    )
+}
+
+//Creates the socket's directory only the user can enter, or checks an existing one is that
+fn private_dir(dir: &Path) -> io::Result<()> {
+    match fs::DirBuilder::new().mode(0o700).create(dir) {
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+        result => return result,
+    }
+
+    //Not following links, a link to someone else's directory is not private
+    let meta = fs::symlink_metadata(dir)?;
+    if !meta.is_dir() || meta.uid() != uid() || meta.mode() & 0o077 != 0 {
+        return Err(not_yours(dir, "a private directory"));
+    }
+    Ok(())
+}
+
+//A command line from a client, answered with reply
+pub struct Command {
+    pub line: String,
+    reply: Sender<String>,
+}
+
+impl Command {
+    pub fn reply(self, result: Result<String, String>) {
+        let text = match result {
+            Ok(output) if output.is_empty() => "ok".to_string(),
+            Ok(output) => format!("ok\n{}", output),
+            Err(err) => format!("error: {}", err),
+        };
+        //The client may have hung up already
+        let _ = self.reply.send(text);
+    }
+}
+
+//Socket the reader listens on, removed again when dropped
+pub struct Remote {
+    path: PathBuf,
+}
+
+impl Remote {
+    //Commands are sent to the reader's event queue as they come in
+    pub fn listen<T: From<Command> + Send + 'static>(events: Sender<T>) -> io::Result<Remote> {
+        let path = socket_path();
+        if env::var_os("XDG_RUNTIME_DIR").is_none_or(|dir| dir.is_empty()) {
+            private_dir(path.parent().unwrap())?;
+        }
+        Remote::listen_at(path, events)
+    }
+
+    fn listen_at<T: From<Command> + Send + 'static>(
+        path: PathBuf,
+        events: Sender<T>,
+    ) -> io::Result<Remote> {
+        if let Ok(meta) = fs::symlink_metadata(&path) {
+            //Someone else's file is left alone rather than removed or listened on
+            if meta.uid() != uid() {
+                return Err(not_yours(&path, "a socket"));
+            }
+            //A socket nobody answers on is left over from a crash
+            if UnixStream::connect(&path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("another reader is listening on {}", path.display()),
+                ));
+            }
+            fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        let remote = Remote { path };
+        //Only the user may send commands
+        fs::set_permissions(&remote.path, fs::Permissions::from_mode(0o600))?;
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let events = events.clone();
+                thread::spawn(move || serve_client(stream, events));
+            }
+        });
+
+        Ok(remote)
+    }
+}
+
+impl Drop for Remote {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+//Hands the client's commands to the reader one at a time and writes back the replies
+fn serve_client<T: From<Command>>(stream: UnixStream, events: Sender<T>) -> io::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (reply, replies) = mpsc::channel();
+        if events.send(Command { line, reply }.into()).is_err() {
+            break;
+        }
+        let Ok(text) = replies.recv() else {
+            break;
+        };
+        write!(writer, "{}\n\n", text)?;
+    }
+
+    Ok(())
+}
+
+//Sends one command to the running reader, returns its reply without the trailing empty line
+pub fn send(command: &str) -> io::Result<String> {
+    let path = socket_path();
+    //Commands and selections are not handed to another user's reader
+    if let Ok(meta) = fs::symlink_metadata(&path)
+        && meta.uid() != uid()
+    {
+        return Err(not_yours(&path, "a socket"));
+    }
+    let mut stream = UnixStream::connect(&path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("no reader listening on {}: {}", path.display(), err),
+        )
+    })?;
+    writeln!(stream, "{}", command)?;
+
+    let mut reply: Vec<String> = vec![];
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        reply.push(line);
+    }
+
+    Ok(reply.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn temp_dir(name: &str) -> TempDir {
+        TempDir(env::temp_dir().join(format!("verse-{}-{}", std::process::id(), name)))
+    }
+
+    #[test]
+    fn private_socket() {
+        let dir = temp_dir("remote");
+        private_dir(&dir.0).unwrap();
+        private_dir(&dir.0).unwrap();
+        let meta = fs::metadata(&dir.0).unwrap();
+        assert_eq!(meta.mode() & 0o777, 0o700);
+
+        //A directory others can enter is refused
+        fs::set_permissions(&dir.0, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(private_dir(&dir.0).is_err());
+        fs::set_permissions(&dir.0, fs::Permissions::from_mode(0o700)).unwrap();
+
+        let path = dir.0.join("verse.sock");
+        let (events, commands) = mpsc::channel::<Command>();
+        let remote = Remote::listen_at(path.clone(), events.clone()).unwrap();
+        let meta = fs::symlink_metadata(&path).unwrap();
+        assert_eq!(meta.mode() & 0o777, 0o600);
+
+        let err = Remote::listen_at(path.clone(), events.clone())
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+        thread::spawn(move || {
+            for command in commands {
+                let line = command.line.clone();
+                command.reply(Ok(line));
+            }
+        });
+        let mut stream = UnixStream::connect(&path).unwrap();
+        writeln!(stream, "location").unwrap();
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply).unwrap();
+        assert_eq!(reply, "ok\n");
+
+        drop(remote);
+        assert!(!path.exists());
+
+        //A socket left over from a crash is replaced
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(Remote::listen_at(path, events).is_ok());
+    }
+}
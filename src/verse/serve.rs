@@ -0,0 +1,812 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::verse::{
+    crossref::{CrossReference, CrossReferences},
+    date::Date,
+    db::{self, BibleBook, Database, Verse},
+    parse::{Reference, parse_book, parse_reference_in},
+    versification::Versification,
+    votd,
+};
+
+const ENDPOINTS: &str = "GET /passage?ref=John 3:16-18    Verses or a whole chapter
+GET /search?q=words[&book=John][&limit=50]
+                                  Verses containing the words
+GET /translations                 Translations in the DB
+GET /crossrefs?ref=John 3:16      Cross references of a verse
+GET /votd[?date=YYYY-MM-DD][&any] Verse of the day
+GET /versification?ref=Malachi 3:19[&from=hebrew]
+                                  The verse in every numbering, from the
+                                  translation's numbering by default
+
+All of them take translation=<name>, and format=text or an Accept: text/plain
+header for plain text instead of JSON.";
+
+const SEARCH_LIMIT: u32 = 50;
+
+//Answers one request at a time with the same DB, the API is meant for local tools
+pub fn serve(bind: &str) -> Result<(), String> {
+    let listener =
+        TcpListener::bind(bind).map_err(|err| format!("Couldn't listen on {}: {}", bind, err))?;
+    let db = Database::connect().map_err(|err| {
+        format!(
+            "Couldn't open the DB, it has to be in the same dir as the executable: {}",
+            err
+        )
+    })?;
+
+    eprintln!("Listening on http://{}", bind);
+
+    let mut server = Server {
+        db,
+        crossrefs: None,
+    };
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(err) = server.handle(stream) {
+                    eprintln!("{} connection error: {}", timestamp(), err);
+                }
+            }
+            Err(err) => eprintln!("{} accept error: {}", timestamp(), err),
+        }
+    }
+
+    Ok(())
+}
+
+struct Server {
+    db: Database,
+    //Loaded on the first cross reference request
+    crossrefs: Option<Result<CrossReferences, String>>,
+}
+
+struct Request {
+    method: String,
+    target: String,
+    path: String,
+    query: Vec<(String, String)>,
+    text: bool,
+}
+
+impl Request {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+struct Response {
+    status: u16,
+    body: String,
+    text: bool,
+}
+
+type Reply = Result<String, (u16, String)>;
+
+impl Server {
+    fn handle(&mut self, stream: TcpStream) -> io::Result<()> {
+        let started = Instant::now();
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+        let mut reader = BufReader::new(&stream);
+        let Some(request) = read_request(&mut reader)? else {
+            return respond(
+                &stream,
+                &Response {
+                    status: 400,
+                    body: json_error("Malformed request"),
+                    text: false,
+                },
+            );
+        };
+
+        let response = self.route(&request);
+        respond(&stream, &response)?;
+
+        eprintln!(
+            "{} {} {} {} {}ms",
+            timestamp(),
+            request.method,
+            request.target,
+            response.status,
+            started.elapsed().as_millis()
+        );
+        Ok(())
+    }
+
+    fn route(&mut self, request: &Request) -> Response {
+        let reply = if request.method != "GET" {
+            Err((405, "Only GET is supported".to_string()))
+        } else {
+            self.with_translation(request, |server| match request.path.as_str() {
+                "/" => Ok(if request.text {
+                    ENDPOINTS.to_string()
+                } else {
+                    format!("{{\"endpoints\":{}}}", json_string(ENDPOINTS))
+                }),
+                "/passage" => server.passage(request),
+                "/search" => server.search(request),
+                "/translations" => server.translations(request),
+                "/crossrefs" => server.crossrefs(request),
+                "/votd" => server.votd(request),
+                "/versification" => server.versification(request),
+                _ => Err((404, format!("No endpoint {}", request.path))),
+            })
+        };
+
+        match reply {
+            Ok(body) => Response {
+                status: 200,
+                body,
+                text: request.text,
+            },
+            Err((status, message)) => Response {
+                status,
+                body: if request.text {
+                    message
+                } else {
+                    json_error(&message)
+                },
+                text: request.text,
+            },
+        }
+    }
+
+    //Runs the endpoint against the requested translation, then switches back
+    fn with_translation(
+        &mut self,
+        request: &Request,
+        endpoint: impl FnOnce(&mut Self) -> Reply,
+    ) -> Reply {
+        let Some(translation) = request.param("translation") else {
+            return endpoint(self);
+        };
+
+        let translation = self
+            .db
+            .find_translation(translation)
+            .map_err(server_error)?
+            .ok_or((400, format!("Unknown translation {}", translation)))?;
+
+        let previous = std::mem::replace(&mut self.db.translation, translation);
+        let reply = endpoint(self);
+        self.db.translation = previous;
+        reply
+    }
+
+    fn passage(&self, request: &Request) -> Reply {
+        let input = request
+            .param("ref")
+            .ok_or((400, "Missing ref parameter".to_string()))?;
+        //In the translation's numbering, like the remote control's goto
+        let versification = self.db.versification().map_err(server_error)?;
+        let reference = parse_reference_in(input, versification)
+            .ok_or((400, format!("Bad reference {}", input)))?;
+
+        let (first, last) = reference.verses.unwrap_or((1, u8::MAX));
+        let verses = self
+            .db
+            .fetch_range(reference.book, reference.chapter, first, last)
+            .map_err(server_error)?;
+        //Labelled as it was asked for, in the translation's numbering
+        let verses = numbered(verses, versification);
+        let (Some(first), Some(last)) = (verses.first(), verses.last()) else {
+            return Err((404, format!("{} is not in the text", input.trim())));
+        };
+
+        let reference = Reference {
+            book: first.book,
+            chapter: first.chapter,
+            verses: reference.verses.map(|_| (first.verse, last.verse)),
+        }
+        .to_string();
+        Ok(if request.text {
+            format!(
+                "{} ({})\n{}",
+                reference,
+                self.db.translation,
+                verses_text(&verses, false)
+            )
+        } else {
+            format!(
+                "{{\"reference\":{},\"translation\":{},\"verses\":{}}}",
+                json_string(&reference),
+                json_string(&self.db.translation),
+                verses_json(&verses)
+            )
+        })
+    }
+
+    fn search(&self, request: &Request) -> Reply {
+        let words = request
+            .param("q")
+            .map(str::trim)
+            .filter(|words| !words.is_empty())
+            .ok_or((400, "Missing q parameter".to_string()))?;
+
+        let (first, last) = match request.param("book") {
+            Some(name) => {
+                let book = parse_book(name).ok_or((400, format!("Unknown book {}", name)))?;
+                (book, book)
+            }
+            None => (BibleBook::Genesis, BibleBook::LAST),
+        };
+
+        let limit = match request.param("limit") {
+            Some(limit) => limit
+                .parse()
+                .ok()
+                .filter(|limit| *limit >= 1)
+                .ok_or((400, format!("Bad limit {}", limit)))?,
+            None => SEARCH_LIMIT,
+        };
+
+        let verses = self
+            .db
+            .search(words, first, last, limit)
+            .map_err(server_error)?;
+        let verses = numbered(verses, self.db.versification().map_err(server_error)?);
+
+        Ok(if request.text {
+            verses_text(&verses, true)
+        } else {
+            format!(
+                "{{\"query\":{},\"translation\":{},\"count\":{},\"verses\":{}}}",
+                json_string(words),
+                json_string(&self.db.translation),
+                verses.len(),
+                verses_json(&verses)
+            )
+        })
+    }
+
+    fn translations(&self, request: &Request) -> Reply {
+        let translations = self.db.translations().map_err(server_error)?;
+
+        Ok(if request.text {
+            translations.join("\n")
+        } else {
+            let names: Vec<String> = translations.iter().map(|name| json_string(name)).collect();
+            format!("{{\"translations\":[{}]}}", names.join(","))
+        })
+    }
+
+    fn crossrefs(&mut self, request: &Request) -> Reply {
+        let input = request
+            .param("ref")
+            .ok_or((400, "Missing ref parameter".to_string()))?;
+        let versification = self.db.versification().map_err(server_error)?;
+        let reference = parse_reference_in(input, versification)
+            .ok_or((400, format!("Bad reference {}", input)))?;
+        let Some((verse, _)) = reference.verses else {
+            return Err((400, format!("{} is not a single verse", reference)));
+        };
+
+        let crossrefs = self
+            .crossrefs
+            .get_or_insert_with(CrossReferences::load)
+            .as_ref()
+            .map_err(|err| (404, format!("No cross references installed. {}", err)))?;
+        //The verses in the translation's numbering, their OSIS ids stay English
+        let references = crossrefs.get(reference.book, reference.chapter, verse);
+        let native = |(book, chapter, verse)| versification.from_english(book, chapter, verse);
+        let references: Vec<(String, &CrossReference)> = references
+            .iter()
+            .map(|crossref| {
+                let label = CrossReference {
+                    first: native(crossref.first),
+                    last: native(crossref.last),
+                    votes: crossref.votes,
+                }
+                .reference();
+                (label, crossref)
+            })
+            .collect();
+
+        let reference = versification.reference(reference.book, reference.chapter, verse);
+        Ok(if request.text {
+            references
+                .iter()
+                .map(|(label, crossref)| format!("{} ({})", label, crossref.votes))
+                .collect::<Vec<String>>()
+                .join("\n")
+        } else {
+            let references: Vec<String> = references
+                .iter()
+                .map(|(label, crossref)| {
+                    format!(
+                        "{{\"reference\":{},\"osis\":{},\"votes\":{}}}",
+                        json_string(label),
+                        json_string(&crossref.osis()),
+                        crossref.votes
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"reference\":{},\"references\":[{}]}}",
+                json_string(&reference),
+                references.join(",")
+            )
+        })
+    }
+
+    fn votd(&self, request: &Request) -> Reply {
+        let date = match request.param("date") {
+            Some(date) => date
+                .parse()
+                .map_err(|_| (400, format!("Bad date {}, expected YYYY-MM-DD", date)))?,
+            None => Date::today(),
+        };
+
+        let verse = if request.param("any").is_some() {
+            votd::random_of_the_day(&self.db, date)
+        } else {
+            votd::verse_of_the_day(&self.db, date)
+        }
+        .map_err(server_error)?;
+        let verse = numbered_verse(verse, self.db.versification().map_err(server_error)?);
+
+        Ok(if request.text {
+            format!(
+                "{} ({})\n{}",
+                verse.reference(),
+                self.db.translation,
+                verse.text
+            )
+        } else {
+            format!(
+                "{{\"date\":\"{}\",\"translation\":{},\"verse\":{}}}",
+                date,
+                json_string(&self.db.translation),
+                verse_json(&verse)
+            )
+        })
+    }
+
+    fn versification(&self, request: &Request) -> Reply {
+        let input = request
+            .param("ref")
+            .ok_or((400, "Missing ref parameter".to_string()))?;
+        let from = match request.param("from") {
+            Some(name) => {
+                Versification::parse(name).ok_or((400, format!("Unknown numbering {}", name)))?
+            }
+            None => self.db.versification().map_err(server_error)?,
+        };
+        let reference =
+            parse_reference_in(input, from).ok_or((400, format!("Bad reference {}", input)))?;
+        let Some((verse, _)) = reference.verses else {
+            return Err((400, format!("{} is not a single verse", input)));
+        };
+
+        let references = Versification::ALL
+            .map(|to| (to, to.reference(reference.book, reference.chapter, verse)));
+        Ok(if request.text {
+            references
+                .iter()
+                .map(|(to, reference)| format!("{:<11} {}", to.as_str(), reference))
+                .collect::<Vec<String>>()
+                .join("\n")
+        } else {
+            let references: Vec<String> = references
+                .iter()
+                .map(|(to, reference)| {
+                    format!(
+                        "{}:{}",
+                        json_string(&to.as_str().to_lowercase()),
+                        json_string(reference)
+                    )
+                })
+                .collect();
+            format!("{{\"references\":{{{}}}}}", references.join(","))
+        })
+    }
+}
+
+fn server_error(err: db::Error) -> (u16, String) {
+    (500, format!("Database error: {}", err))
+}
+
+//Request line and headers, the body if any is ignored. None if it isn't HTTP
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Ok(None);
+    };
+    let (method, target) = (method.to_string(), target.to_string());
+
+    let mut text = false;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("accept")
+        {
+            text = value.contains("text/plain") && !value.contains("application/json");
+        }
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query: Vec<(String, String)> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (url_decode(key), url_decode(value))
+        })
+        .collect();
+
+    match query.iter().find(|(key, _)| key == "format") {
+        Some((_, format)) if format == "text" => text = true,
+        Some((_, format)) if format == "json" => text = false,
+        _ => {}
+    }
+
+    Ok(Some(Request {
+        method,
+        path: url_decode(path),
+        target,
+        query,
+        text,
+    }))
+}
+
+fn respond(mut stream: &TcpStream, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let content_type = if response.text {
+        "text/plain; charset=utf-8"
+    } else {
+        "application/json"
+    };
+    let body = format!("{}\n", response.body);
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+//Percent escapes and + as space, invalid UTF-8 is replaced
+fn url_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |byte: u8| (byte as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_error(message: &str) -> String {
+    format!("{{\"error\":{}}}", json_string(message))
+}
+
+fn verse_json(verse: &Verse) -> String {
+    format!(
+        "{{\"reference\":{},\"book\":{},\"chapter\":{},\"verse\":{},\"text\":{}}}",
+        json_string(&verse.reference()),
+        json_string(verse.book.as_str()),
+        verse.chapter,
+        verse.verse,
+        json_string(&verse.text)
+    )
+}
+
+//Stored verses are in the English numbering, replies in the translation's
+fn numbered_verse(verse: Verse, versification: Versification) -> Verse {
+    let (book, chapter, number) =
+        versification.from_english(verse.book, verse.chapter, verse.verse);
+    Verse {
+        book,
+        chapter,
+        verse: number,
+        text: verse.text,
+    }
+}
+
+fn numbered(verses: Vec<Verse>, versification: Versification) -> Vec<Verse> {
+    verses
+        .into_iter()
+        .map(|verse| numbered_verse(verse, versification))
+        .collect()
+}
+
+fn verses_json(verses: &[Verse]) -> String {
+    let verses: Vec<String> = verses.iter().map(verse_json).collect();
+    format!("[{}]", verses.join(","))
+}
+
+//One verse a line, with the full reference or just the verse number
+fn verses_text(verses: &[Verse], references: bool) -> String {
+    verses
+        .iter()
+        .map(|verse| {
+            if references {
+                format!("{} {}", verse.reference(), verse.text)
+            } else {
+                format!("{} {}", verse.verse, verse.text)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+//UTC time for the request log
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    format!(
+        "{} {:02}:{:02}:{:02}",
+        Date::today(),
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verse::{index::BibleIndex, source::Memory};
+
+    //John 3 in the first translation, Malachi in the Hebrew numbering in another
+    fn server() -> Server {
+        let john = (1..=18).map(|verse| {
+            let text = match verse {
+                16 => "For God so loved the world".to_string(),
+                _ => format!("John 3:{}", verse),
+            };
+            (BibleBook::John, 3, verse, text)
+        });
+        let malachi = [
+            "For behold, the day is coming",
+            "But for you who fear my name",
+            "And you shall tread down the wicked",
+            "Remember the law of my servant Moses",
+            "Behold, I will send you Elijah the prophet",
+            "And he will turn the hearts of fathers",
+        ];
+        let mut hebrew = BibleIndex::build(
+            "WLC",
+            malachi
+                .iter()
+                .zip(1..)
+                .map(|(text, verse)| (BibleBook::Malachi, 4, verse, text.to_string())),
+        );
+        hebrew.versification = Versification::Hebrew;
+
+        Server {
+            db: Database::new(Memory::new(
+                "test",
+                vec![BibleIndex::build("KJV", john), hebrew],
+            ))
+            .unwrap(),
+            crossrefs: None,
+        }
+    }
+
+    fn request(raw: &str) -> Request {
+        read_request(&mut raw.as_bytes()).unwrap().unwrap()
+    }
+
+    fn get(server: &mut Server, target: &str) -> Response {
+        server.route(&request(&format!(
+            "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            target
+        )))
+    }
+
+    #[test]
+    fn reads_requests() {
+        let request = request(
+            "GET /search?q=living+water&book=John%203&format=text HTTP/1.1\r\n\
+             Host: localhost\r\nAccept: application/json\r\n\r\nbody",
+        );
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/search");
+        assert_eq!(request.param("q"), Some("living water"));
+        assert_eq!(request.param("book"), Some("John 3"));
+        assert!(request.text);
+
+        let request = self::request("GET / HTTP/1.1\r\nAccept: text/plain\r\n\r\n");
+        assert!(request.text);
+        assert!(request.query.is_empty());
+
+        assert!(
+            read_request(&mut "garbage\r\n\r\n".as_bytes())
+                .unwrap()
+                .is_none()
+        );
+        assert!(read_request(&mut "".as_bytes()).unwrap().is_none());
+    }
+
+    #[test]
+    fn passage() {
+        let mut server = server();
+
+        let response = get(&mut server, "/passage?ref=John+3:16");
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.body,
+            "{\"reference\":\"John 3:16\",\"translation\":\"KJV\",\"verses\":\
+             [{\"reference\":\"John 3:16\",\"book\":\"John\",\"chapter\":3,\"verse\":16,\"text\":\"For God so loved the world\"}]}"
+        );
+
+        let response = get(&mut server, "/passage?ref=John+3:16-17&format=text");
+        assert_eq!(response.status, 200);
+        assert!(response.text);
+        assert!(response.body.starts_with("John 3:16-17 (KJV)\n"));
+        assert!(response.body.contains("For God so loved the world"));
+    }
+
+    #[test]
+    fn passage_in_the_translations_numbering() {
+        let mut server = server();
+
+        let response = get(
+            &mut server,
+            "/passage?ref=Malachi+3:19-20&translation=wlc&format=text",
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.body,
+            "Malachi 3:19-20 (WLC)\n\
+             19 For behold, the day is coming\n\
+             20 But for you who fear my name"
+        );
+
+        let response = get(&mut server, "/passage?ref=Malachi+3:24&translation=wlc");
+        assert_eq!(
+            response.body,
+            "{\"reference\":\"Malachi 3:24\",\"translation\":\"WLC\",\"verses\":\
+             [{\"reference\":\"Malachi 3:24\",\"book\":\"Malachi\",\"chapter\":3,\"verse\":24,\
+             \"text\":\"And he will turn the hearts of fathers\"}]}"
+        );
+
+        let response = get(&mut server, "/search?q=elijah&translation=wlc&format=text");
+        assert_eq!(
+            response.body,
+            "Malachi 3:23 Behold, I will send you Elijah the prophet"
+        );
+    }
+
+    #[test]
+    fn search() {
+        let mut server = server();
+
+        let response = get(&mut server, "/search?q=SO+LOVED&format=text");
+        assert_eq!(response.status, 200);
+        assert!(response.body.starts_with("John 3:16 "));
+
+        let response = get(&mut server, "/search?q=John&limit=2");
+        assert!(response.body.contains("\"count\":2"));
+    }
+
+    #[test]
+    fn bad_requests() {
+        let mut server = server();
+
+        for target in [
+            "/passage",
+            "/passage?ref=Nowhere+1",
+            "/passage?ref=John+22",
+            "/search",
+            "/search?q=+",
+            "/search?q=love&limit=0",
+            "/search?q=love&book=Nowhere",
+            "/translations?translation=XYZ",
+            "/versification?ref=John+3",
+        ] {
+            assert_eq!(get(&mut server, target).status, 400, "{}", target);
+        }
+
+        let response = get(&mut server, "/passage?ref=John+3:16&translation=XYZ");
+        assert_eq!(response.body, "{\"error\":\"Unknown translation XYZ\"}");
+    }
+
+    #[test]
+    fn not_found() {
+        let mut server = server();
+
+        assert_eq!(get(&mut server, "/passage?ref=John+4:1").status, 404);
+        assert_eq!(get(&mut server, "/passage?ref=John+3:30").status, 404);
+
+        let response = get(&mut server, "/nowhere?format=text");
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body, "No endpoint /nowhere");
+    }
+
+    #[test]
+    fn only_get() {
+        let mut server = server();
+
+        let response = server.route(&request("POST /passage?ref=John+3:16 HTTP/1.1\r\n\r\n"));
+        assert_eq!(response.status, 405);
+    }
+
+    #[test]
+    fn translations() {
+        let mut server = server();
+
+        let response = get(&mut server, "/translations");
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, "{\"translations\":[\"KJV\",\"WLC\"]}");
+
+        let response = get(
+            &mut server,
+            "/versification?ref=Malachi+3:19&from=hebrew&format=text",
+        );
+        assert!(response.body.contains("Malachi 4:1"));
+    }
+
+    #[test]
+    fn url_decoding() {
+        assert_eq!(url_decode("a+b%20c%2B%zz%"), "a b c+%zz%");
+        assert_eq!(url_decode("%E1%84%80"), "ᄀ");
+    }
+}
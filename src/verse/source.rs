@@ -0,0 +1,554 @@
+use crate::verse::{
+    db::{BibleBook, Canon, Error, Result, Verse},
+    index::{BibleIndex, Text},
+    strongs::{self, Strong},
+    versification::Versification,
+};
+
+/*
+Where Bible text comes from. A source holds one or more translations, named like
+"ASV", and answers for any of them. Only translations, chapter and describe have
+to be implemented, everything else falls back on reading chapters and backends
+override what they can answer faster. Sources are shared with background threads.
+*/
+pub trait BibleSource: Send + Sync {
+    //Names of the translations, sorted
+    fn translations(&self) -> Result<Vec<String>>;
+
+    //Verse texts of the chapter, empty if the translation lacks it
+    fn chapter(&self, translation: &str, book: BibleBook, chapter: u8) -> Result<Vec<String>>;
+
+    //Where the text is read from, for the user
+    fn describe(&self) -> String;
+
+    //The translation in memory, if the source holds it that way
+    fn index(&self, _translation: &str) -> Option<&BibleIndex> {
+        None
+    }
+
+    fn metadata(&self, translation: &str) -> Result<Metadata> {
+        let books = self.books(translation)?;
+        Ok(Metadata {
+            translation: translation.to_string(),
+            source: self.describe(),
+            books: books.len(),
+            canon: Canon::of(&books),
+            versification: self.versification(translation)?,
+            language: self.language(translation)?,
+            verses: self.count_verses(translation, BibleBook::Genesis, BibleBook::LAST)?,
+        })
+    }
+
+    //Numbering the translation came in, English unless it was imported from another
+    fn versification(&self, translation: &str) -> Result<Versification> {
+        Ok(self
+            .index(translation)
+            .map_or(Versification::English, |index| index.versification))
+    }
+
+    //Language the translation is in, if the source knows it
+    fn language(&self, translation: &str) -> Result<Option<String>> {
+        Ok(self
+            .index(translation)
+            .and_then(|index| index.language.clone()))
+    }
+
+    //Books the translation has text for
+    fn books(&self, translation: &str) -> Result<Vec<BibleBook>> {
+        let mut books = vec![];
+        for book in BibleBook::ALL {
+            if self.chapter_count(translation, book)? > 0 {
+                books.push(book);
+            }
+        }
+        Ok(books)
+    }
+
+    //Last chapter of the book with text, 0 if it has none
+    fn chapter_count(&self, translation: &str, book: BibleBook) -> Result<u8> {
+        for chapter in (1..=book.max_chapter_count()).rev() {
+            if self.verse_count(translation, book, chapter)? > 0 {
+                return Ok(chapter);
+            }
+        }
+        Ok(0)
+    }
+
+    fn verse_count(&self, translation: &str, book: BibleBook, chapter: u8) -> Result<u8> {
+        Ok(self.chapter(translation, book, chapter)?.len() as u8)
+    }
+
+    fn verse(
+        &self,
+        translation: &str,
+        book: BibleBook,
+        chapter: u8,
+        verse: u8,
+    ) -> Result<Option<String>> {
+        Ok(self
+            .chapter(translation, book, chapter)?
+            .into_iter()
+            .nth((verse as usize).wrapping_sub(1)))
+    }
+
+    //Verses first to last of the chapter, as far as it goes
+    fn range(
+        &self,
+        translation: &str,
+        book: BibleBook,
+        chapter: u8,
+        first: u8,
+        last: u8,
+    ) -> Result<Vec<Verse>> {
+        let texts = self.chapter(translation, book, chapter)?;
+        Ok(verses_between(book, chapter, texts, first, last))
+    }
+
+    //Every chapter of the book
+    fn book(&self, translation: &str, book: BibleBook) -> Result<Vec<Vec<String>>> {
+        (1..=book.max_chapter_count())
+            .map(|chapter| self.chapter(translation, book, chapter))
+            .collect()
+    }
+
+    //Number of verses in the books from first to last
+    fn count_verses(&self, translation: &str, first: BibleBook, last: BibleBook) -> Result<u32> {
+        let mut count = 0;
+        for book in books_between(first, last) {
+            for chapter in 1..=book.max_chapter_count() {
+                count += self.verse_count(translation, book, chapter)? as u32;
+            }
+        }
+        Ok(count)
+    }
+
+    //The nth verse (from 0) of the books from first to last
+    fn nth_verse(
+        &self,
+        translation: &str,
+        first: BibleBook,
+        last: BibleBook,
+        n: u32,
+    ) -> Result<Verse> {
+        let mut n = n as usize;
+        for book in books_between(first, last) {
+            for chapter in 1..=book.max_chapter_count() {
+                let texts = self.chapter(translation, book, chapter)?;
+                if let Some(text) = texts.get(n) {
+                    return Ok(Verse {
+                        book,
+                        chapter,
+                        verse: n as u8 + 1,
+                        text: text.clone(),
+                    });
+                }
+                n -= texts.len();
+            }
+        }
+        Err(Error::NoSuchVerse)
+    }
+
+    //Verses of the books from first to last containing the words, ignoring case and
+    //Strong's tags
+    fn search(
+        &self,
+        translation: &str,
+        words: &str,
+        first: BibleBook,
+        last: BibleBook,
+        limit: u32,
+    ) -> Result<Vec<Verse>> {
+        let words = words.to_lowercase();
+        let mut verses = vec![];
+        for book in books_between(first, last) {
+            for chapter in 1..=book.max_chapter_count() {
+                for verse in self.range(translation, book, chapter, 1, u8::MAX)? {
+                    if verses.len() == limit as usize {
+                        return Ok(verses);
+                    }
+                    if strongs::strip(&verse.text).to_lowercase().contains(&words) {
+                        verses.push(verse);
+                    }
+                }
+            }
+        }
+        Ok(verses)
+    }
+
+    //Verses of the books from first to last with a word tagged with the number
+    fn tagged(
+        &self,
+        translation: &str,
+        strong: Strong,
+        first: BibleBook,
+        last: BibleBook,
+    ) -> Result<Vec<Verse>> {
+        let tag = strong.tag();
+        let mut verses = vec![];
+        for book in books_between(first, last) {
+            for chapter in 1..=book.max_chapter_count() {
+                for verse in self.range(translation, book, chapter, 1, u8::MAX)? {
+                    if verse.text.contains(&tag) {
+                        verses.push(verse);
+                    }
+                }
+            }
+        }
+        Ok(verses)
+    }
+
+    //Reads the whole translation for an in-memory index
+    fn load_index(&self, translation: &str) -> Result<BibleIndex> {
+        let mut verses = vec![];
+        for book in BibleBook::ALL {
+            for (chapter, texts) in (1..).zip(self.book(translation, book)?) {
+                verses.extend(
+                    (1..)
+                        .zip(texts)
+                        .map(|(verse, text)| (book, chapter, verse, text)),
+                );
+            }
+        }
+        Ok(BibleIndex::build(translation, verses))
+    }
+}
+
+//What the metadata command and API report about a translation
+pub struct Metadata {
+    pub translation: String,
+    pub source: String,
+    pub books: usize,
+    pub canon: Canon,
+    pub versification: Versification,
+    pub language: Option<String>,
+    pub verses: u32,
+}
+
+//Verses first to last out of a chapter's texts
+pub fn verses_between(
+    book: BibleBook,
+    chapter: u8,
+    texts: Vec<String>,
+    first: u8,
+    last: u8,
+) -> Vec<Verse> {
+    texts
+        .into_iter()
+        .zip(1..=u8::MAX)
+        .filter(|(_, verse)| (first..=last).contains(verse))
+        .map(|(text, verse)| Verse {
+            book,
+            chapter,
+            verse,
+            text,
+        })
+        .collect()
+}
+
+fn books_between(first: BibleBook, last: BibleBook) -> impl Iterator<Item = BibleBook> {
+    BibleBook::ALL
+        .into_iter()
+        .filter(move |book| *book as u8 >= first as u8 && *book as u8 <= last as u8)
+}
+
+//Translations held in memory, read from compiled files or built for tests
+pub struct Memory {
+    name: String,
+    indexes: Vec<BibleIndex>,
+}
+
+impl Memory {
+    pub fn new(name: &str, mut indexes: Vec<BibleIndex>) -> Self {
+        indexes.sort_by(|a, b| a.translation.cmp(&b.translation));
+        Memory {
+            name: name.to_string(),
+            indexes,
+        }
+    }
+
+    fn get(&self, translation: &str) -> Result<&BibleIndex> {
+        self.index(translation)
+            .ok_or_else(|| Error::UnknownTranslation(translation.to_string()))
+    }
+}
+
+impl BibleSource for Memory {
+    fn translations(&self) -> Result<Vec<String>> {
+        Ok(self
+            .indexes
+            .iter()
+            .map(|index| index.translation.clone())
+            .collect())
+    }
+
+    fn chapter(&self, translation: &str, book: BibleBook, chapter: u8) -> Result<Vec<String>> {
+        Ok(self
+            .get(translation)?
+            .chapter(book, chapter)
+            .into_iter()
+            .map(String::from)
+            .collect())
+    }
+
+    fn describe(&self) -> String {
+        self.name.clone()
+    }
+
+    fn index(&self, translation: &str) -> Option<&BibleIndex> {
+        self.indexes
+            .iter()
+            .find(|index| index.translation == translation)
+    }
+
+    fn verse(
+        &self,
+        translation: &str,
+        book: BibleBook,
+        chapter: u8,
+        verse: u8,
+    ) -> Result<Option<String>> {
+        Ok(self
+            .get(translation)?
+            .verse(book, chapter, verse)
+            .map(String::from))
+    }
+
+    fn verse_count(&self, translation: &str, book: BibleBook, chapter: u8) -> Result<u8> {
+        Ok(self.get(translation)?.chapter(book, chapter).len() as u8)
+    }
+
+    fn count_verses(&self, translation: &str, first: BibleBook, last: BibleBook) -> Result<u32> {
+        Ok(self.get(translation)?.count_verses(first, last))
+    }
+
+    fn nth_verse(
+        &self,
+        translation: &str,
+        first: BibleBook,
+        last: BibleBook,
+        n: u32,
+    ) -> Result<Verse> {
+        self.get(translation)?
+            .nth_verse(first, last, n)
+            .ok_or(Error::NoSuchVerse)
+    }
+
+    fn search(
+        &self,
+        translation: &str,
+        words: &str,
+        first: BibleBook,
+        last: BibleBook,
+        limit: u32,
+    ) -> Result<Vec<Verse>> {
+        Ok(self.get(translation)?.search(words, first, last, limit))
+    }
+
+    fn tagged(
+        &self,
+        translation: &str,
+        strong: Strong,
+        first: BibleBook,
+        last: BibleBook,
+    ) -> Result<Vec<Verse>> {
+        Ok(self.get(translation)?.tagged(strong, first, last))
+    }
+
+    fn load_index(&self, translation: &str) -> Result<BibleIndex> {
+        let index = self.get(translation)?;
+        let (text, verse_starts, chapter_starts) = index.parts();
+        let mut copy = BibleIndex::from_parts(
+            translation,
+            Text::Owned(text.to_string()),
+            verse_starts.to_vec(),
+            chapter_starts.to_vec(),
+        )
+        .map_err(Error::Format)?;
+        copy.versification = index.versification;
+        copy.language = index.language.clone();
+        Ok(copy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Reads chapters out of the memory source and nothing else, so every other
+    //method is the trait's own
+    struct Chapters(Memory);
+
+    impl BibleSource for Chapters {
+        fn translations(&self) -> Result<Vec<String>> {
+            self.0.translations()
+        }
+
+        fn chapter(&self, translation: &str, book: BibleBook, chapter: u8) -> Result<Vec<String>> {
+            self.0.chapter(translation, book, chapter)
+        }
+
+        fn describe(&self) -> String {
+            self.0.describe()
+        }
+    }
+
+    fn bible() -> Memory {
+        let verses = [
+            (
+                BibleBook::Genesis,
+                1,
+                1,
+                "In the beginning God{H430} created",
+            ),
+            (BibleBook::Genesis, 1, 2, "And the earth was without form"),
+            (BibleBook::Genesis, 2, 1, "Thus the heavens were finished"),
+            (BibleBook::John, 1, 1, "In the beginning was the Word"),
+            (BibleBook::John, 3, 16, "For God{G2316} so loved the world"),
+            (BibleBook::Tobit, 1, 1, "The book of the words of Tobit"),
+        ];
+        let mut index = BibleIndex::build(
+            "TEST",
+            verses.map(|(book, chapter, verse, text)| (book, chapter, verse, text.to_string())),
+        );
+        index.language = Some("en".to_string());
+        Memory::new("test", vec![index, BibleIndex::build("EMPTY", vec![])])
+    }
+
+    fn references(verses: Vec<Verse>) -> Vec<String> {
+        verses.iter().map(Verse::reference).collect()
+    }
+
+    #[test]
+    fn memory_answers_like_chapters() {
+        let memory = bible();
+        let chapters = Chapters(bible());
+        let sources: [&dyn BibleSource; 2] = [&memory, &chapters];
+
+        for source in sources {
+            assert_eq!(source.translations().unwrap(), ["EMPTY", "TEST"]);
+            assert_eq!(
+                source.books("TEST").unwrap(),
+                [BibleBook::Genesis, BibleBook::John, BibleBook::Tobit]
+            );
+            assert_eq!(source.chapter_count("TEST", BibleBook::John).unwrap(), 3);
+            assert_eq!(source.verse_count("TEST", BibleBook::John, 3).unwrap(), 16);
+            assert_eq!(
+                source
+                    .verse("TEST", BibleBook::Genesis, 1, 2)
+                    .unwrap()
+                    .as_deref(),
+                Some("And the earth was without form")
+            );
+            assert_eq!(
+                source.verse("TEST", BibleBook::Genesis, 1, 3).unwrap(),
+                None
+            );
+            assert_eq!(
+                source.verse("TEST", BibleBook::Genesis, 1, 0).unwrap(),
+                None
+            );
+            assert_eq!(
+                references(source.range("TEST", BibleBook::Genesis, 1, 2, 9).unwrap()),
+                ["Genesis 1:2"]
+            );
+
+            let count = source
+                .count_verses("TEST", BibleBook::Genesis, BibleBook::LAST)
+                .unwrap();
+            assert_eq!(count, 3 + 1 + 16 + 1);
+            assert_eq!(
+                source
+                    .count_verses("TEST", BibleBook::Genesis, BibleBook::Malachi)
+                    .unwrap(),
+                3
+            );
+            let nth = |n| {
+                source
+                    .nth_verse("TEST", BibleBook::Genesis, BibleBook::LAST, n)
+                    .map(|verse| verse.reference())
+            };
+            assert_eq!(nth(2).unwrap(), "Genesis 2:1");
+            assert_eq!(nth(3).unwrap(), "John 1:1");
+            assert_eq!(nth(count - 2).unwrap(), "John 3:16");
+            assert_eq!(nth(count - 1).unwrap(), "Tobit 1:1");
+            assert!(matches!(nth(count), Err(Error::NoSuchVerse)));
+
+            //Tags are not part of the words
+            let search = |words, first, limit| {
+                references(
+                    source
+                        .search("TEST", words, first, BibleBook::LAST, limit)
+                        .unwrap(),
+                )
+            };
+            assert_eq!(
+                search("in the BEGINNING", BibleBook::Genesis, 10),
+                ["Genesis 1:1", "John 1:1"]
+            );
+            assert_eq!(
+                search("in the beginning", BibleBook::Genesis, 1),
+                ["Genesis 1:1"]
+            );
+            assert_eq!(
+                search("in the beginning", BibleBook::Matthew, 10),
+                ["John 1:1"]
+            );
+            assert_eq!(
+                search("god created", BibleBook::Genesis, 10),
+                ["Genesis 1:1"]
+            );
+            assert_eq!(
+                references(
+                    source
+                        .tagged(
+                            "TEST",
+                            Strong::Greek(2316),
+                            BibleBook::Genesis,
+                            BibleBook::LAST
+                        )
+                        .unwrap()
+                ),
+                ["John 3:16"]
+            );
+
+            let metadata = source.metadata("TEST").unwrap();
+            assert_eq!(metadata.books, 3);
+            assert_eq!(metadata.canon, Canon::Catholic);
+            assert_eq!(metadata.verses, count);
+
+            assert!(source.books("EMPTY").unwrap().is_empty());
+        }
+
+        //Only the source holding the index knows the language
+        assert_eq!(memory.language("TEST").unwrap().as_deref(), Some("en"));
+        assert_eq!(chapters.language("TEST").unwrap(), None);
+    }
+
+    #[test]
+    fn unknown_translation() {
+        let memory = bible();
+        assert!(matches!(
+            memory.chapter("KJV", BibleBook::John, 3),
+            Err(Error::UnknownTranslation(name)) if name == "KJV"
+        ));
+        assert!(memory.index("test").is_none());
+        assert!(memory.load_index("KJV").is_err());
+    }
+
+    #[test]
+    fn index_copies() {
+        let memory = bible();
+        let copy = memory.load_index("TEST").unwrap();
+        let read = Chapters(bible()).load_index("TEST").unwrap();
+
+        for index in [&copy, &read] {
+            assert_eq!(index.translation, "TEST");
+            assert_eq!(index.count_verses(BibleBook::Genesis, BibleBook::LAST), 21);
+            assert_eq!(
+                index.verse(BibleBook::John, 3, 16),
+                Some("For God{G2316} so loved the world")
+            );
+        }
+        assert_eq!(copy.language.as_deref(), Some("en"));
+    }
+}
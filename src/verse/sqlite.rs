@@ -0,0 +1,590 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+
+use crate::verse::{
+    db::{BibleBook, Result, Verse},
+    import::{
+        deuterocanonical_chapter, deuterocanonical_origin,
+        scrollmapper::{book_from_name, douay_kings},
+    },
+    index::BibleIndex,
+    source::BibleSource,
+    strongs::{self, Strong},
+};
+
+//A scrollmapper DB, every translation in a <NAME>_verses table. Translation names end
+//up in the SQL, so only ones from translations() may be passed in
+pub struct Sqlite {
+    path: PathBuf,
+    connection: Mutex<Connection>,
+}
+
+impl Sqlite {
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Sqlite {
+            path: path.to_path_buf(),
+            connection: Mutex::new(connect(path)?),
+        })
+    }
+
+    //A panic elsewhere doesn't leave a read only connection in a bad state
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl BibleSource for Sqlite {
+    //Reading the whole translation takes a while, it gets a connection of its own so
+    //the reader can fetch chapters in the meantime
+    fn load_index(&self, translation: &str) -> Result<BibleIndex> {
+        let connection = connect(&self.path)?;
+        let books = Books::read(&connection, translation)?;
+        let verses = books.verses(
+            connection
+                .prepare(&format!(
+                    "SELECT book_id, chapter, verse, text FROM {}_verses",
+                    translation
+                ))?
+                .query_map([], row)?,
+        )?;
+
+        Ok(BibleIndex::build(
+            translation,
+            verses
+                .into_iter()
+                .map(|verse| (verse.book, verse.chapter, verse.verse, verse.text)),
+        ))
+    }
+
+    fn chapter(&self, translation: &str, book: BibleBook, chapter: u8) -> Result<Vec<String>> {
+        let connection = self.connection();
+        let Some((id, chapter)) = Books::read(&connection, translation)?.find(book, chapter) else {
+            return Ok(vec![]);
+        };
+        let mut stmt = connection.prepare_cached(&format!(
+            "SELECT verse, text FROM {}_verses WHERE book_id = ? AND chapter = ? ORDER BY verse",
+            translation
+        ))?;
+
+        //Verses are found by position, ones missing from the table stay empty
+        let mut texts = vec![];
+        for row in stmt.query_map([id, chapter as u32], |row| {
+            Ok((row.get::<_, u8>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (verse, text) = row?;
+            if verse as usize > texts.len() {
+                texts.resize(verse as usize - 1, String::new());
+                texts.push(text);
+            }
+        }
+        Ok(texts)
+    }
+
+    fn describe(&self) -> String {
+        format!("SQLite database {}", self.path.display())
+    }
+
+    fn books(&self, translation: &str) -> Result<Vec<BibleBook>> {
+        let mut books = self
+            .chapters(translation)?
+            .into_iter()
+            .map(|(book, _, _)| book)
+            .collect::<Vec<BibleBook>>();
+        books.dedup();
+        Ok(books)
+    }
+
+    fn verse(
+        &self,
+        translation: &str,
+        book: BibleBook,
+        chapter: u8,
+        verse: u8,
+    ) -> Result<Option<String>> {
+        let connection = self.connection();
+        let Some((id, chapter)) = Books::read(&connection, translation)?.find(book, chapter) else {
+            return Ok(None);
+        };
+
+        Ok(connection
+            .prepare_cached(&format!(
+                "SELECT text FROM {}_verses WHERE book_id = ? AND chapter = ? AND verse = ?",
+                translation
+            ))?
+            .query_row([id, chapter as u32, verse as u32], |row| row.get(0))
+            .optional()?)
+    }
+
+    fn count_verses(&self, translation: &str, first: BibleBook, last: BibleBook) -> Result<u32> {
+        Ok(self
+            .chapters(translation)?
+            .into_iter()
+            .filter(|(book, _, _)| between(*book, first, last))
+            .map(|(_, _, count)| count)
+            .sum())
+    }
+
+    //Counting the verses chapter by chapter, as the translation's books may be in
+    //another order than verse's
+    fn nth_verse(
+        &self,
+        translation: &str,
+        first: BibleBook,
+        last: BibleBook,
+        n: u32,
+    ) -> Result<Verse> {
+        let mut n = n;
+        let (book, chapter, offset) = self
+            .chapters(translation)?
+            .into_iter()
+            .filter(|(book, _, _)| between(*book, first, last))
+            .find_map(|(book, chapter, count)| match n.checked_sub(count) {
+                Some(rest) => {
+                    n = rest;
+                    None
+                }
+                None => Some((book, chapter, n)),
+            })
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+        let connection = self.connection();
+        let (id, source_chapter) = Books::read(&connection, translation)?
+            .find(book, chapter)
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        let (verse, text) = connection
+            .prepare_cached(&format!(
+                "SELECT verse, text FROM {}_verses WHERE book_id = ? AND chapter = ? \
+                 ORDER BY verse LIMIT 1 OFFSET ?",
+                translation
+            ))?
+            .query_row([id, source_chapter as u32, offset], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+
+        Ok(Verse {
+            book,
+            chapter,
+            verse,
+            text,
+        })
+    }
+
+    //LIKE only narrows the verses down, Strong's tags may sit between the words and
+    //their punctuation, "earth{H776}." for "earth.", so the words are matched without them.
+    //LIKE ignores case only for ASCII letters, the pattern keeps to the ASCII parts of
+    //the words, "élie" narrows by "%lie%"
+    fn search(
+        &self,
+        translation: &str,
+        words: &str,
+        first: BibleBook,
+        last: BibleBook,
+        limit: u32,
+    ) -> Result<Vec<Verse>> {
+        let pattern = format!(
+            "%{}%",
+            words
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<&str>>()
+                .join("%")
+        );
+        let lowercase = words.to_lowercase();
+
+        let connection = self.connection();
+        let books = Books::read(&connection, translation)?;
+        let mut stmt = connection.prepare_cached(&format!(
+            "SELECT book_id, chapter, verse, text FROM {}_verses WHERE text LIKE ?",
+            translation
+        ))?;
+
+        let mut verses = books.verses(stmt.query_map([pattern], row)?)?;
+        verses.retain(|verse| {
+            between(verse.book, first, last)
+                && strongs::strip(&verse.text)
+                    .to_lowercase()
+                    .contains(&lowercase)
+        });
+        verses.truncate(limit as usize);
+        Ok(verses)
+    }
+
+    fn tagged(
+        &self,
+        translation: &str,
+        strong: Strong,
+        first: BibleBook,
+        last: BibleBook,
+    ) -> Result<Vec<Verse>> {
+        let connection = self.connection();
+        let books = Books::read(&connection, translation)?;
+        let mut stmt = connection.prepare_cached(&format!(
+            "SELECT book_id, chapter, verse, text FROM {}_verses WHERE instr(text, ?) > 0",
+            translation
+        ))?;
+
+        let mut verses = books.verses(stmt.query_map([strong.tag()], row)?)?;
+        verses.retain(|verse| between(verse.book, first, last));
+        Ok(verses)
+    }
+
+    //One <NAME>_verses table each
+    fn translations(&self) -> Result<Vec<String>> {
+        Ok(self
+            .connection()
+            .prepare_cached(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE '%\\_verses' ESCAPE '\\' ORDER BY name",
+            )?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .map(|name| name.map(|name| name.trim_end_matches("_verses").to_string()))
+            .collect::<rusqlite::Result<Vec<String>>>()?)
+    }
+}
+
+impl Sqlite {
+    //Book, chapter and verse count of every chapter with text, in canonical order
+    fn chapters(&self, translation: &str) -> Result<Vec<(BibleBook, u8, u32)>> {
+        let connection = self.connection();
+        let books = Books::read(&connection, translation)?;
+        let mut chapters = connection
+            .prepare_cached(&format!(
+                "SELECT book_id, chapter, COUNT(*) FROM {}_verses GROUP BY book_id, chapter",
+                translation
+            ))?
+            .query_map([], |row| {
+                Ok((row.get::<_, u32>(0)?, row.get::<_, u8>(1)?, row.get(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<(u32, u8, u32)>>>()?
+            .into_iter()
+            .filter_map(|(id, chapter, count)| {
+                let (book, chapter) = books.locate(id, chapter)?;
+                Some((book, chapter, count))
+            })
+            .collect::<Vec<_>>();
+
+        chapters.sort_by_key(|(book, chapter, _)| (*book as u8, *chapter));
+        Ok(chapters)
+    }
+}
+
+/*
+Which of verse's books a translation's book ids stand for. Newer databases name them in
+a <NAME>_books table, which Bibles like Douay-Rheims need as they number Tobit and the
+other deuterocanonical books among the Old Testament ones. Without the table the ids
+are taken as 1 Genesis to 66 Revelation. Chapters Catholic and Orthodox Bibles add to
+Daniel, Esther and the Psalms are moved to books of their own, as on import
+*/
+struct Books(Vec<(u32, BibleBook)>);
+
+impl Books {
+    fn read(connection: &Connection, translation: &str) -> Result<Books> {
+        let table = format!("{}_books", translation);
+        let named = connection
+            .prepare_cached("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?")?
+            .exists([&table])?;
+        if !named {
+            return Ok(Books(
+                (1..=BibleBook::Revelation as u8)
+                    .filter_map(|id| Some((id as u32, BibleBook::try_from(id).ok()?)))
+                    .collect(),
+            ));
+        }
+
+        let names = connection
+            .prepare_cached(&format!("SELECT id, name FROM {}", table))?
+            .query_map([], |row| {
+                Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<(u32, String)>>>()?;
+        let douay = douay_kings(names.iter().map(|(_, name)| name.as_str()));
+
+        Ok(Books(
+            names
+                .iter()
+                .filter_map(|(id, name)| Some((*id, book_from_name(name, douay).ok()?)))
+                .collect(),
+        ))
+    }
+
+    //Book and chapter a row's book id and chapter are, None for books verse doesn't have
+    fn locate(&self, id: u32, chapter: u8) -> Option<(BibleBook, u8)> {
+        let (_, book) = self.0.iter().find(|(book_id, _)| *book_id == id)?;
+        match self.has(deuterocanonical_chapter(*book, chapter).0) {
+            true => Some((*book, chapter)),
+            false => Some(deuterocanonical_chapter(*book, chapter)),
+        }
+    }
+
+    //Book id and chapter the book's chapter is found under, the way back of locate
+    fn find(&self, book: BibleBook, chapter: u8) -> Option<(u32, u8)> {
+        let id = |book: BibleBook| {
+            self.0
+                .iter()
+                .find(|(_, named)| *named == book)
+                .map(|(id, _)| *id)
+        };
+
+        let found = match (id(book), deuterocanonical_origin(book, chapter)) {
+            (Some(id), _) => (id, chapter),
+            (None, Some((book, chapter))) => (id(book)?, chapter),
+            (None, None) => return None,
+        };
+        (self.locate(found.0, found.1) == Some((book, chapter))).then_some(found)
+    }
+
+    fn has(&self, book: BibleBook) -> bool {
+        self.0.iter().any(|(_, named)| *named == book)
+    }
+
+    //Rows of book_id, chapter, verse, text as verses in canonical order
+    fn verses(
+        &self,
+        rows: impl Iterator<Item = rusqlite::Result<(u32, u8, u8, String)>>,
+    ) -> Result<Vec<Verse>> {
+        let mut verses = vec![];
+        for row in rows {
+            let (id, chapter, verse, text) = row?;
+            if let Some((book, chapter)) = self.locate(id, chapter) {
+                verses.push(Verse {
+                    book,
+                    chapter,
+                    verse,
+                    text,
+                });
+            }
+        }
+
+        verses.sort_by_key(|verse| (verse.book as u8, verse.chapter, verse.verse));
+        Ok(verses)
+    }
+}
+
+fn connect(path: &Path) -> rusqlite::Result<Connection> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+}
+
+//Row of book_id, chapter, verse, text
+fn row(row: &rusqlite::Row) -> rusqlite::Result<(u32, u8, u8, String)> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+}
+
+//Whether the book is one of first to last in verse's order
+fn between(book: BibleBook, first: BibleBook, last: BibleBook) -> bool {
+    (first as u8..=last as u8).contains(&(book as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+    use crate::verse::source::Memory;
+
+    //A database of its own in the temp dir, removed when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, sql: &str) -> Self {
+            let file =
+                TempFile(env::temp_dir().join(format!("verse-{}-{}.db", process::id(), name)));
+            let _ = fs::remove_file(&file.0);
+            Connection::open(&file.0)
+                .unwrap()
+                .execute_batch(sql)
+                .unwrap();
+            file
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    //Douay-Rheims numbers its books in the Vulgate's order, Tobias among the Old Testament
+    //ones and Samuel and Kings as 1-4 Kings. Susanna is Daniel 13 and Matthew 17:21 is missing
+    const DRC: &str = "
+        CREATE TABLE DRC_books (id INTEGER, name TEXT);
+        INSERT INTO DRC_books VALUES (1, 'Genesis'), (9, 'I Kings'), (11, 'III Kings'),
+            (12, 'IV Kings'), (16, '2 Esdras'), (17, 'Tobias'), (27, 'Daniel'),
+            (47, 'Matthew'), (73, 'Apocalypse'), (80, 'Prayer of Manasses');
+        CREATE TABLE DRC_verses (id INTEGER, book_id INTEGER, chapter INTEGER, verse INTEGER, text TEXT);
+        INSERT INTO DRC_verses (book_id, chapter, verse, text) VALUES
+            (1, 1, 1, 'In the beginning God created heaven, and earth.'),
+            (9, 1, 1, 'There was a man of Ramathaimsophim'),
+            (11, 1, 1, 'Now king David was old'),
+            (16, 1, 1, 'The words of Nehemias'),
+            (17, 1, 1, 'Tobias of the tribe and city of Nephthali'),
+            (27, 1, 1, 'In the third year of the reign of Joakim'),
+            (27, 13, 1, 'Now there was a man that dwelt in Babylon'),
+            (47, 17, 20, 'Because of your unbelief'),
+            (47, 17, 22, 'And when they abode together in Galilee'),
+            (73, 1, 1, 'The Revelation of Jesus Christ'),
+            (80, 1, 1, 'O Lord almighty');
+    ";
+
+    #[test]
+    fn books_by_name() {
+        let file = TempFile::new("drc", DRC);
+        let sqlite = Sqlite::open(&file.0).unwrap();
+
+        assert_eq!(
+            sqlite.books("DRC").unwrap(),
+            [
+                BibleBook::Genesis,
+                BibleBook::FirstSamuel,
+                BibleBook::FirstKings,
+                BibleBook::Nehemiah,
+                BibleBook::Daniel,
+                BibleBook::Matthew,
+                BibleBook::Revelation,
+                BibleBook::Tobit,
+                BibleBook::Susanna,
+            ]
+        );
+        assert_eq!(
+            sqlite
+                .verse("DRC", BibleBook::Tobit, 1, 1)
+                .unwrap()
+                .as_deref(),
+            Some("Tobias of the tribe and city of Nephthali")
+        );
+        assert_eq!(
+            sqlite
+                .verse("DRC", BibleBook::Susanna, 1, 1)
+                .unwrap()
+                .as_deref(),
+            Some("Now there was a man that dwelt in Babylon")
+        );
+        assert_eq!(
+            sqlite.chapter("DRC", BibleBook::FirstKings, 1).unwrap(),
+            ["Now king David was old"]
+        );
+        assert!(
+            sqlite
+                .chapter("DRC", BibleBook::Daniel, 13)
+                .unwrap()
+                .is_empty()
+        );
+
+        let matthew = sqlite.chapter("DRC", BibleBook::Matthew, 17).unwrap();
+        assert_eq!(matthew.len(), 22);
+        assert_eq!(matthew[19], "Because of your unbelief");
+        assert_eq!(matthew[20], "");
+
+        let index = sqlite.load_index("DRC").unwrap();
+        assert_eq!(
+            index.verse(BibleBook::Tobit, 1, 1),
+            Some("Tobias of the tribe and city of Nephthali")
+        );
+        assert_eq!(
+            index.verse(BibleBook::Matthew, 17, 22),
+            Some("And when they abode together in Galilee")
+        );
+        assert_eq!(
+            index.verse(BibleBook::Susanna, 1, 1),
+            Some("Now there was a man that dwelt in Babylon")
+        );
+    }
+
+    #[test]
+    fn ranges_in_canonical_order() {
+        let file = TempFile::new("drc-ranges", DRC);
+        let sqlite = Sqlite::open(&file.0).unwrap();
+
+        assert_eq!(
+            sqlite
+                .count_verses("DRC", BibleBook::Genesis, BibleBook::Malachi)
+                .unwrap(),
+            5
+        );
+        let verse = sqlite
+            .nth_verse("DRC", BibleBook::Genesis, BibleBook::ThirdMeqabyan, 8)
+            .unwrap();
+        assert_eq!(
+            (verse.book, verse.chapter, verse.verse),
+            (BibleBook::Tobit, 1, 1)
+        );
+        let verse = sqlite
+            .nth_verse("DRC", BibleBook::Susanna, BibleBook::Susanna, 0)
+            .unwrap();
+        assert_eq!(
+            (verse.book, verse.chapter, verse.verse),
+            (BibleBook::Susanna, 1, 1)
+        );
+
+        let found = sqlite
+            .search(
+                "DRC",
+                "MAN",
+                BibleBook::Genesis,
+                BibleBook::ThirdMeqabyan,
+                10,
+            )
+            .unwrap();
+        let found: Vec<_> = found.iter().map(|verse| verse.book).collect();
+        assert_eq!(found, [BibleBook::FirstSamuel, BibleBook::Susanna]);
+    }
+
+    //The same verses found with the same words whether they're read from SQLite or memory
+    #[test]
+    fn search_like_memory() {
+        let file = TempFile::new(
+            "lsg",
+            "CREATE TABLE LSG_verses (id INTEGER, book_id INTEGER, chapter INTEGER, verse INTEGER, text TEXT);
+             INSERT INTO LSG_verses (book_id, chapter, verse, text) VALUES
+                (39, 4, 5, 'Voici, je vous enverrai Élie, le prophète'),
+                (42, 1, 17, 'avec l''esprit et la puissance d''Élie'),
+                (43, 1, 21, 'Es-tu Élie? Et il dit: Je ne le suis point.'),
+                (44, 7, 40, 'ΕΙΠΟΝ ΤΩ ΑΑΡΩΝ'),
+                (45, 1, 1, 'Paul, serviteur de Jésus{G2424} Christ');",
+        );
+        let sqlite = Sqlite::open(&file.0).unwrap();
+        let memory = Memory::new("test", vec![sqlite.load_index("LSG").unwrap()]);
+
+        for (words, found) in [
+            ("élie", 3),
+            ("ÉLIE, LE", 1),
+            ("Prophète", 1),
+            ("ειπον τω", 1),
+            ("jésus christ", 1),
+            ("élie?", 1),
+        ] {
+            let search = |source: &dyn BibleSource| {
+                source
+                    .search("LSG", words, BibleBook::Genesis, BibleBook::Revelation, 10)
+                    .unwrap()
+                    .into_iter()
+                    .map(|verse| (verse.book, verse.chapter, verse.verse))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(search(&sqlite).len(), found, "{}", words);
+            assert_eq!(search(&sqlite), search(&memory), "{}", words);
+        }
+    }
+
+    //Older databases have no books table, their ids are 1 Genesis to 66 Revelation
+    #[test]
+    fn books_by_number() {
+        let file = TempFile::new(
+            "kjv",
+            "CREATE TABLE KJV_verses (id INTEGER, book_id INTEGER, chapter INTEGER, verse INTEGER, text TEXT);
+             INSERT INTO KJV_verses (book_id, chapter, verse, text) VALUES
+                (1, 1, 1, 'In the beginning'), (66, 1, 1, 'The Revelation'), (67, 1, 1, 'Unknown');",
+        );
+        let sqlite = Sqlite::open(&file.0).unwrap();
+
+        assert_eq!(
+            sqlite.books("KJV").unwrap(),
+            [BibleBook::Genesis, BibleBook::Revelation]
+        );
+        assert_eq!(sqlite.translations().unwrap(), ["KJV"]);
+    }
+}
@@ -0,0 +1,471 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs, io,
+    path::PathBuf,
+};
+
+use crate::verse::{
+    date::Date,
+    db::{BibleBook, Order},
+};
+
+//Where user data lives, $XDG_DATA_HOME/verse or ~/.local/share/verse
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir).join("verse");
+    }
+    if let Some(home) = env::var_os("HOME").filter(|home| !home.is_empty()) {
+        return PathBuf::from(home).join(".local/share/verse");
+    }
+
+    //No home, keep the data next to the executable like the DB
+    env::current_exe()
+        .ok()
+        .and_then(|path| path.parent().map(|dir| dir.to_path_buf()))
+        .unwrap_or_default()
+}
+
+pub struct PlanProgress {
+    pub name: String,
+    pub start: Date,
+    //(day, passage) pairs that were checked off, days start at 1
+    pub done: HashSet<(u16, u8)>,
+}
+
+impl PlanProgress {
+    //Day of the plan on the date, 1 on the start date
+    pub fn day_on(&self, date: Date) -> i32 {
+        date.days_since(self.start) + 1
+    }
+}
+
+//User data, kept as one record per line in data_dir()/user.txt
+pub struct Store {
+    path: PathBuf,
+    pub plan: Option<PlanProgress>,
+    //Chapters marked as read
+    pub read: HashSet<(BibleBook, u8)>,
+    //Chapter last opened in each book
+    pub last: HashMap<BibleBook, u8>,
+    //Highlighted verses with their category, a word like "promise"
+    pub highlights: HashMap<(BibleBook, u8, u8), String>,
+    //Notes on verses, one line each
+    pub notes: HashMap<(BibleBook, u8, u8), String>,
+    //Order the books are read in
+    pub order: Order,
+    //Language of book names and the reader, a locale code. None follows the translation
+    pub language: Option<String>,
+    //Records this version doesn't know, written back untouched
+    unknown: Vec<String>,
+    //The records as last loaded or saved, what this process changed is told apart by them
+    saved: Vec<String>,
+}
+
+impl Store {
+    pub fn load() -> Self {
+        Store::load_from(data_dir().join("user.txt"))
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+        let mut store = Store::parse(path, contents.lines());
+        store.saved = store.records();
+        store
+    }
+
+    fn parse<'a>(path: PathBuf, lines: impl Iterator<Item = &'a str>) -> Self {
+        let mut store = Store {
+            path,
+            plan: None,
+            read: HashSet::new(),
+            last: HashMap::new(),
+            highlights: HashMap::new(),
+            notes: HashMap::new(),
+            order: Order::Canon,
+            language: None,
+            unknown: vec![],
+            saved: vec![],
+        };
+
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            if !store.parse_record(line) {
+                store.unknown.push(line.to_string());
+            }
+        }
+
+        store
+    }
+
+    fn parse_record(&mut self, line: &str) -> bool {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        match fields.as_slice() {
+            ["plan", name, start] => {
+                let Ok(start) = start.parse() else {
+                    return false;
+                };
+                self.plan = Some(PlanProgress {
+                    name: name.to_string(),
+                    start,
+                    done: HashSet::new(),
+                });
+                true
+            }
+            ["done", day, passage] => {
+                let (Ok(day), Ok(passage), Some(plan)) =
+                    (day.parse(), passage.parse(), self.plan.as_mut())
+                else {
+                    return false;
+                };
+                plan.done.insert((day, passage));
+                true
+            }
+            ["read", book, chapter] => {
+                let Some(key) = parse_chapter(book, chapter) else {
+                    return false;
+                };
+                self.read.insert(key);
+                true
+            }
+            ["last", book, chapter] => {
+                let Some((book, chapter)) = parse_chapter(book, chapter) else {
+                    return false;
+                };
+                self.last.insert(book, chapter);
+                true
+            }
+            ["highlight", book, chapter, verse, category] => {
+                let Some(key) = parse_verse(book, chapter, verse) else {
+                    return false;
+                };
+                self.highlights.insert(key, category.to_string());
+                true
+            }
+            ["note", book, chapter, verse, text @ ..] if !text.is_empty() => {
+                let Some(key) = parse_verse(book, chapter, verse) else {
+                    return false;
+                };
+                self.notes.insert(key, text.join(" "));
+                true
+            }
+            ["order", name] => {
+                let Some(order) = Order::parse(name) else {
+                    return false;
+                };
+                self.order = order;
+                true
+            }
+            ["language", code] => {
+                self.language = Some(code.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    //Chapters of the book marked as read
+    pub fn read_in(&self, book: BibleBook) -> usize {
+        (1..=book.max_chapter_count())
+            .filter(|chapter| self.read.contains(&(book, *chapter)))
+            .count()
+    }
+
+    /*
+    The reader and commands like verse highlight may have the file open at the same
+    time. Saving reads it again and puts only what this process changed since it
+    loaded or last saved on top, record by record, so the others' changes stay. The
+    store then holds the merged records. The file is replaced by a rename, a crash
+    halfway leaves the old one
+    */
+    pub fn save(&mut self) -> io::Result<()> {
+        let ours = self.records();
+        let contents = match fs::read_to_string(&self.path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            contents => contents?,
+        };
+        let mut records: Vec<String> = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect();
+
+        for removed in self.saved.iter().filter(|record| !ours.contains(record)) {
+            records.retain(|record| record != removed);
+        }
+        for added in ours.iter().filter(|record| !self.saved.contains(record)) {
+            records.retain(|record| record_key(record) != record_key(added));
+            records.push(added.clone());
+        }
+        //Plan progress only counts after its plan
+        records.sort_by_key(|record| !record.starts_with("plan "));
+
+        let merged = Store::parse(self.path.clone(), records.iter().map(String::as_str));
+        *self = Store {
+            saved: merged.records(),
+            ..merged
+        };
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut temp = self.path.as_os_str().to_owned();
+        temp.push(format!(".{}.tmp", std::process::id()));
+        let temp = PathBuf::from(temp);
+        let result = fs::write(&temp, self.saved.join("\n") + "\n")
+            .and_then(|_| fs::rename(&temp, &self.path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result
+    }
+
+    //The store as the lines of its file
+    fn records(&self) -> Vec<String> {
+        let mut lines: Vec<String> = vec![];
+
+        if let Some(plan) = &self.plan {
+            lines.push(format!("plan {} {}", plan.name, plan.start));
+
+            let mut done: Vec<&(u16, u8)> = plan.done.iter().collect();
+            done.sort();
+            lines.extend(
+                done.into_iter()
+                    .map(|(day, passage)| format!("done {} {}", day, passage)),
+            );
+        }
+
+        let mut read: Vec<(u8, u8)> = self
+            .read
+            .iter()
+            .map(|(book, chapter)| (*book as u8, *chapter))
+            .collect();
+        read.sort();
+        lines.extend(
+            read.into_iter()
+                .map(|(book, chapter)| format!("read {} {}", book, chapter)),
+        );
+
+        let mut last: Vec<(u8, u8)> = self
+            .last
+            .iter()
+            .map(|(book, chapter)| (*book as u8, *chapter))
+            .collect();
+        last.sort();
+        lines.extend(
+            last.into_iter()
+                .map(|(book, chapter)| format!("last {} {}", book, chapter)),
+        );
+
+        lines.extend(
+            sorted_verses(&self.highlights).map(|(book, chapter, verse, category)| {
+                format!("highlight {} {} {} {}", book, chapter, verse, category)
+            }),
+        );
+        lines.extend(
+            sorted_verses(&self.notes).map(|(book, chapter, verse, text)| {
+                format!("note {} {} {} {}", book, chapter, verse, text)
+            }),
+        );
+
+        if self.order != Order::Canon {
+            lines.push(format!("order {}", self.order.as_str().to_lowercase()));
+        }
+        if let Some(language) = &self.language {
+            lines.push(format!("language {}", language));
+        }
+
+        lines.extend(self.unknown.iter().cloned());
+        lines
+    }
+}
+
+//What a record is about, "note 43 3 16" for a note. A record replaces the one with
+//the same key
+fn record_key(record: &str) -> String {
+    let fields: Vec<&str> = record.split_whitespace().collect();
+    let len = match fields.first() {
+        Some(&("plan" | "order" | "language")) => 1,
+        Some(&"last") => 2,
+        Some(&("highlight" | "note")) => 4,
+        _ => fields.len(),
+    };
+    fields[..len.min(fields.len())].join(" ")
+}
+
+//Book id and chapter as stored, checked against the book's chapter count
+fn parse_chapter(book: &str, chapter: &str) -> Option<(BibleBook, u8)> {
+    let book = BibleBook::try_from(book.parse::<u8>().ok()?).ok()?;
+    let chapter = chapter.parse().ok()?;
+
+    if chapter < 1 || chapter > book.max_chapter_count() {
+        return None;
+    }
+    Some((book, chapter))
+}
+
+//Book id, chapter and verse as stored
+fn parse_verse(book: &str, chapter: &str, verse: &str) -> Option<(BibleBook, u8, u8)> {
+    let (book, chapter) = parse_chapter(book, chapter)?;
+    let verse = verse.parse().ok().filter(|verse| *verse >= 1)?;
+    Some((book, chapter, verse))
+}
+
+//Per verse records in Bible order, with book ids as stored
+fn sorted_verses(
+    map: &HashMap<(BibleBook, u8, u8), String>,
+) -> impl Iterator<Item = (u8, u8, u8, &str)> {
+    let mut verses: Vec<(u8, u8, u8, &str)> = map
+        .iter()
+        .map(|((book, chapter, verse), value)| (*book as u8, *chapter, *verse, value.as_str()))
+        .collect();
+    verses.sort();
+    verses.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    //A user.txt of its own in the temp dir, removed when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let file =
+                TempFile(env::temp_dir().join(format!("verse-{}-{}.txt", process::id(), name)));
+            fs::write(&file.0, contents).unwrap();
+            file
+        }
+
+        fn contents(&self) -> String {
+            fs::read_to_string(&self.0).unwrap()
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    const USER: &str = "plan nt90 2026-01-01
+done 1 0
+done 2 1
+read 43 3
+last 43 3
+highlight 43 3 16 promise
+note 43 3 16 For God so loved the world
+order tanakh
+language ko
+bookmark 43 3 16
+";
+
+    #[test]
+    fn round_trip() {
+        let file = TempFile::new("round-trip", USER);
+        let mut store = Store::load_from(file.0.clone());
+
+        let plan = store.plan.as_ref().unwrap();
+        assert_eq!(plan.name, "nt90");
+        assert!(plan.start == Date::from_ymd(2026, 1, 1).unwrap());
+        assert_eq!(plan.done, HashSet::from([(1, 0), (2, 1)]));
+        assert!(store.read.contains(&(BibleBook::John, 3)));
+        assert_eq!(store.last.get(&BibleBook::John), Some(&3));
+        assert_eq!(
+            store
+                .notes
+                .get(&(BibleBook::John, 3, 16))
+                .map(String::as_str),
+            Some("For God so loved the world")
+        );
+        assert_eq!(store.order, Order::Tanakh);
+        assert_eq!(store.language.as_deref(), Some("ko"));
+        assert_eq!(store.read_in(BibleBook::John), 1);
+
+        store.save().unwrap();
+        assert_eq!(file.contents(), USER);
+    }
+
+    #[test]
+    fn bad_records_kept() {
+        let file = TempFile::new(
+            "bad-records",
+            "read 43 99\nread 99 1\nhighlight 43 3 0 promise\n",
+        );
+        let mut store = Store::load_from(file.0.clone());
+        assert!(store.read.is_empty());
+        assert!(store.highlights.is_empty());
+
+        store.read.insert((BibleBook::Genesis, 1));
+        store.save().unwrap();
+        assert_eq!(
+            file.contents(),
+            "read 1 1\nread 43 99\nread 99 1\nhighlight 43 3 0 promise\n"
+        );
+    }
+
+    //The reader and a command each change the file, neither loses the other's changes
+    #[test]
+    fn changes_merged() {
+        let file = TempFile::new("merged", USER);
+        let mut reader = Store::load_from(file.0.clone());
+        let mut command = Store::load_from(file.0.clone());
+
+        command
+            .highlights
+            .insert((BibleBook::Romans, 5, 8), "grace".to_string());
+        command.notes.remove(&(BibleBook::John, 3, 16));
+        command.save().unwrap();
+
+        reader.read.insert((BibleBook::John, 4));
+        reader.last.insert(BibleBook::John, 4);
+        reader.plan.as_mut().unwrap().done.remove(&(2, 1));
+        reader.save().unwrap();
+
+        assert_eq!(
+            reader
+                .highlights
+                .get(&(BibleBook::Romans, 5, 8))
+                .map(String::as_str),
+            Some("grace")
+        );
+        assert!(reader.notes.is_empty());
+        assert_eq!(
+            file.contents(),
+            "plan nt90 2026-01-01
+done 1 0
+read 43 3
+read 43 4
+last 43 4
+highlight 43 3 16 promise
+highlight 45 5 8 grace
+order tanakh
+language ko
+bookmark 43 3 16
+"
+        );
+
+        //Changing a record the other changed too, the later save wins
+        command
+            .highlights
+            .insert((BibleBook::John, 3, 16), "love".to_string());
+        command.save().unwrap();
+        assert!(command.read.contains(&(BibleBook::John, 4)));
+        assert!(file.contents().contains("highlight 43 3 16 love\n"));
+        assert!(!file.contents().contains("promise"));
+    }
+
+    #[test]
+    fn missing_file() {
+        let file = TempFile::new("missing", "");
+        fs::remove_file(&file.0).unwrap();
+
+        let mut store = Store::load_from(file.0.clone());
+        assert!(store.plan.is_none());
+        store.language = Some("de".to_string());
+        store.save().unwrap();
+        assert_eq!(file.contents(), "language de\n");
+    }
+}
@@ -0,0 +1,318 @@
+use std::{borrow::Cow, collections::HashMap, env, fmt, fs, path::PathBuf};
+
+use crate::verse::json::{self, Value};
+
+/*
+Strong's numbers in verse text. Tagged translations, like scrollmapper's KJV with
+Strong's or imports of tagged OSIS, Zefania, TheWord, USFM and USX files, keep the
+numbers right after the word they're on, morphology in parentheses:
+
+    In the beginning{H7225} God{H430} created{H1254}{(H8804)}{H853} the heaven{H8064}
+
+The Database strips the tags for display, words turns a tagged verse into its text
+and the numbers of each word.
+*/
+
+//A number of Strong's Hebrew (Old Testament) or Greek (New Testament) dictionary
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Strong {
+    Hebrew(u16),
+    Greek(u16),
+}
+
+impl Strong {
+    //"H7225", "g25" or "H07225", ignoring leading zeros
+    pub fn parse(text: &str) -> Option<Strong> {
+        let text = text.trim();
+        let digits = text.get(1..)?;
+        if digits.is_empty() || digits.len() > 5 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let number = digits.parse().ok().filter(|number| *number > 0)?;
+
+        match &text[..1] {
+            "H" | "h" => Some(Strong::Hebrew(number)),
+            "G" | "g" => Some(Strong::Greek(number)),
+            _ => None,
+        }
+    }
+
+    //The tag that follows a word in tagged text, "{H7225}"
+    pub fn tag(self) -> String {
+        format!("{{{}}}", self)
+    }
+}
+
+impl fmt::Display for Strong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strong::Hebrew(number) => write!(f, "H{}", number),
+            Strong::Greek(number) => write!(f, "G{}", number),
+        }
+    }
+}
+
+//A tagged word, by its byte range in the verse's text without tags
+pub struct Word {
+    pub start: usize,
+    pub end: usize,
+    pub numbers: Vec<Strong>,
+}
+
+//The text without its tags, as it's shown
+pub fn strip(tagged: &str) -> Cow<'_, str> {
+    if !tagged.contains('{') {
+        return Cow::Borrowed(tagged);
+    }
+    Cow::Owned(words(tagged).0)
+}
+
+//The text without its tags, and the words that had numbers
+pub fn words(tagged: &str) -> (String, Vec<Word>) {
+    let mut text = String::with_capacity(tagged.len());
+    let mut words: Vec<Word> = vec![];
+    let mut rest = tagged;
+
+    while let Some(open) = rest.find('{') {
+        let Some((length, strong)) = tag(&rest[open..]) else {
+            //A brace that isn't a tag, part of the text
+            text.push_str(&rest[..=open]);
+            rest = &rest[open + 1..];
+            continue;
+        };
+        text.push_str(&rest[..open]);
+        rest = &rest[open + length..];
+
+        //A tag between spaces leaves one of them
+        if text.ends_with(char::is_whitespace) {
+            rest = rest.trim_start();
+        }
+
+        //Morphology isn't looked up
+        let Some(strong) = strong else {
+            continue;
+        };
+
+        //The word before the tag, past any punctuation
+        let end = text.trim_end_matches(|c: char| !c.is_alphanumeric()).len();
+        let start = text[..end]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !is_word_char(*c))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        if start == end {
+            continue;
+        }
+
+        match words.last_mut() {
+            Some(word) if word.end == end => {
+                if !word.numbers.contains(&strong) {
+                    word.numbers.push(strong);
+                }
+            }
+            _ => words.push(Word {
+                start,
+                end,
+                numbers: vec![strong],
+            }),
+        }
+    }
+    text.push_str(rest);
+
+    (text, words)
+}
+
+//Length of the tag the text starts with and its number, no number for morphology
+//like {(H8804)}. None if the brace doesn't start a tag
+fn tag(text: &str) -> Option<(usize, Option<Strong>)> {
+    let end = text.find('}')?;
+    let inner = &text[1..end];
+
+    match inner
+        .strip_prefix('(')
+        .and_then(|inner| inner.strip_suffix(')'))
+    {
+        Some(morphology) => Strong::parse(morphology).map(|_| (end + 1, None)),
+        None => Strong::parse(inner).map(|strong| (end + 1, Some(strong))),
+    }
+}
+
+//Letters of a word, "LORD's" and "fore-father" are one
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '\'' | '’' | '-')
+}
+
+//What Strong's dictionary says about a word
+pub struct Entry {
+    //The word in Hebrew or Greek letters
+    pub lemma: String,
+    pub transliteration: String,
+    pub pronunciation: String,
+    //Where the word comes from, "from 7218"
+    pub derivation: String,
+    pub definition: String,
+    //How the KJV translates it
+    pub usage: String,
+}
+
+/*
+Strong's Hebrew and Greek dictionaries from Open Scriptures
+(github.com/openscriptures/strongs), strongs-hebrew-dictionary.js and
+strongs-greek-dictionary.js next to the executable. Either is enough:
+
+    var strongsHebrewDictionary = {"H1":{"lemma":"אָב","xlit":"ʼâb","pron":"awb",
+        "derivation":"a primitive word;","strongs_def":"father, ...","kjv_def":"chief, ..."},...
+*/
+pub struct Lexicon {
+    entries: HashMap<Strong, Entry>,
+}
+
+const FILES: [&str; 2] = [
+    "strongs-hebrew-dictionary.js",
+    "strongs-greek-dictionary.js",
+];
+
+impl Lexicon {
+    pub fn paths() -> Option<Vec<PathBuf>> {
+        let dir = env::current_exe().ok()?.parent()?.to_path_buf();
+        Some(FILES.iter().map(|file| dir.join(file)).collect())
+    }
+
+    pub fn load() -> Result<Self, String> {
+        let paths = Self::paths().ok_or("Could not get path to dir.")?;
+
+        let mut entries = HashMap::new();
+        let mut found = false;
+        for path in paths.iter().filter(|path| path.exists()) {
+            let contents = fs::read_to_string(path)
+                .map_err(|err| format!("Couldn't read {}: {}", path.display(), err))?;
+            read(&contents, &mut entries)
+                .map_err(|err| format!("Couldn't read {}: {}", path.display(), err))?;
+            found = true;
+        }
+
+        if !found {
+            return Err(format!(
+                "No Strong's dictionary, put {} or {} next to the executable",
+                FILES[0], FILES[1]
+            ));
+        }
+        Ok(Lexicon { entries })
+    }
+
+    pub fn get(&self, strong: Strong) -> Option<&Entry> {
+        self.entries.get(&strong)
+    }
+}
+
+//The entries of a dictionary, the .js files assign its JSON object to a variable
+fn read(contents: &str, entries: &mut HashMap<Strong, Entry>) -> Result<(), String> {
+    let start = contents
+        .find("= {")
+        .map(|i| i + 2)
+        .or_else(|| contents.find('{'))
+        .ok_or("no dictionary in it")?;
+    let end = contents
+        .rfind('}')
+        .filter(|end| *end > start)
+        .ok_or("no dictionary in it")?;
+
+    let Value::Object(members) = json::parse(&contents[start..=end])? else {
+        return Err("no dictionary in it".to_string());
+    };
+    for (key, value) in &members {
+        let Some(strong) = Strong::parse(key) else {
+            continue;
+        };
+        //The Greek dictionary calls the transliteration translit
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| value.get(name)?.as_str())
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+
+        entries.insert(
+            strong,
+            Entry {
+                lemma: field(&["lemma"]),
+                transliteration: field(&["xlit", "translit"]),
+                pronunciation: field(&["pron"]),
+                derivation: field(&["derivation"]),
+                definition: field(&["strongs_def"]),
+                usage: field(&["kjv_def"]),
+            },
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Strong::parse("H7225"), Some(Strong::Hebrew(7225)));
+        assert_eq!(Strong::parse("g25"), Some(Strong::Greek(25)));
+        assert_eq!(Strong::parse(" H07225 "), Some(Strong::Hebrew(7225)));
+        for bad in ["", "H", "H0", "X25", "H123456", "H12a", "7225"] {
+            assert_eq!(Strong::parse(bad), None, "{}", bad);
+        }
+        assert_eq!(Strong::Hebrew(430).tag(), "{H430}");
+    }
+
+    #[test]
+    fn stripped() {
+        assert_eq!(
+            strip(
+                "In the beginning{H7225} God{H430} created{H1254}{(H8804)}{H853} the earth{H776}."
+            ),
+            "In the beginning God created the earth."
+        );
+        assert_eq!(strip("a {H853} b"), "a b");
+        assert_eq!(strip("a {brace} and {H}"), "a {brace} and {H}");
+        assert!(matches!(strip("no tags"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn tagged_words() {
+        let (text, words) = words("the LORD's{H3068} anointed,{H4899}{H4899} fore-father{H1}");
+        assert_eq!(text, "the LORD's anointed, fore-father");
+
+        let found: Vec<(&str, &[Strong])> = words
+            .iter()
+            .map(|word| (&text[word.start..word.end], word.numbers.as_slice()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("LORD's", &[Strong::Hebrew(3068)][..]),
+                ("anointed", &[Strong::Hebrew(4899)][..]),
+                ("fore-father", &[Strong::Hebrew(1)][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn lexicon() {
+        let mut entries = HashMap::new();
+        read(
+            "var strongsGreekDictionary = {\"G25\":{\"lemma\":\"ἀγαπάω\",\"translit\":\"agapáō\",\
+             \"strongs_def\":\" to love \",\"kjv_def\":\"(be-)love(-ed)\"},\"x\":{}};\n",
+            &mut entries,
+        )
+        .unwrap();
+
+        let entry = &entries[&Strong::Greek(25)];
+        assert_eq!(entry.lemma, "ἀγαπάω");
+        assert_eq!(entry.transliteration, "agapáō");
+        assert_eq!(entry.definition, "to love");
+        assert_eq!(entry.pronunciation, "");
+        assert_eq!(entries.len(), 1);
+
+        assert!(read("nothing here", &mut entries).is_err());
+    }
+}
@@ -1,124 +1,1438 @@
-use std::io;
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    thread,
+    time::{Duration, Instant},
+};
 
-use crossterm::event::{self, Event, KeyEventKind};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{
     Frame,
-    layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
+    layout::{Constraint, Flex, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Clear, Paragraph, Widget, Wrap},
+    widgets::{
+        Block, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
+        Widget, Wrap,
+    },
 };
 
-use crate::verse::db::*;
+use crate::verse::{
+    date::Date,
+    db::{Arrangement, BibleBook, Canon, Database, Testament, Verse},
+    event::{AppEvent, Events},
+    keymap::{Action, Key, Keymap, Lookup, Mode},
+    locale::Locale,
+    parse::{parse_chapters, parse_reference_in},
+    plan::Plan,
+    remote::Remote,
+    store::Store,
+    strongs::{self, Lexicon, Strong, Word},
+    versification::Versification,
+    votd,
+};
 
 pub struct App {
     should_close: bool,
+    //Verse of the day shown before the reader, until a key is pressed
+    start: Option<Verse>,
     current_chapter: u8,
     current_scroll: u16,
 
     show_book_menu: bool,
     book_input_string: String,
 
+    show_chapter_menu: bool,
+    chapter_cursor: u8,
+    chapter_input_string: String,
+
+    //Appends the following chapters below the current one
+    continuous: bool,
+    //Inner area of the passage from the last draw, used to measure wrapped text
+    text_area: Cell<Rect>,
+
+    show_plan: bool,
+    //Loaded when the plan view is opened
+    plan: Option<Plan>,
+    plan_day: u16,
+    plan_cursor: usize,
+
+    show_progress: bool,
+    progress_cursor: usize,
+    //When the end of the current chapter came into view
+    end_seen: Option<(BibleBook, u8, Instant)>,
+
+    //Tagged words of each verse of the current chapter while a word is being picked,
+    //and the picked one by verse (from 0) and place among the verse's
+    words: Vec<Vec<Word>>,
+    word: Option<(usize, usize)>,
+    //Strong's dictionary, read when it's first opened
+    lexicon: Option<Result<Lexicon, String>>,
+    //The lexicon popup for the picked word
+    lookup: Option<WordLookup>,
+
+    show_help: bool,
+    help_filter: String,
+    help_scroll: u16,
+
+    keymap: Keymap,
+    //Keys typed so far of a multi-key binding
+    pending_keys: Vec<Key>,
+
+    //Transient feedback shown in the status bar
+    message: Option<Message>,
+    //When each pending timer fires
+    timers: Vec<(Instant, Timer)>,
+    //Book being read in the background, and since when
+    loading: Option<(BibleBook, Instant)>,
+    events: Events,
+
+    //Verses of the current chapter picked out by a goto, a search or by hand
+    selection: Option<(u8, u8)>,
+    //Verse a selection by hand started on and the one its end has been moved to
+    selecting: Option<(u8, u8)>,
+    //Words typed into the search prompt while it's open
+    search_input: Option<String>,
+    //Verses found by the last search and the one shown
+    matches: Vec<Verse>,
+    match_index: usize,
+    //Socket editors drive the reader through, None if it couldn't be opened
+    remote: Option<Remote>,
+
+    store: Store,
     db: Database,
+    //Canon of the translation, the books there are
+    canon: Canon,
+    //Its books in the order chosen, the menu lists and navigation goes through
+    books: Arrangement,
+    //Numbering of the translation, verses are shown and referred to in it
+    versification: Versification,
+    //Language of book names and the reader's text
+    locale: &'static Locale,
+}
+
+struct Message {
+    text: String,
+    error: bool,
+}
+
+//One of the Strong's numbers of a word and the verses with it, cursor on one of them
+struct WordLookup {
+    numbers: Vec<Strong>,
+    shown: usize,
+    occurrences: Vec<Verse>,
+    cursor: usize,
 }
 
+#[derive(Copy, Clone, PartialEq)]
+enum Timer {
+    //Writes the store after the reading position changed
+    Autosave,
+    ClearMessage,
+}
+
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(4);
+
+//How long after a change the store is saved, so a crash loses little
+const AUTOSAVE_AFTER: Duration = Duration::from_secs(30);
+
+const SPINNER: [&str; 8] = ["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"];
+
+//How long the end of a chapter has to stay on screen before it counts as read
+const AUTO_READ_AFTER: Duration = Duration::from_secs(10);
+
+//How often the main loop wakes up without input
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+//Most verses a search finds
+const SEARCH_LIMIT: u32 = 50;
+
+//Chapters per row in the chapter grid
+const CHAPTER_GRID_COLUMNS: u8 = 10;
+
 impl App {
     pub fn init() -> Self {
-        let db =
-            Database::connect_and_load(BibleBook::Genesis).expect("Couldn't connect to Database.");
+        App::new(Database::connect().expect(
+            "Couldn't connect to Database. The DB has to be in the same dir as the executable.",
+        ))
+    }
 
-        App {
+    //A reader on any source
+    pub fn new(db: Database) -> Self {
+        let start = votd::verse_of_the_day(&db, Date::today()).ok();
+        let canon = db.canon().unwrap_or(Canon::Protestant);
+        let versification = db.versification().unwrap_or_default();
+        let store = Store::load();
+        let books = store.order.arrange(canon);
+        let locale = Locale::pick(
+            store.language.as_deref(),
+            db.language().ok().flatten().as_deref(),
+        );
+        let (keymap, key_errors) = Keymap::load();
+        let events = Events::new();
+        let remote = Remote::listen(events.sender());
+
+        let mut app = App {
             should_close: false,
+            start,
             current_chapter: 1,
             db,
             current_scroll: 0,
             show_book_menu: false,
             book_input_string: String::new(),
+            show_chapter_menu: false,
+            chapter_cursor: 1,
+            chapter_input_string: String::new(),
+            continuous: false,
+            text_area: Cell::new(Rect::default()),
+            show_plan: false,
+            plan: None,
+            plan_day: 1,
+            plan_cursor: 0,
+            show_progress: false,
+            progress_cursor: 0,
+            end_seen: None,
+            words: vec![],
+            word: None,
+            lexicon: None,
+            lookup: None,
+            show_help: false,
+            help_filter: String::new(),
+            help_scroll: 0,
+            keymap,
+            pending_keys: vec![],
+            message: None,
+            timers: vec![],
+            loading: None,
+            events,
+            selection: None,
+            selecting: None,
+            search_input: None,
+            matches: vec![],
+            match_index: 0,
+            remote: None,
+            store,
+            canon,
+            books,
+            versification,
+            locale,
+        };
+
+        match remote {
+            Ok(remote) => app.remote = Some(remote),
+            Err(err) => app.error(format!("Remote control is off: {}", err)),
         }
+        //One line fits the status bar, the first bad key is where to start fixing
+        if let Some(error) = key_errors.first() {
+            match key_errors.len() {
+                1 => app.error(error.clone()),
+                n => app.error(format!("{} (and {} more)", error, n - 1)),
+            }
+        }
+        app.load_book(BibleBook::Genesis);
+        app
     }
 
     pub fn run(&mut self) {
         let mut terminal = ratatui::init();
 
+        self.events.start(TICK_RATE);
+
         //Main Loop
         while !self.should_close {
             let _ = terminal.draw(|frame| self.draw(frame));
-            let _ = self.handle_events();
+
+            let Some(event) = self.events.next() else {
+                break;
+            };
+            self.handle_event(event);
+
+            //Catch up on everything else that queued up before drawing again
+            while let Some(event) = self.events.try_next() {
+                self.handle_event(event);
+            }
         }
 
+        self.save_store();
         ratatui::restore();
     }
 
     fn update_menu(&mut self, c: char) {
-        if c == 'B' {
-            self.show_book_menu = !self.show_book_menu;
-            self.book_input_string.clear();
+        //No book needs more typed than its prefix, "prayerofm" for the Prayer of Manasseh
+        let longest = BibleBook::menu_prefixes(self.books.books(), self.locale)
+            .iter()
+            .map(String::len)
+            .max()
+            .unwrap_or_default();
+        if self.book_input_string.len() >= longest {
             return;
         }
 
-        if self.show_book_menu {
-            if self.book_input_string.len() > 5 {
-                return;
-            }
+        self.book_input_string.push(c);
+
+        if let Some(book) =
+            minimum_prefix_match(self.books.books(), self.locale, &self.book_input_string)
+        {
+            self.pick_book(book);
+        }
+    }
+
+    fn pick_book(&mut self, book: BibleBook) {
+        self.show_book_menu = false;
+
+        //Continue where the book was left off
+        let chapter = self.store.last.get(&book).copied().unwrap_or(1);
+        self.goto(book, chapter);
+
+        //Single chapter books have nothing to pick
+        if book.max_chapter_count() > 1 {
+            self.open_chapter_menu();
+        }
+    }
 
-            self.book_input_string.push(c);
+    fn open_chapter_menu(&mut self) {
+        self.show_book_menu = false;
+        self.show_chapter_menu = true;
+        self.chapter_cursor = self.current_chapter;
+        self.chapter_input_string.clear();
+    }
+
+    fn update_chapter_menu(&mut self, c: char) {
+        if !c.is_ascii_digit() {
+            return;
+        }
 
-            let search: Option<BibleBook> = minimum_prefix_match(&self.book_input_string);
+        let max = self.db.book.max_chapter_count();
+        self.chapter_input_string.push(c);
 
-            match search {
-                Some(book) => {
-                    self.current_chapter = 1;
-                    self.current_scroll = 0;
-                    self.show_book_menu = false;
-                    let _ = self.db.load_book(book);
+        match self.chapter_input_string.parse::<u16>() {
+            Ok(num) if num >= 1 && num <= max as u16 => {
+                self.chapter_cursor = num as u8;
+
+                //No further digit could make a valid chapter, so jump right away
+                if num * 10 > max as u16 {
+                    self.select_chapter(self.chapter_cursor);
                 }
-                None => {}
+            }
+            _ => {
+                self.chapter_input_string.pop();
             }
         }
     }
 
+    fn move_chapter_cursor(&mut self, action: Action) {
+        let max = self.db.book.max_chapter_count();
+
+        match action {
+            Action::CursorLeft => self.chapter_cursor = self.chapter_cursor.saturating_sub(1),
+            Action::CursorRight => self.chapter_cursor = self.chapter_cursor.saturating_add(1),
+            Action::CursorUp if self.chapter_cursor > CHAPTER_GRID_COLUMNS => {
+                self.chapter_cursor -= CHAPTER_GRID_COLUMNS
+            }
+            Action::CursorDown if self.chapter_cursor + CHAPTER_GRID_COLUMNS <= max => {
+                self.chapter_cursor += CHAPTER_GRID_COLUMNS
+            }
+            Action::CursorFirst => self.chapter_cursor = 1,
+            Action::CursorLast => self.chapter_cursor = max,
+            _ => {}
+        }
+
+        self.chapter_cursor = self.chapter_cursor.clamp(1, max);
+        self.chapter_input_string.clear();
+    }
+
+    fn select_chapter(&mut self, chapter: u8) {
+        self.goto(self.db.book, chapter);
+        self.show_chapter_menu = false;
+        self.chapter_input_string.clear();
+    }
+
     fn draw(&self, frame: &mut Frame) {
         frame.render_widget(self, frame.area());
     }
 
-    fn handle_events(&mut self) -> io::Result<()> {
-        match event::read()? {
+    fn mode(&self) -> Mode {
+        if self.show_help {
+            Mode::Help
+        } else if self.show_plan {
+            Mode::Plan
+        } else if self.show_progress {
+            Mode::Progress
+        } else if self.show_chapter_menu {
+            Mode::ChapterMenu
+        } else if self.show_book_menu {
+            Mode::BookMenu
+        } else if self.lookup.is_some() {
+            Mode::Lexicon
+        } else if self.word.is_some() {
+            Mode::Words
+        } else if self.search_input.is_some() {
+            Mode::Search
+        } else if self.selecting.is_some() {
+            Mode::Selection
+        } else {
+            Mode::Reader
+        }
+    }
+
+    fn handle_event(&mut self, event: AppEvent) {
+        match event {
+            AppEvent::Input(event) => self.handle_input(event),
+            AppEvent::Tick => self.tick(),
+            AppEvent::Remote(command) => {
+                let result = self.remote_command(&command.line);
+                command.reply(result);
+            }
+            AppEvent::BookLoaded {
+                book,
+                translation,
+                chapters,
+            } => {
+                //Another book or translation was asked for in the meantime
+                if book != self.db.book || translation != self.db.translation {
+                    return;
+                }
+
+                self.loading = None;
+                match chapters {
+                    Ok(chapters) => self.db.set_chapters(chapters),
+                    Err(err) => self.error(format!("Couldn't load {}: {}", book.as_str(), err)),
+                }
+            }
+            AppEvent::IndexLoaded { index, took } => match index {
+                Ok(index) => {
+                    self.notify(format!(
+                        "{} in memory: {} verses, {:.1} MiB, read in {}ms",
+                        index.translation,
+                        index.verse_count(),
+                        index.memory_usage().mib(),
+                        took.as_millis()
+                    ));
+                    self.db.set_index(index);
+                }
+                Err(err) => self.error(format!("Couldn't read the translation: {}", err)),
+            },
+        }
+    }
+
+    //Reads the whole translation into memory in the background, books switch instantly after
+    pub fn preload(&mut self) {
+        if self.db.index().is_some() {
+            return;
+        }
+        let events = self.events.sender();
+        let source = self.db.source();
+        let translation = self.db.translation.clone();
+        thread::spawn(move || {
+            let started = Instant::now();
+            let index = source.load_index(&translation);
+            let _ = events.send(AppEvent::IndexLoaded {
+                index,
+                took: started.elapsed(),
+            });
+        });
+    }
+
+    fn handle_input(&mut self, event: Event) {
+        match event {
+            //Enter opens the verse of the day, any other key the reader
+            Event::Key(key_event)
+                if key_event.kind == KeyEventKind::Press && self.start.is_some() =>
+            {
+                if let (KeyCode::Enter, Some(verse)) = (key_event.code, self.start.take()) {
+                    self.goto(verse.book, verse.chapter);
+                }
+                self.start = None;
+            }
+
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                match key_event.code {
-                    event::KeyCode::Left => self.prev_chapter(),
-                    event::KeyCode::Right => self.next_chapter(),
-                    event::KeyCode::Up => {
-                        self.current_scroll = self.current_scroll.saturating_sub(1)
+                let mode = self.mode();
+                self.pending_keys.push(Key::from(key_event));
+
+                match self.keymap.lookup(mode, &self.pending_keys) {
+                    Lookup::Action(action) => {
+                        self.pending_keys.clear();
+                        self.perform(mode, action);
                     }
-                    event::KeyCode::Down => {
-                        self.current_scroll = self.current_scroll.wrapping_add(1)
+                    Lookup::Pending => {}
+                    Lookup::None => {
+                        //Unbound characters are typed into whatever input the mode has
+                        let typed = self.pending_keys.len() == 1
+                            && !key_event
+                                .modifiers
+                                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+                        self.pending_keys.clear();
+
+                        if let (true, KeyCode::Char(c)) = (typed, key_event.code) {
+                            self.type_char(mode, c);
+                        }
                     }
-                    event::KeyCode::Esc => self.should_close = true,
-                    event::KeyCode::Char('q') => self.should_close = true,
-                    event::KeyCode::Char(c) => self.update_menu(c),
-                    _ => {}
                 }
             }
 
+            Event::Resize(width, height) => self.resize(width, height),
+
             _ => {}
         }
+    }
+
+    fn perform(&mut self, mode: Mode, action: Action) {
+        match (mode, action) {
+            (_, Action::Quit) => self.should_close = true,
+            (Mode::Help, Action::ScrollUp) => self.help_scroll = self.help_scroll.saturating_sub(1),
+            (Mode::Help, Action::ScrollDown) => {
+                self.help_scroll = self.help_scroll.saturating_add(1)
+            }
+            (_, Action::ScrollUp) => self.scroll_by(-1),
+            (_, Action::ScrollDown) => self.scroll_by(1),
+            (_, Action::PageUp) => self.scroll_by(-self.page_height()),
+            (_, Action::PageDown) => self.scroll_by(self.page_height()),
+            (_, Action::HalfPageUp) => self.scroll_by(-self.page_height() / 2),
+            (_, Action::HalfPageDown) => self.scroll_by(self.page_height() / 2),
+            (_, Action::Top) => self.current_scroll = 0,
+            (_, Action::Bottom) => self.current_scroll = self.max_scroll(),
+            (_, Action::PrevChapter) => self.prev_chapter(),
+            (_, Action::NextChapter) => self.next_chapter(),
+            (_, Action::PrevBook) => {
+                if let Some(book) = self.books.prev(self.db.book) {
+                    self.goto(book, 1);
+                }
+            }
+            (_, Action::NextBook) => {
+                if let Some(book) = self.books.next(self.db.book) {
+                    self.goto(book, 1);
+                }
+            }
+            (_, Action::OpenBookMenu) => {
+                self.show_book_menu = true;
+                self.book_input_string.clear();
+            }
+            (_, Action::OpenChapterMenu) => self.open_chapter_menu(),
+            (_, Action::CycleOrder) => {
+                self.store.order = self.store.order.next();
+                self.books = self.store.order.arrange(self.canon);
+                self.notify(self.locale.fill(
+                    "Books in {} order, {}",
+                    &[
+                        self.locale.text(self.store.order.as_str()),
+                        self.locale.text(self.store.order.description()),
+                    ],
+                ));
+                self.save_store();
+            }
+            (_, Action::ToggleContinuous) => {
+                self.continuous = !self.continuous;
+                self.scroll_by(0);
+                self.notify(self.locale.text(match self.continuous {
+                    true => "Continuous reading on",
+                    false => "Continuous reading off",
+                }));
+            }
+            (_, Action::OpenHelp) => {
+                self.show_help = true;
+                self.help_filter.clear();
+                self.help_scroll = 0;
+            }
+            (_, Action::OpenPlan) => self.open_plan(),
+            (_, Action::ToggleRead) => self.toggle_read(),
+            (_, Action::OpenProgress) => {
+                self.show_progress = true;
+                self.progress_cursor = self.books.position(self.db.book).unwrap_or(0);
+            }
+            (Mode::Progress, Action::CursorUp) => {
+                self.progress_cursor = self.progress_cursor.saturating_sub(1)
+            }
+            (Mode::Progress, Action::CursorDown) => {
+                self.progress_cursor = (self.progress_cursor + 1).min(self.books.books().len() - 1)
+            }
+            (Mode::Progress, Action::CursorFirst) => self.progress_cursor = 0,
+            (Mode::Progress, Action::CursorLast) => {
+                self.progress_cursor = self.books.books().len() - 1
+            }
+            (Mode::Progress, Action::Confirm) => {
+                let book = self.books.books()[self.progress_cursor];
+                self.show_progress = false;
+                self.goto(book, self.store.last.get(&book).copied().unwrap_or(1));
+            }
+            (Mode::Progress, Action::Close) => self.show_progress = false,
+            (_, Action::OpenWords) => self.open_words(),
+            (Mode::Search, Action::Search) => {
+                let words = self.search_input.take().unwrap_or_default();
+                if let Err(err) = self.search(&words) {
+                    self.error(err);
+                }
+            }
+            (_, Action::Search) => self.search_input = Some(String::new()),
+            (_, Action::NextMatch) => self.show_match(1),
+            (_, Action::PrevMatch) => self.show_match(-1),
+            (_, Action::SelectVerses) => {
+                let verse = self.verse_at_scroll() as u8;
+                self.selecting = Some((verse, verse));
+                self.selection = Some((verse, verse));
+            }
+            (Mode::Selection, Action::CursorUp) => self.move_selection(-1),
+            (Mode::Selection, Action::CursorDown) => self.move_selection(1),
+            (_, Action::KeepSelection) => self.selecting = None,
+            (Mode::Selection, Action::Close) => {
+                self.selecting = None;
+                self.selection = None;
+            }
+            (Mode::Search, Action::Close) => self.search_input = None,
+            (Mode::Words, Action::Confirm) => self.open_lexicon(),
+            (Mode::Words, Action::Close) => self.close_words(),
+            (Mode::Lexicon, Action::CursorUp) => self.move_lookup_cursor(-1),
+            (Mode::Lexicon, Action::CursorDown) => self.move_lookup_cursor(1),
+            (Mode::Lexicon, Action::CursorFirst) => self.move_lookup_cursor(i32::MIN),
+            (Mode::Lexicon, Action::CursorLast) => self.move_lookup_cursor(i32::MAX),
+            (Mode::Lexicon, Action::Confirm) => self.open_occurrence(),
+            (Mode::Lexicon, Action::Close) => self.lookup = None,
+            (_, Action::PrevNumber) => {
+                if let Some(shown) = self
+                    .lookup
+                    .as_ref()
+                    .and_then(|lookup| lookup.shown.checked_sub(1))
+                {
+                    self.show_number(shown);
+                }
+            }
+            (_, Action::NextNumber) => {
+                if let Some(shown) = self.lookup.as_ref().map(|lookup| lookup.shown + 1) {
+                    self.show_number(shown);
+                }
+            }
+            (Mode::Plan, Action::CursorUp) => self.move_plan_cursor(0, -1),
+            (Mode::Plan, Action::CursorDown) => self.move_plan_cursor(0, 1),
+            (_, Action::PrevDay) => self.move_plan_cursor(-1, 0),
+            (_, Action::NextDay) => self.move_plan_cursor(1, 0),
+            (_, Action::ToggleDone) => self.toggle_plan_passage(),
+            (Mode::Plan, Action::Confirm) => self.open_plan_passage(),
+            (Mode::Plan, Action::Close) => self.show_plan = false,
+            (Mode::Help, Action::Close) => self.show_help = false,
+            (Mode::ChapterMenu, Action::Close) => self.show_chapter_menu = false,
+            (_, Action::Close) => self.show_book_menu = false,
+            (Mode::Help, Action::Erase) => {
+                self.help_filter.pop();
+                self.help_scroll = 0;
+            }
+            (Mode::Search, Action::Erase) => {
+                if let Some(input) = &mut self.search_input {
+                    input.pop();
+                }
+            }
+            (Mode::ChapterMenu, Action::Erase) => {
+                self.chapter_input_string.pop();
+                if let Ok(num) = self.chapter_input_string.parse::<u8>() {
+                    self.chapter_cursor = num;
+                }
+            }
+            (_, Action::Erase) => {
+                self.book_input_string.pop();
+            }
+            (Mode::BookMenu, Action::Confirm) => {
+                if let Some(book) =
+                    typed_book(self.books.books(), self.locale, &self.book_input_string)
+                {
+                    self.pick_book(book);
+                }
+            }
+            (_, Action::Confirm) => self.select_chapter(self.chapter_cursor),
+            (Mode::Words, Action::CursorLeft)
+            | (Mode::Words, Action::CursorRight)
+            | (Mode::Words, Action::CursorUp)
+            | (Mode::Words, Action::CursorDown)
+            | (Mode::Words, Action::CursorFirst)
+            | (Mode::Words, Action::CursorLast) => self.move_word_cursor(action),
+            (_, Action::CursorLeft)
+            | (_, Action::CursorRight)
+            | (_, Action::CursorUp)
+            | (_, Action::CursorDown)
+            | (_, Action::CursorFirst)
+            | (_, Action::CursorLast) => self.move_chapter_cursor(action),
+        }
+    }
+
+    fn type_char(&mut self, mode: Mode, c: char) {
+        match mode {
+            Mode::Reader
+            | Mode::Plan
+            | Mode::Progress
+            | Mode::Words
+            | Mode::Lexicon
+            | Mode::Selection => {}
+            Mode::BookMenu => self.update_menu(c),
+            Mode::Search => {
+                if let Some(input) = &mut self.search_input {
+                    input.push(c);
+                }
+            }
+            Mode::ChapterMenu => self.update_chapter_menu(c),
+            Mode::Help => {
+                self.help_filter.push(c);
+                self.help_scroll = 0;
+            }
+        }
+    }
+
+    fn open_plan(&mut self) {
+        let Some(progress) = &self.store.plan else {
+            self.error(
+                self.locale
+                    .text("No reading plan, start one with verse plan start <name>"),
+            );
+            return;
+        };
+
+        match Plan::find(&progress.name) {
+            Ok(plan) => {
+                self.plan_day = progress
+                    .day_on(Date::today())
+                    .clamp(1, plan.days.len() as i32) as u16;
+                self.plan_cursor = 0;
+                self.plan = Some(plan);
+                self.show_plan = true;
+            }
+            Err(err) => self.error(err),
+        }
+    }
+
+    fn move_plan_cursor(&mut self, days: i32, passages: i32) {
+        let Some(plan) = &self.plan else {
+            return;
+        };
+
+        if days != 0 {
+            self.plan_day = (self.plan_day as i32 + days).clamp(1, plan.days.len() as i32) as u16;
+            self.plan_cursor = 0;
+        }
+
+        let count = plan.days[self.plan_day as usize - 1].len() as i32;
+        self.plan_cursor =
+            (self.plan_cursor as i32 + passages).clamp(0, (count - 1).max(0)) as usize;
+    }
+
+    fn toggle_plan_passage(&mut self) {
+        let (Some(plan), Some(progress)) = (&self.plan, &mut self.store.plan) else {
+            return;
+        };
+        if self.plan_cursor >= plan.days[self.plan_day as usize - 1].len() {
+            return;
+        }
+
+        let key = (self.plan_day, self.plan_cursor as u8);
+        if !progress.done.remove(&key) {
+            progress.done.insert(key);
+        }
+
+        self.save_store();
+    }
+
+    fn open_plan_passage(&mut self) {
+        let Some(passage) = self
+            .plan
+            .as_ref()
+            .and_then(|plan| plan.days[self.plan_day as usize - 1].get(self.plan_cursor))
+            .copied()
+        else {
+            return;
+        };
+
+        self.show_plan = false;
+        self.goto(passage.book, passage.first);
+    }
+
+    //Puts a cursor on the first word with a Strong's number, from the verse at the top
+    fn open_words(&mut self) {
+        let verses = self
+            .db
+            .tagged_chapter(self.db.book, self.current_chapter)
+            .unwrap_or_default();
+        self.words = verses.iter().map(|text| strongs::words(text).1).collect();
+
+        let top = self.verse_at_scroll() - 1;
+        let first = (top..self.words.len()).chain(0..top).find(|verse| {
+            self.words
+                .get(*verse)
+                .is_some_and(|words| !words.is_empty())
+        });
+        match first {
+            Some(verse) => {
+                self.word = Some((verse, 0));
+                self.keep_verse_in_view(verse);
+            }
+            None => {
+                self.words.clear();
+                self.error(self.locale.text("No Strong's numbers in this chapter"));
+            }
+        }
+    }
+
+    fn close_words(&mut self) {
+        self.words.clear();
+        self.word = None;
+    }
+
+    //Left and right go word by word, up and down verse by verse
+    fn move_word_cursor(&mut self, action: Action) {
+        let Some((verse, word)) = self.word else {
+            return;
+        };
+        //Verses with tagged words, the cursor skips the others
+        let tagged: Vec<usize> = (0..self.words.len())
+            .filter(|verse| !self.words[*verse].is_empty())
+            .collect();
+        let position = tagged
+            .iter()
+            .position(|tagged| *tagged == verse)
+            .unwrap_or(0);
+        let previous = position.checked_sub(1).map(|position| tagged[position]);
+        let next = tagged.get(position + 1).copied();
+        let last_word = |verse: usize| self.words[verse].len() - 1;
+
+        let moved = match action {
+            Action::CursorLeft if word > 0 => Some((verse, word - 1)),
+            Action::CursorLeft => previous.map(|verse| (verse, last_word(verse))),
+            Action::CursorRight if word < last_word(verse) => Some((verse, word + 1)),
+            Action::CursorRight | Action::CursorDown => next.map(|verse| (verse, 0)),
+            Action::CursorUp => previous.map(|verse| (verse, 0)),
+            Action::CursorFirst => tagged.first().map(|verse| (*verse, 0)),
+            Action::CursorLast => tagged.last().map(|verse| (*verse, last_word(*verse))),
+            _ => None,
+        };
+        if let Some((verse, word)) = moved {
+            self.word = Some((verse, word));
+            self.keep_verse_in_view(verse);
+        }
+    }
+
+    //Opens the lexicon on the picked word, reading the dictionary the first time
+    fn open_lexicon(&mut self) {
+        let Some(numbers) = self
+            .word
+            .and_then(|(verse, word)| self.words.get(verse)?.get(word))
+            .map(|word| word.numbers.clone())
+        else {
+            return;
+        };
+
+        if self.lexicon.is_none() {
+            self.lexicon = Some(Lexicon::load());
+        }
+        self.lookup = Some(WordLookup {
+            numbers,
+            shown: 0,
+            occurrences: vec![],
+            cursor: 0,
+        });
+        self.show_number(0);
+    }
+
+    //Shows another of the word's numbers with the verses it's in
+    fn show_number(&mut self, shown: usize) {
+        let Some(strong) = self
+            .lookup
+            .as_ref()
+            .and_then(|lookup| lookup.numbers.get(shown))
+            .copied()
+        else {
+            return;
+        };
+
+        let occurrences = self
+            .db
+            .occurrences(strong, BibleBook::Genesis, BibleBook::LAST)
+            .unwrap_or_else(|err| {
+                self.error(format!("Couldn't search for {}: {}", strong, err));
+                vec![]
+            });
+        if let Some(lookup) = &mut self.lookup {
+            lookup.shown = shown;
+            lookup.occurrences = occurrences;
+            lookup.cursor = 0;
+        }
+    }
+
+    fn move_lookup_cursor(&mut self, delta: i32) {
+        if let Some(lookup) = &mut self.lookup {
+            let last = lookup.occurrences.len().saturating_sub(1) as i32;
+            lookup.cursor = (lookup.cursor as i32).saturating_add(delta).clamp(0, last) as usize;
+        }
+    }
+
+    //Reads the verse under the lexicon's cursor
+    fn open_occurrence(&mut self) {
+        let Some((book, chapter, verse)) = self
+            .lookup
+            .as_ref()
+            .and_then(|lookup| lookup.occurrences.get(lookup.cursor))
+            .map(|verse| (verse.book, verse.chapter, verse.verse))
+        else {
+            return;
+        };
+
+        self.lookup = None;
+        self.goto(book, chapter);
+        self.selection = Some((verse, verse));
+        self.scroll_to_verse(verse);
+    }
+
+    //Searches the whole Bible for the words and shows the first verse found
+    fn search(&mut self, words: &str) -> Result<(), String> {
+        if words.trim().is_empty() {
+            return Err("Nothing to search for".to_string());
+        }
+        let verses = self
+            .db
+            .search(words, BibleBook::Genesis, BibleBook::LAST, SEARCH_LIMIT)
+            .map_err(|err| err.to_string())?;
+        if verses.is_empty() {
+            return Err(format!("No verses with {}", words));
+        }
 
+        self.matches = verses;
+        self.match_index = 0;
+        self.show_match(0);
+        self.notify(self.locale.fill(
+            "{} verses with {}",
+            &[&self.matches.len().to_string(), words.trim()],
+        ));
         Ok(())
     }
 
-    fn prev_chapter(&mut self) {
-        if self.current_chapter == 1 {
+    //Moves through the verses of the last search, round from the last to the first
+    fn show_match(&mut self, delta: i32) {
+        if self.matches.is_empty() {
             return;
         }
-        self.current_chapter -= 1;
+        let count = self.matches.len() as i32;
+        self.match_index = (self.match_index as i32 + delta).rem_euclid(count) as usize;
+
+        let verse = &self.matches[self.match_index];
+        let (book, chapter, verse) = (verse.book, verse.chapter, verse.verse);
+        self.goto(book, chapter);
+        self.selection = Some((verse, verse));
+        self.scroll_to_verse(verse);
+        self.notify(self.locale.fill(
+            "Match {} of {}",
+            &[&(self.match_index + 1).to_string(), &count.to_string()],
+        ));
+    }
+
+    //Moves the end of the selection by hand a verse, its start stays put
+    fn move_selection(&mut self, delta: i32) {
+        let Some((start, end)) = self.selecting else {
+            return;
+        };
+        let count = self.chapter_text(self.db.book, self.current_chapter).len();
+        let end = (end as i32 + delta).clamp(1, count.max(1) as i32) as u8;
+
+        self.selecting = Some((start, end));
+        self.selection = Some((start.min(end), start.max(end)));
+        self.keep_verse_in_view(end as usize - 1);
+    }
+
+    //"<q> Quit | <?> Help" style hint for the given actions, taken from the keymap
+    fn hint(&self, mode: Mode, actions: &[Action]) -> String {
+        actions
+            .iter()
+            .filter_map(|action| {
+                self.keymap
+                    .keys_for(mode, *action)
+                    .map(|keys| format!("<{}> {}", keys, self.locale.text(action.description())))
+            })
+            .collect::<Vec<String>>()
+            .join(" | ")
+    }
+
+    fn prev_chapter(&mut self) {
+        match prev_position(&self.books, self.db.book, self.current_chapter) {
+            Some((book, chapter)) => self.goto(book, chapter),
+            None => self.notify(self.locale.text("Already at the start of the Bible")),
+        }
     }
 
     fn next_chapter(&mut self) {
-        if self.current_chapter == self.db.book.max_chapter_count() {
+        match next_position(&self.books, self.db.book, self.current_chapter) {
+            Some((book, chapter)) => self.goto(book, chapter),
+            None => self.notify(self.locale.text("Already at the end of the Bible")),
+        }
+    }
+
+    //Switches to the book right away and reads all of it on another thread,
+    //until then chapters are fetched one at a time as they are shown
+    fn load_book(&mut self, book: BibleBook) {
+        self.db.switch_book(book);
+
+        //Nothing to wait for with the translation in memory
+        if self.db.index().is_some() {
+            let chapters = self.db.fetch_book(book).unwrap_or_default();
+            self.db.set_chapters(chapters);
+            self.loading = None;
+            return;
+        }
+
+        self.loading = Some((book, Instant::now()));
+
+        let events = self.events.sender();
+        let source = self.db.source();
+        let translation = self.db.translation.clone();
+        thread::spawn(move || {
+            let chapters = source.book(&translation, book);
+            let _ = events.send(AppEvent::BookLoaded {
+                book,
+                translation,
+                chapters,
+            });
+        });
+    }
+
+    fn notify(&mut self, text: impl Into<String>) {
+        self.message = Some(Message {
+            text: text.into(),
+            error: false,
+        });
+        self.schedule(Timer::ClearMessage, MESSAGE_TIMEOUT);
+    }
+
+    fn error(&mut self, text: impl Into<String>) {
+        self.message = Some(Message {
+            text: text.into(),
+            error: true,
+        });
+        self.schedule(Timer::ClearMessage, MESSAGE_TIMEOUT);
+    }
+
+    //Replaces the timer if it is already pending
+    fn schedule(&mut self, timer: Timer, after: Duration) {
+        self.timers.retain(|(_, pending)| *pending != timer);
+        self.timers.push((Instant::now() + after, timer));
+    }
+
+    fn run_timers(&mut self) {
+        let now = Instant::now();
+        let (due, pending): (Vec<(Instant, Timer)>, _) = std::mem::take(&mut self.timers)
+            .into_iter()
+            .partition(|(at, _)| *at <= now);
+        self.timers = pending;
+
+        for (_, timer) in due {
+            match timer {
+                Timer::Autosave => self.save_store(),
+                Timer::ClearMessage => self.message = None,
+            }
+        }
+    }
+
+    fn goto(&mut self, book: BibleBook, chapter: u8) {
+        if book != self.db.book {
+            self.load_book(book);
+        }
+        self.current_chapter = chapter;
+        self.current_scroll = 0;
+        self.selection = None;
+        self.selecting = None;
+        self.close_words();
+        self.store.last.insert(book, chapter);
+
+        //Saved a while later rather than on every chapter turn
+        if !self
+            .timers
+            .iter()
+            .any(|(_, timer)| *timer == Timer::Autosave)
+        {
+            self.schedule(Timer::Autosave, AUTOSAVE_AFTER);
+        }
+    }
+
+    //Scrolls the verse of the current chapter to the top, as far as the chapter allows
+    fn scroll_to_verse(&mut self, verse: u8) {
+        self.current_scroll = self.rows_before(verse as usize - 1).min(self.max_scroll());
+    }
+
+    //Scrolls as little as needed to show the verse (from 0) of the current chapter
+    fn keep_verse_in_view(&mut self, verse: usize) {
+        let (top, bottom) = (self.rows_before(verse), self.rows_before(verse + 1));
+        let height = self.text_area.get().height;
+
+        if top < self.current_scroll {
+            self.current_scroll = top;
+        } else if bottom > self.current_scroll + height {
+            //The start of a verse taller than the passage
+            self.current_scroll = bottom
+                .saturating_sub(height)
+                .min(top)
+                .min(self.max_scroll());
+        }
+    }
+
+    //Rows the current chapter takes up before the verse (from 0)
+    fn rows_before(&self, verse: usize) -> u16 {
+        let lines = chapter_lines(
+            self.locale,
+            self.versification,
+            self.db.book,
+            self.current_chapter,
+            &self.chapter_text(self.db.book, self.current_chapter),
+            self.continuous,
+        );
+        let before = verse + if self.continuous { 1 } else { 0 };
+
+        Paragraph::new(lines.into_iter().take(before).collect::<Vec<Line>>())
+            .wrap(Wrap { trim: true })
+            .line_count(self.text_area.get().width) as u16
+    }
+
+    //Runs a command from the remote control socket, see remote.rs
+    fn remote_command(&mut self, line: &str) -> Result<String, String> {
+        let (command, argument) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let argument = argument.trim();
+        self.start = None;
+
+        match command {
+            "goto" => {
+                if let Some(reference) = parse_reference_in(argument, self.versification) {
+                    self.goto(reference.book, reference.chapter);
+                    if let Some((first, last)) = reference.verses {
+                        self.selection = Some((first, last));
+                        self.scroll_to_verse(first);
+                    }
+                } else if let Some((book, first, _)) = parse_chapters(argument) {
+                    self.goto(book, first);
+                } else {
+                    return Err(format!("Bad reference {}", argument));
+                }
+                Ok(self.location())
+            }
+            "search" => {
+                self.search(argument)?;
+                Ok(self
+                    .matches
+                    .iter()
+                    .map(|verse| format!("{} {}", verse.reference(), verse.text))
+                    .collect::<Vec<String>>()
+                    .join("\n"))
+            }
+            "translation" => {
+                if argument.is_empty() {
+                    let translations = self.db.translations().map_err(|err| err.to_string())?;
+                    return Ok(translations.join("\n"));
+                }
+
+                let translation = self
+                    .db
+                    .find_translation(argument)
+                    .map_err(|err| err.to_string())?
+                    .ok_or_else(|| format!("Unknown translation {}", argument))?;
+
+                //Everything about the translation is read before any of it is shown, so a
+                //failure leaves the reader as it was
+                let source = self.db.source();
+                let canon = Canon::of(&source.books(&translation).map_err(|err| err.to_string())?);
+                let versification = source
+                    .versification(&translation)
+                    .map_err(|err| err.to_string())?;
+                let language = source.language(&translation).ok().flatten();
+
+                self.db.translation = translation;
+                self.canon = canon;
+                self.books = self.store.order.arrange(canon);
+                self.versification = versification;
+                self.locale = Locale::pick(self.store.language.as_deref(), language.as_deref());
+                self.close_words();
+                self.lookup = None;
+                self.load_book(self.db.book);
+                self.notify(self.locale.fill("Switched to {}", &[&self.db.translation]));
+                Ok(String::new())
+            }
+            "location" => Ok(self.location()),
+            "selection" => {
+                let (first, last) = self.selection.unwrap_or_else(|| {
+                    let verse = self.verse_at_scroll() as u8;
+                    (verse, verse)
+                });
+                let verses = self.chapter_text(self.db.book, self.current_chapter);
+                //Numbered as the translation numbers them, like goto takes them
+                let native = |verse| {
+                    self.versification
+                        .from_english(self.db.book, self.current_chapter, verse)
+                };
+                let (_, _, last_verse) = native(last);
+
+                let mut lines = vec![format!(
+                    "{}{} ({})",
+                    self.versification
+                        .reference(self.db.book, self.current_chapter, first),
+                    if last > first {
+                        format!("-{}", last_verse)
+                    } else {
+                        String::new()
+                    },
+                    self.db.translation
+                )];
+                lines.extend(
+                    (first..=last)
+                        .filter_map(|verse| Some((verse, verses.get(verse as usize - 1)?)))
+                        .map(|(verse, text)| format!("{} {}", native(verse).2, text)),
+                );
+                Ok(lines.join("\n"))
+            }
+            _ => Err(format!("Unknown command {}", command)),
+        }
+    }
+
+    //"John 3:16 ASV", the verse at the top of the reader
+    fn location(&self) -> String {
+        format!(
+            "{} {}",
+            self.versification.reference(
+                self.db.book,
+                self.current_chapter,
+                self.verse_at_scroll() as u8
+            ),
+            self.db.translation
+        )
+    }
+
+    //Runs at the tick rate, input or not
+    fn tick(&mut self) {
+        self.run_timers();
+
+        let position = (self.db.book, self.current_chapter);
+
+        //Reaching the end of the chapter and staying there marks it as read, the text
+        //doesn't count while the start screen or a popup covers it
+        let end_visible = self.chapter_height(position.0, position.1)
+            <= self.current_scroll + self.text_area.get().height;
+        let reading = self.start.is_none() && self.mode() == Mode::Reader;
+        if !reading || !end_visible || self.store.read.contains(&position) {
+            self.end_seen = None;
             return;
         }
-        self.current_chapter += 1;
+
+        match self.end_seen {
+            Some((book, chapter, since)) if (book, chapter) == position => {
+                if since.elapsed() >= AUTO_READ_AFTER {
+                    self.end_seen = None;
+                    self.toggle_read();
+                }
+            }
+            _ => self.end_seen = Some((position.0, position.1, Instant::now())),
+        }
+    }
+
+    fn toggle_read(&mut self) {
+        let position = (self.db.book, self.current_chapter);
+        let reference = format!("{} {}", self.locale.book(position.0), position.1);
+
+        if self.store.read.remove(&position) {
+            self.notify(self.locale.fill("{} marked as unread", &[&reference]));
+        } else {
+            self.store.read.insert(position);
+            self.notify(self.locale.fill("{} marked as read", &[&reference]));
+        }
+        self.save_store();
+    }
+
+    fn save_store(&mut self) {
+        if let Err(err) = self.store.save() {
+            self.error(format!("Couldn't save user data: {}", err));
+        }
+    }
+
+    fn scroll_by(&mut self, delta: i32) {
+        let mut scroll = self.current_scroll as i32 + delta;
+
+        if self.continuous {
+            //Scroll back into the end of the previous chapter
+            while scroll < 0 {
+                let Some((book, chapter)) =
+                    prev_position(&self.books, self.db.book, self.current_chapter)
+                else {
+                    break;
+                };
+                self.goto(book, chapter);
+                scroll += self.chapter_height(book, chapter) as i32;
+            }
+
+            //Once the current chapter is scrolled past, the next one becomes current
+            loop {
+                let height = self.chapter_height(self.db.book, self.current_chapter) as i32;
+                let next = next_position(&self.books, self.db.book, self.current_chapter);
+                match next {
+                    Some((book, chapter)) if scroll >= height => {
+                        self.goto(book, chapter);
+                        scroll -= height;
+                    }
+                    _ => break,
+                }
+            }
+        }
+
+        self.current_scroll = scroll.clamp(0, self.max_scroll() as i32) as u16;
+    }
+
+    //Furthest the current chapter can be scrolled without running into blank space
+    fn max_scroll(&self) -> u16 {
+        let height = self.chapter_height(self.db.book, self.current_chapter);
+
+        //In continuous mode the following chapter fills the space below
+        if self.continuous
+            && next_position(&self.books, self.db.book, self.current_chapter).is_some()
+        {
+            return height.saturating_sub(1);
+        }
+        height.saturating_sub(self.text_area.get().height)
+    }
+
+    //Rows to move on PageUp/PageDown, keeping one row of context
+    fn page_height(&self) -> i32 {
+        (self.text_area.get().height as i32 - 1).max(1)
+    }
+
+    //Keeps the same relative position in the chapter when the text rewraps
+    fn resize(&mut self, width: u16, height: u16) {
+        let old_max = self.max_scroll();
+        let ratio = match old_max {
+            0 => 0.0,
+            max => self.current_scroll as f32 / max as f32,
+        };
+
+        let [passage_area, _] = split_status_bar(Rect::new(0, 0, width, height));
+        self.text_area.set(title_block("", 0).inner(passage_area));
+        self.current_scroll = (ratio * self.max_scroll() as f32).round() as u16;
+    }
+
+    fn chapter_text(&self, book: BibleBook, chapter: u8) -> Cow<'_, [String]> {
+        if book == self.db.book
+            && let Some(verses) = self.db.get_chapter(chapter)
+        {
+            return Cow::Borrowed(verses);
+        }
+
+        Cow::Owned(self.db.fetch_chapter(book, chapter).unwrap_or_default())
+    }
+
+    //Number of rows the chapter takes up once wrapped to the passage width
+    fn chapter_height(&self, book: BibleBook, chapter: u8) -> u16 {
+        let lines = chapter_lines(
+            self.locale,
+            self.versification,
+            book,
+            chapter,
+            &self.chapter_text(book, chapter),
+            self.continuous,
+        );
+
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .line_count(self.text_area.get().width) as u16
+    }
+}
+
+fn next_position(books: &Arrangement, book: BibleBook, chapter: u8) -> Option<(BibleBook, u8)> {
+    if chapter < book.max_chapter_count() {
+        return Some((book, chapter + 1));
+    }
+    books.next(book).map(|book| (book, 1))
+}
+
+fn prev_position(books: &Arrangement, book: BibleBook, chapter: u8) -> Option<(BibleBook, u8)> {
+    if chapter > 1 {
+        return Some((book, chapter - 1));
     }
+    books
+        .prev(book)
+        .map(|book| (book, book.max_chapter_count()))
+}
+
+//Lines of a chapter, headed by its reference when chapters flow into each other. Verses
+//are numbered as the translation numbers them, with the chapter if it's another one
+fn chapter_lines<'a>(
+    locale: &Locale,
+    versification: Versification,
+    book: BibleBook,
+    chapter: u8,
+    verses: &[String],
+    heading: bool,
+) -> Vec<Line<'a>> {
+    let mut lines = vec![];
+
+    if heading {
+        lines.push(
+            Line::from(format!("{} {}", locale.book(book), chapter))
+                .centered()
+                .style(Style::default().fg(Color::LightBlue)),
+        );
+    }
+
+    let (_, first_chapter, _) = versification.from_english(book, chapter, 1);
+    lines.extend(verses.iter().enumerate().map(|(i, s)| {
+        let number = match versification.from_english(book, chapter, i as u8 + 1) {
+            (_, chapter, verse) if chapter == first_chapter => verse.to_string(),
+            (_, chapter, verse) => format!("{}:{}", chapter, verse),
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("{:>3} ", number),
+                Style::default().fg(Color::Yellow),
+            ), // Colored verse number
+            Span::raw(s.clone()),
+        ])
+    }));
+
+    lines
+}
+
+//Verse of the day, centered on an otherwise empty screen
+fn render_start(
+    verse: &Verse,
+    translation: &str,
+    locale: &Locale,
+    versification: Versification,
+    area: Rect,
+    buf: &mut ratatui::prelude::Buffer,
+) {
+    let width = area.width.min(70);
+    let (_, chapter, number) = versification.from_english(verse.book, verse.chapter, verse.verse);
+    let text = Paragraph::new(vec![
+        Line::styled(
+            locale.text("Verse of the day"),
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        )
+        .centered(),
+        Line::default(),
+        Line::from(verse.text.as_str()).centered(),
+        Line::default(),
+        Line::styled(
+            format!(
+                "{} {}:{} ({})",
+                locale.book(verse.book),
+                chapter,
+                number,
+                translation
+            ),
+            Style::default().fg(Color::Yellow),
+        )
+        .centered(),
+        Line::default(),
+        Line::styled(
+            locale.text("<Enter> read the chapter | any key to continue"),
+            Style::default().fg(Color::DarkGray),
+        )
+        .centered(),
+    ])
+    .wrap(Wrap { trim: true });
+
+    let height = (text.line_count(width) as u16).min(area.height);
+    let [text_area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [text_area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(text_area);
+
+    text.render(text_area, buf);
 }
 
 //Rendering
@@ -127,28 +1441,146 @@ impl Widget for &App {
     where
         Self: Sized,
     {
+        let [passage_area, status_area] = split_status_bar(area);
+
         //Render Verses
-        let verse_raw_data = self
-            .db
-            .get_chapter(self.current_chapter)
-            .expect("Invalid data given to get_chapter, check render");
+        let mut block = title_block(self.locale.book(self.db.book), self.current_chapter);
+        self.text_area.set(block.inner(passage_area));
 
-        let text: Vec<Line> = verse_raw_data
-            .iter()
-            .enumerate()
-            .map(|(i, s)| {
-                Line::from(vec![
-                    Span::styled(format!("{:>3} ", i + 1), Style::default().fg(Color::Yellow)), // Colored verse number
-                    Span::raw(s),
-                ])
-            })
-            .collect();
+        //The chapter is another one in the translation's numbering, like Psalm 22 in the Vulgate
+        let (_, chapter, _) =
+            self.versification
+                .from_english(self.db.book, self.current_chapter, 1);
+        if chapter != self.current_chapter {
+            block = block.title(
+                Line::from(format!(
+                    "({} {})",
+                    self.locale.text(self.versification.as_str()),
+                    chapter
+                ))
+                .right_aligned()
+                .style(Style::default().fg(Color::DarkGray)),
+            );
+        }
+
+        if let Some(verse) = &self.start {
+            render_start(
+                verse,
+                &self.db.translation,
+                self.locale,
+                self.versification,
+                area,
+                buf,
+            );
+            return;
+        }
+
+        //Position in the chapter, only when it doesn't fit
+        let max_scroll = self.max_scroll();
+        if max_scroll > 0 {
+            let percent = self.current_scroll as u32 * 100 / max_scroll as u32;
+            block = block.title(
+                Line::from(format!("{}%", percent))
+                    .left_aligned()
+                    .style(Style::default().fg(Color::LightBlue)),
+            );
+        }
+
+        let mut text: Vec<Line> = chapter_lines(
+            self.locale,
+            self.versification,
+            self.db.book,
+            self.current_chapter,
+            &self.chapter_text(self.db.book, self.current_chapter),
+            self.continuous,
+        );
+
+        if let Some((first, last)) = self.selection {
+            let skip = if self.continuous { 1 } else { 0 };
+            for line in text
+                .iter_mut()
+                .skip(first as usize - 1 + skip)
+                .take((last - first) as usize + 1)
+            {
+                line.style = Style::default().bg(Color::DarkGray);
+            }
+        }
+
+        //The picked word, the text is the line's last span
+        if let Some((verse, word)) = self.word
+            && let Some(word) = self.words.get(verse).and_then(|words| words.get(word))
+            && let Some(line) = text.get_mut(verse + if self.continuous { 1 } else { 0 })
+            && let Some(span) = line.spans.pop()
+        {
+            let content = span.content.to_string();
+            match (
+                content.get(..word.start),
+                content.get(word.start..word.end),
+                content.get(word.end..),
+            ) {
+                (Some(before), Some(picked), Some(after)) => line.spans.extend([
+                    Span::raw(before.to_string()),
+                    Span::styled(
+                        picked.to_string(),
+                        Style::default().fg(Color::Black).bg(Color::Yellow),
+                    ),
+                    Span::raw(after.to_string()),
+                ]),
+                _ => line.spans.push(span),
+            }
+        }
+
+        //Keep appending chapters until the screen is filled
+        if self.continuous {
+            let needed = self.current_scroll + self.text_area.get().height;
+            let mut height = self.chapter_height(self.db.book, self.current_chapter);
+            let mut position = next_position(&self.books, self.db.book, self.current_chapter);
+
+            while let Some((book, chapter)) = position {
+                if height >= needed {
+                    break;
+                }
+                text.extend(chapter_lines(
+                    self.locale,
+                    self.versification,
+                    book,
+                    chapter,
+                    &self.chapter_text(book, chapter),
+                    true,
+                ));
+                height += self.chapter_height(book, chapter);
+                position = next_position(&self.books, book, chapter);
+            }
+        }
 
         Paragraph::new(text)
-            .block(title_block(self.db.book.as_str(), self.current_chapter))
+            .block(block)
             .wrap(Wrap { trim: true })
             .scroll((self.current_scroll, 0))
-            .render(area, buf);
+            .render(passage_area, buf);
+
+        //Scrollbar on the right border
+        if max_scroll > 0 {
+            let mut state = ScrollbarState::new(max_scroll as usize)
+                .position(self.current_scroll as usize)
+                .viewport_content_length(self.text_area.get().height as usize);
+
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .style(Style::default().fg(Color::Magenta))
+                .thumb_style(Style::default().fg(Color::LightBlue))
+                .render(
+                    passage_area.inner(Margin {
+                        vertical: 1,
+                        horizontal: 0,
+                    }),
+                    buf,
+                    &mut state,
+                );
+        }
+
+        self.render_status_bar(status_area, buf);
 
         //Book Menu
         if self.show_book_menu {
@@ -165,11 +1597,17 @@ impl Widget for &App {
                     .areas(menu_area);
 
             //Highlited Book List
-            Paragraph::new(display_list())
+            Paragraph::new(display_list(self.books.books(), self.locale))
                 .block(
                     Block::bordered()
-                        .title(Line::from("Books").centered())
-                        .title_bottom(Line::from("Press B twice to reset buffer").centered())
+                        .title(Line::from(self.locale.text("Books")).centered())
+                        .title_bottom(
+                            Line::from(self.hint(
+                                Mode::BookMenu,
+                                &[Action::Erase, Action::Confirm, Action::Close],
+                            ))
+                            .centered(),
+                        )
                         .style(Style::default().bg(Color::LightBlue).fg(Color::Black)),
                 )
                 .wrap(Wrap { trim: true })
@@ -180,10 +1618,678 @@ impl Widget for &App {
                 .block(Block::new().style(Style::default().fg(Color::Black).bg(Color::Cyan)))
                 .render(input_area, buf);
         }
+
+        //Chapter Menu
+        if self.show_chapter_menu {
+            self.render_chapter_menu(area, buf);
+        }
+
+        if self.show_plan {
+            self.render_plan(area, buf);
+        }
+
+        if self.show_progress {
+            self.render_progress(area, buf);
+        }
+
+        if self.lookup.is_some() {
+            self.render_lexicon(area, buf);
+        }
+
+        if self.show_help {
+            self.render_help(area, buf);
+        }
+
+        //Which-key popup for a half typed binding
+        if !self.pending_keys.is_empty() {
+            self.render_pending_keys(area, buf);
+        }
+    }
+}
+
+impl App {
+    //Mode, translation, reference and position, with any message in between
+    fn render_status_bar(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let mode = self.mode();
+        let mode_color = match mode {
+            Mode::Reader | Mode::Words | Mode::Selection => Color::LightBlue,
+            Mode::BookMenu
+            | Mode::ChapterMenu
+            | Mode::Plan
+            | Mode::Progress
+            | Mode::Lexicon
+            | Mode::Search => Color::Cyan,
+            Mode::Help => Color::Yellow,
+        };
+
+        //The verse at the top as the translation numbers it, like the passage
+        let (_, chapter, verse) = self.versification.from_english(
+            self.db.book,
+            self.current_chapter,
+            self.verse_at_scroll() as u8,
+        );
+        let left = Line::from(vec![
+            Span::styled(
+                format!(" {} ", self.locale.text(mode.indicator())),
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(mode_color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" {} ", self.db.translation),
+                Style::default().fg(Color::Black).bg(Color::Magenta),
+            ),
+            Span::raw(format!(
+                " {} {}:{} ",
+                self.locale.book(self.db.book),
+                chapter,
+                verse
+            )),
+        ]);
+
+        let book = self.db.book;
+        let book_percent = self.current_chapter as u32 * 100 / book.max_chapter_count() as u32;
+        let bible_percent = (self.books.chapter_offset(book) + self.current_chapter as u16) as u32
+            * 100
+            / self.books.total_chapters() as u32;
+        let right = Line::from(vec![
+            Span::raw(format!(
+                " {}/{} | {} {}% | {} {}% ",
+                self.current_chapter,
+                book.max_chapter_count(),
+                self.locale.book(book),
+                book_percent,
+                self.locale.text("Bible"),
+                bible_percent
+            )),
+            Span::styled(
+                format!(
+                    " <{}> {} ",
+                    self.keymap
+                        .keys_for(Mode::Reader, Action::OpenHelp)
+                        .unwrap_or_default(),
+                    self.locale.text("Help")
+                ),
+                Style::default().fg(Color::Black).bg(Color::LightBlue),
+            ),
+        ])
+        .right_aligned();
+
+        let [left_area, message_area, right_area] = Layout::horizontal([
+            Constraint::Length(left.width() as u16),
+            Constraint::Min(0),
+            Constraint::Length(right.width() as u16),
+        ])
+        .areas(area);
+
+        left.render(left_area, buf);
+        right.render(right_area, buf);
+
+        if let Some(input) = &self.search_input {
+            Line::from(format!(" {}: {}", self.locale.text("Search"), input))
+                .render(message_area, buf);
+        } else if let Some((book, since)) = self.loading {
+            let frame = (since.elapsed().as_millis() / TICK_RATE.as_millis()) as usize;
+            Line::styled(
+                format!(
+                    " {} {}",
+                    SPINNER[frame % SPINNER.len()],
+                    self.locale.fill("Loading {}", &[self.locale.book(book)])
+                ),
+                Style::default().fg(Color::Yellow),
+            )
+            .render(message_area, buf);
+        } else if let Some(message) = &self.message {
+            let color = if message.error {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            Line::styled(format!(" {}", message.text), Style::default().fg(color))
+                .render(message_area, buf);
+        }
+    }
+
+    //Verse at the top of the passage, what the status bar refers to
+    fn verse_at_scroll(&self) -> usize {
+        let verses = self.chapter_text(self.db.book, self.current_chapter);
+        let lines = chapter_lines(
+            self.locale,
+            self.versification,
+            self.db.book,
+            self.current_chapter,
+            &verses,
+            self.continuous,
+        );
+        let width = self.text_area.get().width;
+
+        //The inline heading isn't a verse
+        let skip = if self.continuous { 1 } else { 0 };
+        let mut row = 0;
+        for (i, line) in lines.into_iter().enumerate() {
+            row += Paragraph::new(line)
+                .wrap(Wrap { trim: true })
+                .line_count(width) as u16;
+            if row > self.current_scroll && i >= skip {
+                return i + 1 - skip;
+            }
+        }
+
+        verses.len().max(1)
+    }
+
+    //Chapters read per book and in total
+    fn render_progress(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        const BAR_WIDTH: usize = 20;
+
+        let bar = |read: usize, total: usize| {
+            let filled = read * BAR_WIDTH / total;
+            format!("{}{}", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled))
+        };
+
+        //Wide enough for "Additions to Esther", padded by width for names in wide letters
+        let summary = |name: &str, read: usize, total: usize| {
+            let padding = 20usize.saturating_sub(Span::raw(name).width());
+            format!(
+                " {}{}{} {:>4}/{:<4} {:>3}%",
+                name,
+                " ".repeat(padding),
+                bar(read, total),
+                read,
+                total,
+                read * 100 / total
+            )
+        };
+        let books = self.books.books();
+        let mut header = vec![
+            Line::from(summary(
+                self.locale.text("Bible"),
+                books.iter().map(|book| self.store.read_in(*book)).sum(),
+                self.books.total_chapters() as usize,
+            ))
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        ];
+        for testament in [Testament::Old, Testament::New] {
+            let books = || books.iter().filter(|book| book.testament() == testament);
+            header.push(Line::from(summary(
+                self.locale.text(testament.as_str()),
+                books().map(|book| self.store.read_in(*book)).sum(),
+                books().map(|book| book.max_chapter_count() as usize).sum(),
+            )));
+        }
+
+        let lines: Vec<Line> = books
+            .iter()
+            .enumerate()
+            .map(|(i, book)| {
+                let read = self.store.read_in(*book);
+                let total = book.max_chapter_count() as usize;
+                let style = if i == self.progress_cursor {
+                    Style::default().fg(Color::Black).bg(Color::Yellow)
+                } else if read == total {
+                    Style::default().fg(Color::Black).bg(Color::Green)
+                } else {
+                    Style::default()
+                };
+
+                Line::styled(summary(self.locale.book(*book), read, total), style)
+            })
+            .collect();
+
+        let hint = self.hint(Mode::Progress, &[Action::Confirm, Action::Close]);
+        let width = (header[0].width() as u16 + 2)
+            .max(Span::raw(&hint).width() as u16 + 4)
+            .min(area.width);
+        let height = (area.height * 3 / 4).max(6).min(area.height);
+        let progress_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        Clear.render(progress_area, buf);
+
+        let block = Block::bordered()
+            .title(Line::from(self.locale.text("Reading progress")).centered())
+            .title_bottom(Line::from(hint).centered())
+            .style(Style::default().bg(Color::LightBlue).fg(Color::Black));
+        let inner = block.inner(progress_area);
+        block.render(progress_area, buf);
+
+        let [header_area, list_area] =
+            Layout::vertical([Constraint::Length(4), Constraint::Min(0)]).areas(inner);
+        Paragraph::new(header).render(header_area, buf);
+
+        //Keep the cursor in view
+        let visible = list_area.height as usize;
+        let first = self
+            .progress_cursor
+            .saturating_sub(visible.saturating_sub(1));
+        Paragraph::new(lines)
+            .scroll((first as u16, 0))
+            .render(list_area, buf);
+    }
+
+    //Dictionary entry of one of the picked word's numbers, and the verses with it
+    fn render_lexicon(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let Some(lookup) = &self.lookup else {
+            return;
+        };
+        let strong = lookup.numbers[lookup.shown];
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+
+        let mut header = vec![];
+        //The word's numbers when it has more, the one shown highlighted
+        if lookup.numbers.len() > 1 {
+            header.push(Line::from(
+                lookup
+                    .numbers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, number)| {
+                        let style = if i == lookup.shown {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else {
+                            Style::default()
+                        };
+                        Span::styled(format!(" {} ", number), style)
+                    })
+                    .collect::<Vec<Span>>(),
+            ));
+            header.push(Line::default());
+        }
+
+        match &self.lexicon {
+            Some(Ok(lexicon)) => match lexicon.get(strong) {
+                Some(entry) => {
+                    let mut title = vec![
+                        Span::styled(format!("{}  ", strong), bold),
+                        Span::styled(format!("{}  ", entry.lemma), bold),
+                        Span::raw(entry.transliteration.clone()),
+                    ];
+                    if !entry.pronunciation.is_empty() {
+                        title.push(Span::raw(format!(" ({})", entry.pronunciation)));
+                    }
+                    header.push(Line::from(title));
+                    header.extend(
+                        [&entry.derivation, &entry.definition]
+                            .into_iter()
+                            .filter(|text| !text.is_empty())
+                            .map(|text| Line::from(text.clone())),
+                    );
+                    if !entry.usage.is_empty() {
+                        header.push(Line::from(vec![
+                            Span::styled("KJV: ", bold),
+                            Span::raw(entry.usage.clone()),
+                        ]));
+                    }
+                }
+                None => header.push(Line::from(
+                    self.locale
+                        .fill("{} isn't in the dictionary", &[&strong.to_string()]),
+                )),
+            },
+            Some(Err(err)) => {
+                header.push(Line::styled(strong.to_string(), bold));
+                header.push(Line::from(err.clone()));
+            }
+            None => {}
+        }
+        header.push(Line::default());
+        header.push(Line::styled(
+            self.locale
+                .fill("{} verses", &[&lookup.occurrences.len().to_string()]),
+            bold,
+        ));
+
+        let lines: Vec<Line> = lookup
+            .occurrences
+            .iter()
+            .enumerate()
+            .map(|(i, verse)| {
+                let style = if i == lookup.cursor {
+                    Style::default().fg(Color::Black).bg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                //Numbered as the translation numbers it
+                let (book, chapter, number) =
+                    self.versification
+                        .from_english(verse.book, verse.chapter, verse.verse);
+                Line::styled(
+                    format!(
+                        " {} {}:{}  {}",
+                        self.locale.book(book),
+                        chapter,
+                        number,
+                        verse.text
+                    ),
+                    style,
+                )
+            })
+            .collect();
+
+        let mut actions = vec![Action::Confirm, Action::Close];
+        if lookup.numbers.len() > 1 {
+            actions.extend([Action::PrevNumber, Action::NextNumber]);
+        }
+        let hint = self.hint(Mode::Lexicon, &actions);
+        let lexicon_area = area.inner(Margin {
+            horizontal: area.width / 8,
+            vertical: area.height / 8,
+        });
+        Clear.render(lexicon_area, buf);
+
+        let block = Block::bordered()
+            .title(Line::from(self.locale.text("Lexicon")).centered())
+            .title_bottom(Line::from(hint).centered())
+            .style(Style::default().bg(Color::LightBlue).fg(Color::Black));
+        let inner = block.inner(lexicon_area);
+        block.render(lexicon_area, buf);
+
+        //The entry as far as it fits, leaving a few rows for the verses
+        let header = Paragraph::new(header).wrap(Wrap { trim: true });
+        let height = (header.line_count(inner.width) as u16).min(inner.height.saturating_sub(3));
+        let [header_area, list_area] =
+            Layout::vertical([Constraint::Length(height), Constraint::Min(0)]).areas(inner);
+        header.render(header_area, buf);
+
+        //Keep the cursor in view
+        let visible = list_area.height as usize;
+        let first = lookup.cursor.saturating_sub(visible.saturating_sub(1));
+        Paragraph::new(lines)
+            .scroll((first as u16, 0))
+            .render(list_area, buf);
+    }
+
+    //Passages of the selected plan day with check boxes
+    fn render_plan(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let (Some(plan), Some(progress)) = (&self.plan, &self.store.plan) else {
+            return;
+        };
+        let passages = &plan.days[self.plan_day as usize - 1];
+        let date = progress.start.add_days(self.plan_day as i32 - 1);
+
+        let mut lines = vec![
+            Line::from(format!(
+                "{} | {}",
+                self.locale.fill(
+                    "Day {} of {}",
+                    &[&self.plan_day.to_string(), &plan.days.len().to_string()]
+                ),
+                date
+            )),
+            Line::default(),
+        ];
+
+        lines.extend(passages.iter().enumerate().map(|(i, passage)| {
+            let done = progress.done.contains(&(self.plan_day, i as u8));
+            let style = if i == self.plan_cursor {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Line::styled(
+                format!(
+                    " [{}] {} {}",
+                    if done { "x" } else { " " },
+                    self.locale.book(passage.book),
+                    passage.chapters()
+                ),
+                style,
+            )
+        }));
+
+        //Behind is measured against today, not the day being looked at
+        lines.push(Line::default());
+        let behind = plan.behind(progress, progress.day_on(Date::today()));
+        lines.push(match behind.first() {
+            Some(first) => Line::styled(
+                self.locale.fill(
+                    "{} day(s) behind, catch up from day {}",
+                    &[&behind.len().to_string(), &first.to_string()],
+                ),
+                Style::default().fg(Color::Red),
+            ),
+            None => Line::from(self.locale.text("On track")),
+        });
+
+        let hint = self.hint(
+            Mode::Plan,
+            &[
+                Action::ToggleDone,
+                Action::Confirm,
+                Action::PrevDay,
+                Action::NextDay,
+            ],
+        );
+        let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4)
+            .max(Span::raw(&hint).width() as u16 + 4)
+            .min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let plan_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        Clear.render(plan_area, buf);
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(Line::from(self.locale.text(&plan.title)).centered())
+                    .title_bottom(Line::from(hint).centered())
+                    .style(Style::default().bg(Color::LightBlue).fg(Color::Black)),
+            )
+            .render(plan_area, buf);
+    }
+
+    //Help generated from the keymap, one group per mode, narrowed down by the filter
+    fn render_help(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let help_area = area.inner(Margin {
+            horizontal: area.width / 6,
+            vertical: area.height / 8,
+        });
+        Clear.render(help_area, buf);
+
+        let filter = self.help_filter.to_lowercase();
+        let mut lines: Vec<Line> = vec![];
+
+        for mode in Mode::ALL {
+            //Keys bound to the same action share a line
+            let mut entries: Vec<(String, Action)> = vec![];
+            for binding in self.keymap.bindings(mode) {
+                match entries
+                    .iter_mut()
+                    .find(|(_, action)| *action == binding.action)
+                {
+                    Some((keys, _)) => *keys = format!("{}, {}", keys, binding.keys_to_string()),
+                    None => entries.push((binding.keys_to_string(), binding.action)),
+                }
+            }
+
+            //Descriptions are searched in English too
+            entries.retain(|(keys, action)| {
+                let matches = |text: &str| {
+                    text.to_lowercase().contains(&filter)
+                        || self.locale.text(text).to_lowercase().contains(&filter)
+                };
+                keys.to_lowercase().contains(&filter)
+                    || matches(action.description())
+                    || matches(mode.as_str())
+            });
+            if entries.is_empty() {
+                continue;
+            }
+
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::styled(
+                self.locale.text(mode.as_str()),
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+            lines.extend(entries.into_iter().map(|(keys, action)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("  {:<20}", keys),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(self.locale.text(action.description())),
+                ])
+            }));
+        }
+
+        let [display_area, input_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(help_area);
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(Line::from(self.locale.text("Help")).centered())
+                    .title_bottom(
+                        Line::from(self.hint(
+                            Mode::Help,
+                            &[Action::ScrollUp, Action::ScrollDown, Action::Close],
+                        ))
+                        .centered(),
+                    )
+                    .style(Style::default().bg(Color::LightBlue).fg(Color::Black)),
+            )
+            .scroll((self.help_scroll, 0))
+            .render(display_area, buf);
+
+        //Input
+        Paragraph::new(format!(
+            "{}: {}",
+            self.locale.text("Search"),
+            self.help_filter
+        ))
+        .block(Block::new().style(Style::default().fg(Color::Black).bg(Color::Cyan)))
+        .render(input_area, buf);
+    }
+
+    fn render_pending_keys(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let lines: Vec<Line> = self
+            .keymap
+            .continuations(self.mode(), &self.pending_keys)
+            .map(|binding| {
+                let rest: Vec<String> = binding.keys[self.pending_keys.len()..]
+                    .iter()
+                    .map(|key| key.to_string())
+                    .collect();
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<6}", rest.join(" ")),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(self.locale.text(binding.action.description())),
+                ])
+            })
+            .collect();
+
+        let pending: Vec<String> = self
+            .pending_keys
+            .iter()
+            .map(|key| key.to_string())
+            .collect();
+        let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 2)
+            .max(pending.join(" ").len() as u16 + 4)
+            .min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup_area = Rect {
+            x: area.x + area.width - width,
+            y: area.y + area.height - height,
+            width,
+            height,
+        };
+        Clear.render(popup_area, buf);
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(Line::from(pending.join(" ")))
+                    .style(Style::default().bg(Color::LightBlue).fg(Color::Black)),
+            )
+            .render(popup_area, buf);
+    }
+
+    fn render_chapter_menu(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let max = self.db.book.max_chapter_count();
+        let rows = max.div_ceil(CHAPTER_GRID_COLUMNS) as u16;
+
+        //Each cell is 5 wide ("  150"), plus borders
+        let width = (CHAPTER_GRID_COLUMNS as u16 * 5 + 3).min(area.width);
+        let height = (rows + 3).min(area.height);
+        let menu_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        Clear.render(menu_area, buf);
+
+        let [display_area, input_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(menu_area);
+
+        //Keep the row holding the cursor visible when the grid doesn't fit
+        let visible_rows = display_area.height.saturating_sub(2);
+        let cursor_row = (self.chapter_cursor - 1) as u16 / CHAPTER_GRID_COLUMNS as u16;
+        let first_row = cursor_row.saturating_sub(visible_rows.saturating_sub(1));
+
+        let grid: Vec<Line> = (0..rows)
+            .map(|row| {
+                let spans: Vec<Span> = (1..=CHAPTER_GRID_COLUMNS)
+                    .map(|col| row as u8 * CHAPTER_GRID_COLUMNS + col)
+                    .take_while(|chapter| *chapter <= max)
+                    .map(|chapter| {
+                        let style = if chapter == self.chapter_cursor {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else if chapter == self.current_chapter {
+                            Style::default().fg(Color::Yellow)
+                        } else if self.store.read.contains(&(self.db.book, chapter)) {
+                            Style::default().fg(Color::Black).bg(Color::Green)
+                        } else {
+                            Style::default()
+                        };
+                        Span::styled(format!("{:>5}", chapter), style)
+                    })
+                    .collect();
+                Line::from(spans)
+            })
+            .collect();
+
+        Paragraph::new(grid)
+            .block(
+                Block::bordered()
+                    .title(Line::from(self.locale.book(self.db.book)).centered())
+                    .title_bottom(
+                        Line::from(self.hint(Mode::ChapterMenu, &[Action::Confirm, Action::Close]))
+                            .centered(),
+                    )
+                    .style(Style::default().bg(Color::LightBlue).fg(Color::Black)),
+            )
+            .scroll((first_row, 0))
+            .render(display_area, buf);
+
+        //Input
+        Paragraph::new(format!(
+            "{}: {}",
+            self.locale.text("Chapter"),
+            self.chapter_input_string
+        ))
+        .block(Block::new().style(Style::default().fg(Color::Black).bg(Color::Cyan)))
+        .render(input_area, buf);
     }
 }
 
-fn title_block(title: &str, chapter_no: u8) -> Block {
+fn split_status_bar(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area)
+}
+
+fn title_block(title: &str, chapter_no: u8) -> Block<'_> {
     let title = format!("{} {}", title, chapter_no);
 
     Block::bordered()
@@ -192,220 +2298,104 @@ fn title_block(title: &str, chapter_no: u8) -> Block {
                 .right_aligned()
                 .style(Style::default().fg(Color::LightBlue)),
         )
-        .title_bottom(
-            Line::from("<q> Quit | <Up,Down> Scroll | <Left,Right> Chapter | <B> Book")
-                .right_aligned()
-                .style(Style::default().fg(Color::LightBlue)),
-        )
         //.border_set(border::THICK)
         .style(Style::default().fg(Color::Magenta))
 }
 
-fn display_list() -> Line<'static> {
-    Line::from(vec![
-        Span::styled("ge", Style::default().fg(Color::Yellow)),
-        Span::raw("nesis "),
-        Span::styled("ex", Style::default().fg(Color::Yellow)),
-        Span::raw("odus "),
-        Span::styled("le", Style::default().fg(Color::Yellow)),
-        Span::raw("viticus "),
-        Span::styled("nu", Style::default().fg(Color::Yellow)),
-        Span::raw("mbers "),
-        Span::styled("de", Style::default().fg(Color::Yellow)),
-        Span::raw("uteronomy "),
-        Span::styled("jos", Style::default().fg(Color::Yellow)),
-        Span::raw("hua "),
-        Span::styled("judg", Style::default().fg(Color::Yellow)),
-        Span::raw("es "),
-        Span::styled("ru", Style::default().fg(Color::Yellow)),
-        Span::raw("th "),
-        Span::styled("1s", Style::default().fg(Color::Yellow)),
-        Span::raw("amuel "),
-        Span::styled("2s", Style::default().fg(Color::Yellow)),
-        Span::raw("amuel "),
-        Span::styled("1k", Style::default().fg(Color::Yellow)),
-        Span::raw("ings "),
-        Span::styled("2k", Style::default().fg(Color::Yellow)),
-        Span::raw("ings "),
-        Span::styled("1ch", Style::default().fg(Color::Yellow)),
-        Span::raw("ronicles "),
-        Span::styled("2ch", Style::default().fg(Color::Yellow)),
-        Span::raw("ronicles "),
-        Span::styled("ezr", Style::default().fg(Color::Yellow)),
-        Span::raw("a "),
-        Span::styled("ne", Style::default().fg(Color::Yellow)),
-        Span::raw("hemiah "),
-        Span::styled("es", Style::default().fg(Color::Yellow)),
-        Span::raw("ther "),
-        Span::styled("job", Style::default().fg(Color::Yellow)),
-        Span::raw(" "),
-        Span::styled("ps", Style::default().fg(Color::Yellow)),
-        Span::raw("alms "),
-        Span::styled("pr", Style::default().fg(Color::Yellow)),
-        Span::raw("overbs "),
-        Span::styled("ec", Style::default().fg(Color::Yellow)),
-        Span::raw("clesiastes "),
-        Span::styled("s", Style::default().fg(Color::Yellow)),
-        Span::raw("ongOfSolomon "),
-        Span::styled("i", Style::default().fg(Color::Yellow)),
-        Span::raw("saiah "),
-        Span::styled("je", Style::default().fg(Color::Yellow)),
-        Span::raw("remiah "),
-        Span::styled("la", Style::default().fg(Color::Yellow)),
-        Span::raw("mentations "),
-        Span::styled("eze", Style::default().fg(Color::Yellow)),
-        Span::raw("kiel "),
-        Span::styled("da", Style::default().fg(Color::Yellow)),
-        Span::raw("niel "),
-        Span::styled("ho", Style::default().fg(Color::Yellow)),
-        Span::raw("sea "),
-        Span::styled("joe", Style::default().fg(Color::Yellow)),
-        Span::raw("l "),
-        Span::styled("am", Style::default().fg(Color::Yellow)),
-        Span::raw("os "),
-        Span::styled("o", Style::default().fg(Color::Yellow)),
-        Span::raw("badiah "),
-        Span::styled("jon", Style::default().fg(Color::Yellow)),
-        Span::raw("ah "),
-        Span::styled("mi", Style::default().fg(Color::Yellow)),
-        Span::raw("cah "),
-        Span::styled("na", Style::default().fg(Color::Yellow)),
-        Span::raw("hum "),
-        Span::styled("hab", Style::default().fg(Color::Yellow)),
-        Span::raw("akkuk "),
-        Span::styled("zep", Style::default().fg(Color::Yellow)),
-        Span::raw("haniah "),
-        Span::styled("hag", Style::default().fg(Color::Yellow)),
-        Span::raw("gai "),
-        Span::styled("zec", Style::default().fg(Color::Yellow)),
-        Span::raw("hariah "),
-        Span::styled("mal", Style::default().fg(Color::Yellow)),
-        Span::raw("achi "),
-        Span::styled("mat", Style::default().fg(Color::Yellow)),
-        Span::raw("thew "),
-        Span::styled("mar", Style::default().fg(Color::Yellow)),
-        Span::raw("k "),
-        Span::styled("lu", Style::default().fg(Color::Yellow)),
-        Span::raw("ke "),
-        Span::styled("joh", Style::default().fg(Color::Yellow)),
-        Span::raw("n "),
-        Span::styled("ac", Style::default().fg(Color::Yellow)),
-        Span::raw("ts "),
-        Span::styled("ro", Style::default().fg(Color::Yellow)),
-        Span::raw("mans "),
-        Span::styled("1co", Style::default().fg(Color::Yellow)),
-        Span::raw("rinthians "),
-        Span::styled("2co", Style::default().fg(Color::Yellow)),
-        Span::raw("rinthians "),
-        Span::styled("ga", Style::default().fg(Color::Yellow)),
-        Span::raw("latians "),
-        Span::styled("ep", Style::default().fg(Color::Yellow)),
-        Span::raw("hesians "),
-        Span::styled("phili", Style::default().fg(Color::Yellow)),
-        Span::raw("ppians "),
-        Span::styled("c", Style::default().fg(Color::Yellow)),
-        Span::raw("olossians "),
-        Span::styled("1th", Style::default().fg(Color::Yellow)),
-        Span::raw("essalonians "),
-        Span::styled("2th", Style::default().fg(Color::Yellow)),
-        Span::raw("essalonians "),
-        Span::styled("1ti", Style::default().fg(Color::Yellow)),
-        Span::raw("mothy "),
-        Span::styled("2ti", Style::default().fg(Color::Yellow)),
-        Span::raw("mothy "),
-        Span::styled("t", Style::default().fg(Color::Yellow)),
-        Span::raw("itus "),
-        Span::styled("phile", Style::default().fg(Color::Yellow)),
-        Span::raw("mon "),
-        Span::styled("he", Style::default().fg(Color::Yellow)),
-        Span::raw("brews "),
-        Span::styled("ja", Style::default().fg(Color::Yellow)),
-        Span::raw("mes "),
-        Span::styled("1p", Style::default().fg(Color::Yellow)),
-        Span::raw("eter "),
-        Span::styled("2p", Style::default().fg(Color::Yellow)),
-        Span::raw("eter "),
-        Span::styled("1j", Style::default().fg(Color::Yellow)),
-        Span::raw("ohn "),
-        Span::styled("2j", Style::default().fg(Color::Yellow)),
-        Span::raw("ohn "),
-        Span::styled("3", Style::default().fg(Color::Yellow)),
-        Span::raw("John "),
-        Span::styled("jude", Style::default().fg(Color::Yellow)),
-        Span::raw(" "),
-        Span::styled("re", Style::default().fg(Color::Yellow)),
-        Span::raw("velation "),
-    ])
+//Book names with the start to type for each highlighted, "ge" for Genesis
+fn display_list(books: &[BibleBook], locale: &Locale) -> Line<'static> {
+    let spans: Vec<Span> = books
+        .iter()
+        .zip(BibleBook::menu_prefixes(books, locale))
+        .flat_map(|(book, prefix)| {
+            //"SongOfSolomon", the name without spaces and dots as it's typed
+            let name: String = locale
+                .book(*book)
+                .split([' ', '.'])
+                .flat_map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .into_iter()
+                        .flat_map(char::to_uppercase)
+                        .chain(chars)
+                })
+                .collect();
+            let rest: String = name.chars().skip(prefix.chars().count()).collect();
+            let rest = format!("{} ", rest);
+
+            [
+                Span::styled(prefix, Style::default().fg(Color::Yellow)),
+                Span::raw(rest),
+            ]
+        })
+        .collect();
+
+    Line::from(spans)
+}
+
+//The book picked as soon as its prefix is typed. A name that another one starts with,
+//Jeremiah's "예레미야" of Lamentations' "예레미야애가", waits for Enter
+fn minimum_prefix_match(books: &[BibleBook], locale: &Locale, prefix: &str) -> Option<BibleBook> {
+    let prefixes = BibleBook::menu_prefixes(books, locale);
+    if prefixes
+        .iter()
+        .filter(|book_prefix| book_prefix.starts_with(prefix))
+        .count()
+        > 1
+    {
+        return None;
+    }
+
+    typed_book(books, locale, prefix)
+}
+
+//The book whose prefix is typed, picked with Enter
+fn typed_book(books: &[BibleBook], locale: &Locale, prefix: &str) -> Option<BibleBook> {
+    books
+        .iter()
+        .zip(BibleBook::menu_prefixes(books, locale))
+        .find(|(_, book_prefix)| book_prefix == prefix)
+        .map(|(book, _)| *book)
 }
 
-fn minimum_prefix_match(prefix: &str) -> Option<BibleBook> {
-    match prefix {
-        "ge" => Some(BibleBook::Genesis),
-        "ex" => Some(BibleBook::Exodus),
-        "le" => Some(BibleBook::Leviticus),
-        "nu" => Some(BibleBook::Numbers),
-        "de" => Some(BibleBook::Deuteronomy),
-        "jos" => Some(BibleBook::Joshua),
-        "judg" => Some(BibleBook::Judges),
-        "ru" => Some(BibleBook::Ruth),
-        "1s" => Some(BibleBook::FirstSamuel),
-        "2s" => Some(BibleBook::SecondSamuel),
-        "1k" => Some(BibleBook::FirstKings),
-        "2k" => Some(BibleBook::SecondKings),
-        "1ch" => Some(BibleBook::FirstChronicles),
-        "2ch" => Some(BibleBook::SecondChronicles),
-        "ezr" => Some(BibleBook::Ezra),
-        "ne" => Some(BibleBook::Nehemiah),
-        "es" => Some(BibleBook::Esther),
-        "job" => Some(BibleBook::Job),
-        "ps" => Some(BibleBook::Psalms),
-        "pr" => Some(BibleBook::Proverbs),
-        "ec" => Some(BibleBook::Ecclesiastes),
-        "s" => Some(BibleBook::SongOfSolomon),
-        "i" => Some(BibleBook::Isaiah),
-        "je" => Some(BibleBook::Jeremiah),
-        "la" => Some(BibleBook::Lamentations),
-        "eze" => Some(BibleBook::Ezekiel),
-        "da" => Some(BibleBook::Daniel),
-        "ho" => Some(BibleBook::Hosea),
-        "joe" => Some(BibleBook::Joel),
-        "am" => Some(BibleBook::Amos),
-        "o" => Some(BibleBook::Obadiah),
-        "jon" => Some(BibleBook::Jonah),
-        "mi" => Some(BibleBook::Micah),
-        "na" => Some(BibleBook::Nahum),
-        "hab" => Some(BibleBook::Habakkuk),
-        "zep" => Some(BibleBook::Zephaniah),
-        "hag" => Some(BibleBook::Haggai),
-        "zec" => Some(BibleBook::Zechariah),
-        "mal" => Some(BibleBook::Malachi),
-        "mat" => Some(BibleBook::Matthew),
-        "mar" => Some(BibleBook::Mark),
-        "lu" => Some(BibleBook::Luke),
-        "joh" => Some(BibleBook::John),
-        "ac" => Some(BibleBook::Acts),
-        "ro" => Some(BibleBook::Romans),
-        "1co" => Some(BibleBook::FirstCorinthians),
-        "2co" => Some(BibleBook::SecondCorinthians),
-        "ga" => Some(BibleBook::Galatians),
-        "ep" => Some(BibleBook::Ephesians),
-        "phili" => Some(BibleBook::Philippians),
-        "c" => Some(BibleBook::Colossians),
-        "1th" => Some(BibleBook::FirstThessalonians),
-        "2th" => Some(BibleBook::SecondThessalonians),
-        "1ti" => Some(BibleBook::FirstTimothy),
-        "2ti" => Some(BibleBook::SecondTimothy),
-        "t" => Some(BibleBook::Titus),
-        "phile" => Some(BibleBook::Philemon),
-        "he" => Some(BibleBook::Hebrews),
-        "ja" => Some(BibleBook::James),
-        "1p" => Some(BibleBook::FirstPeter),
-        "2p" => Some(BibleBook::SecondPeter),
-        "1j" => Some(BibleBook::FirstJohn),
-        "2j" => Some(BibleBook::SecondJohn),
-        "3" => Some(BibleBook::ThirdJohn),
-        "jude" => Some(BibleBook::Jude),
-        "re" => Some(BibleBook::Revelation),
-        _ => None,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verse::db::{Canon, Order};
+
+    //Typing the highlighted start of a book, and Enter if it waits for it, opens that
+    //book and no other on the way, in every language and canon. Orders only move the
+    //same books around
+    #[test]
+    fn menu_prefixes_reachable() {
+        for locale in Locale::all() {
+            for canon in Canon::ALL {
+                let arrangement = Order::Canon.arrange(canon);
+                let books = arrangement.books();
+                let prefixes = BibleBook::menu_prefixes(books, locale);
+
+                for (book, prefix) in books.iter().zip(&prefixes) {
+                    let mut typed = String::new();
+                    let mut picked = None;
+                    for c in prefix.chars() {
+                        typed.push(c);
+                        picked = minimum_prefix_match(books, locale, &typed);
+                        if picked.is_some() {
+                            break;
+                        }
+                    }
+                    let picked = picked.or_else(|| typed_book(books, locale, &typed));
+
+                    assert!(
+                        picked == Some(*book),
+                        "{} typed as {} in {}",
+                        book.as_str(),
+                        typed,
+                        locale.code
+                    );
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,395 @@
+use crate::verse::db::BibleBook;
+
+/*
+Verse numbering traditions. verse keeps every translation in the English (KJV)
+numbering, so highlights, notes and cross references line up whatever translation
+they were made in. Translations numbered otherwise are mapped to it when imported
+and shown in their own numbering.
+
+A scheme is a stack of layers over the English numbering, each a list of shifts
+moving a block of verses elsewhere:
+
+    Hebrew      Masoretic chapter divisions, psalm titles counted as verses
+    Septuagint  as the Hebrew, with the Greek psalm numbering (9 and 10 are one)
+    Vulgate     English chapter divisions, psalm titles and the Greek psalm numbering
+
+Verses split differently are mapped to where they start, verses with no counterpart
+like psalm titles to the nearest verse.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Versification {
+    #[default]
+    English,
+    Hebrew,
+    Septuagint,
+    Vulgate,
+}
+
+//Chapters first to last, verses first to last in them, moved to start at the chapter and verse
+struct Shift {
+    book: BibleBook,
+    chapters: (u8, u8),
+    verses: (u8, u8),
+    to: (u8, u8),
+}
+
+//A block of verses in one chapter
+const fn shift(book: BibleBook, chapter: u8, first: u8, last: u8, to: (u8, u8)) -> Shift {
+    Shift {
+        book,
+        chapters: (chapter, chapter),
+        verses: (first, last),
+        to,
+    }
+}
+
+//Whole psalms whose verses the title pushes down by one or two
+const fn titled(first: u8, last: u8, lines: u8) -> Shift {
+    Shift {
+        book: BibleBook::Psalms,
+        chapters: (first, last),
+        verses: (1, u8::MAX),
+        to: (first, 1 + lines),
+    }
+}
+
+//Whole psalms renumbered
+const fn psalms(first: u8, last: u8, to: u8) -> Shift {
+    Shift {
+        book: BibleBook::Psalms,
+        chapters: (first, last),
+        verses: (1, u8::MAX),
+        to: (to, 1),
+    }
+}
+
+impl Shift {
+    //Where the verse goes, if the shift moves it
+    fn apply(&self, book: BibleBook, chapter: u8, verse: u8) -> Option<(u8, u8)> {
+        if book != self.book
+            || !(self.chapters.0..=self.chapters.1).contains(&chapter)
+            || !(self.verses.0..=self.verses.1).contains(&verse)
+        {
+            return None;
+        }
+        Some((
+            self.to.0 + (chapter - self.chapters.0),
+            clamp(self.to.1 as u16 + (verse - self.verses.0) as u16),
+        ))
+    }
+
+    //Where the verse came from, if the shift moved it there
+    fn undo(&self, book: BibleBook, chapter: u8, verse: u8) -> Option<(u8, u8)> {
+        let chapters = self.chapters.1 - self.chapters.0;
+        let verses = (self.verses.1 - self.verses.0) as u16;
+        if book != self.book
+            || chapter < self.to.0
+            || chapter - self.to.0 > chapters
+            || verse < self.to.1
+            || (verse - self.to.1) as u16 > verses
+        {
+            return None;
+        }
+        Some((
+            self.chapters.0 + (chapter - self.to.0),
+            self.verses.0 + (verse - self.to.1),
+        ))
+    }
+
+    //Last chapter the shift moves verses to
+    fn last_chapter(&self) -> u8 {
+        self.to.0 + (self.chapters.1 - self.chapters.0)
+    }
+}
+
+fn clamp(verse: u16) -> u8 {
+    verse.min(u8::MAX as u16) as u8
+}
+
+//English chapter and verse divisions to Masoretic ones
+const HEBREW_CHAPTERS: &[Shift] = {
+    use BibleBook::*;
+    &[
+        shift(Genesis, 31, 55, 55, (32, 1)),
+        shift(Genesis, 32, 1, 32, (32, 2)),
+        shift(Exodus, 8, 1, 4, (7, 26)),
+        shift(Exodus, 8, 5, 32, (8, 1)),
+        shift(Exodus, 22, 1, 1, (21, 37)),
+        shift(Exodus, 22, 2, 31, (22, 1)),
+        shift(Leviticus, 6, 1, 7, (5, 20)),
+        shift(Leviticus, 6, 8, 30, (6, 1)),
+        shift(Numbers, 16, 36, 50, (17, 1)),
+        shift(Numbers, 17, 1, 13, (17, 16)),
+        shift(Numbers, 29, 40, 40, (30, 1)),
+        shift(Numbers, 30, 1, 16, (30, 2)),
+        shift(Deuteronomy, 12, 32, 32, (13, 1)),
+        shift(Deuteronomy, 13, 1, 18, (13, 2)),
+        shift(Deuteronomy, 22, 30, 30, (23, 1)),
+        shift(Deuteronomy, 23, 1, 25, (23, 2)),
+        shift(Deuteronomy, 29, 1, 1, (28, 69)),
+        shift(Deuteronomy, 29, 2, 29, (29, 1)),
+        shift(FirstSamuel, 20, 42, 42, (21, 1)),
+        shift(FirstSamuel, 21, 1, 15, (21, 2)),
+        shift(FirstSamuel, 23, 29, 29, (24, 1)),
+        shift(FirstSamuel, 24, 1, 22, (24, 2)),
+        shift(SecondSamuel, 18, 33, 33, (19, 1)),
+        shift(SecondSamuel, 19, 1, 43, (19, 2)),
+        shift(FirstKings, 4, 21, 34, (5, 1)),
+        shift(FirstKings, 5, 1, 18, (5, 15)),
+        shift(FirstKings, 22, 43, 43, (22, 44)),
+        shift(FirstKings, 22, 44, 53, (22, 45)),
+        shift(SecondKings, 11, 21, 21, (12, 1)),
+        shift(SecondKings, 12, 1, 21, (12, 2)),
+        shift(FirstChronicles, 6, 1, 15, (5, 27)),
+        shift(FirstChronicles, 6, 16, 81, (6, 1)),
+        shift(FirstChronicles, 12, 4, 4, (12, 5)),
+        shift(FirstChronicles, 12, 5, 40, (12, 6)),
+        shift(SecondChronicles, 2, 1, 1, (1, 18)),
+        shift(SecondChronicles, 2, 2, 18, (2, 1)),
+        shift(SecondChronicles, 14, 1, 1, (13, 23)),
+        shift(SecondChronicles, 14, 2, 15, (14, 1)),
+        shift(Nehemiah, 4, 1, 6, (3, 33)),
+        shift(Nehemiah, 4, 7, 23, (4, 1)),
+        shift(Nehemiah, 9, 38, 38, (10, 1)),
+        shift(Nehemiah, 10, 1, 39, (10, 2)),
+        shift(Job, 41, 1, 8, (40, 25)),
+        shift(Job, 41, 9, 34, (41, 1)),
+        shift(Ecclesiastes, 5, 1, 1, (4, 17)),
+        shift(Ecclesiastes, 5, 2, 20, (5, 1)),
+        shift(SongOfSolomon, 6, 13, 13, (7, 1)),
+        shift(SongOfSolomon, 7, 1, 13, (7, 2)),
+        shift(Isaiah, 9, 1, 1, (8, 23)),
+        shift(Isaiah, 9, 2, 21, (9, 1)),
+        shift(Isaiah, 64, 2, 12, (64, 1)),
+        shift(Jeremiah, 9, 1, 1, (8, 23)),
+        shift(Jeremiah, 9, 2, 26, (9, 1)),
+        shift(Ezekiel, 20, 45, 49, (21, 1)),
+        shift(Ezekiel, 21, 1, 32, (21, 6)),
+        shift(Daniel, 4, 1, 3, (3, 31)),
+        shift(Daniel, 4, 4, 37, (4, 1)),
+        shift(Daniel, 5, 31, 31, (6, 1)),
+        shift(Daniel, 6, 1, 28, (6, 2)),
+        shift(Hosea, 1, 10, 11, (2, 1)),
+        shift(Hosea, 2, 1, 23, (2, 3)),
+        shift(Hosea, 11, 12, 12, (12, 1)),
+        shift(Hosea, 12, 1, 14, (12, 2)),
+        shift(Hosea, 13, 16, 16, (14, 1)),
+        shift(Hosea, 14, 1, 9, (14, 2)),
+        shift(Joel, 2, 28, 32, (3, 1)),
+        shift(Joel, 3, 1, 21, (4, 1)),
+        shift(Jonah, 1, 17, 17, (2, 1)),
+        shift(Jonah, 2, 1, 10, (2, 2)),
+        shift(Micah, 5, 1, 1, (4, 14)),
+        shift(Micah, 5, 2, 15, (5, 1)),
+        shift(Nahum, 1, 15, 15, (2, 1)),
+        shift(Nahum, 2, 1, 13, (2, 2)),
+        shift(Zechariah, 1, 18, 21, (2, 1)),
+        shift(Zechariah, 2, 1, 13, (2, 5)),
+        shift(Malachi, 4, 1, 6, (3, 19)),
+    ]
+};
+
+//Psalm titles counted as verses, one or two of them
+const PSALM_TITLES: &[Shift] = &[
+    titled(3, 9, 1),
+    titled(12, 12, 1),
+    //The last two verses of 13 are one in Hebrew
+    shift(BibleBook::Psalms, 13, 1, 5, (13, 2)),
+    shift(BibleBook::Psalms, 13, 6, 6, (13, 6)),
+    titled(18, 22, 1),
+    titled(30, 31, 1),
+    titled(34, 34, 1),
+    titled(36, 36, 1),
+    titled(38, 42, 1),
+    titled(44, 49, 1),
+    titled(51, 52, 2),
+    titled(53, 53, 1),
+    titled(54, 54, 2),
+    titled(55, 59, 1),
+    titled(60, 60, 2),
+    titled(61, 65, 1),
+    titled(67, 70, 1),
+    titled(75, 77, 1),
+    titled(80, 81, 1),
+    titled(83, 85, 1),
+    titled(88, 89, 1),
+    titled(92, 92, 1),
+    titled(102, 102, 1),
+    titled(108, 108, 1),
+    titled(140, 140, 1),
+    titled(142, 142, 1),
+];
+
+//Hebrew psalm numbers to Greek ones, psalm verses numbered as in Hebrew
+const GREEK_PSALMS: &[Shift] = &[
+    shift(BibleBook::Psalms, 10, 1, 18, (9, 22)),
+    psalms(11, 113, 10),
+    shift(BibleBook::Psalms, 114, 1, 8, (113, 1)),
+    shift(BibleBook::Psalms, 115, 1, 18, (113, 9)),
+    shift(BibleBook::Psalms, 116, 1, 9, (114, 1)),
+    shift(BibleBook::Psalms, 116, 10, 19, (115, 1)),
+    psalms(117, 146, 116),
+    shift(BibleBook::Psalms, 147, 1, 11, (146, 1)),
+    shift(BibleBook::Psalms, 147, 12, 20, (147, 1)),
+];
+
+impl Versification {
+    pub const ALL: [Versification; 4] = [
+        Versification::English,
+        Versification::Hebrew,
+        Versification::Septuagint,
+        Versification::Vulgate,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Versification::English => "English",
+            Versification::Hebrew => "Hebrew",
+            Versification::Septuagint => "Septuagint",
+            Versification::Vulgate => "Vulgate",
+        }
+    }
+
+    //Name or abbreviation like "lxx", any case
+    pub fn parse(name: &str) -> Option<Versification> {
+        match name.trim().to_lowercase().as_str() {
+            "english" | "kjv" => Some(Versification::English),
+            "hebrew" | "mt" => Some(Versification::Hebrew),
+            "septuagint" | "lxx" => Some(Versification::Septuagint),
+            "vulgate" | "vulg" => Some(Versification::Vulgate),
+            _ => None,
+        }
+    }
+
+    fn layers(&self) -> &'static [&'static [Shift]] {
+        match self {
+            Versification::English => &[],
+            Versification::Hebrew => &[HEBREW_CHAPTERS, PSALM_TITLES],
+            Versification::Septuagint => &[HEBREW_CHAPTERS, PSALM_TITLES, GREEK_PSALMS],
+            Versification::Vulgate => &[PSALM_TITLES, GREEK_PSALMS],
+        }
+    }
+
+    //The English verse in this numbering
+    pub fn from_english(&self, book: BibleBook, chapter: u8, verse: u8) -> (BibleBook, u8, u8) {
+        let (chapter, verse) = self.layers().iter().fold((chapter, verse), |at, layer| {
+            layer
+                .iter()
+                .find_map(|shift| shift.apply(book, at.0, at.1))
+                .unwrap_or(at)
+        });
+        (book, chapter, verse)
+    }
+
+    //The verse of this numbering in the English one
+    pub fn to_english(&self, book: BibleBook, chapter: u8, verse: u8) -> (BibleBook, u8, u8) {
+        let (chapter, verse) = self
+            .layers()
+            .iter()
+            .rev()
+            .fold((chapter, verse), |at, layer| {
+                layer
+                    .iter()
+                    .find_map(|shift| shift.undo(book, at.0, at.1))
+                    .unwrap_or(at)
+            });
+        (book, chapter, verse)
+    }
+
+    //The verse of this numbering in another
+    pub fn map(
+        &self,
+        to: Versification,
+        book: BibleBook,
+        chapter: u8,
+        verse: u8,
+    ) -> (BibleBook, u8, u8) {
+        let (book, chapter, verse) = self.to_english(book, chapter, verse);
+        to.from_english(book, chapter, verse)
+    }
+
+    //Chapters the book has in this numbering, Joel has 4 in Hebrew
+    pub fn max_chapter_count(&self, book: BibleBook) -> u8 {
+        self.layers()
+            .iter()
+            .flat_map(|layer| layer.iter())
+            .filter(|shift| shift.book == book)
+            .map(Shift::last_chapter)
+            .fold(book.max_chapter_count(), u8::max)
+    }
+
+    //"Malachi 3:19", the English verse as this numbering has it
+    pub fn reference(&self, book: BibleBook, chapter: u8, verse: u8) -> String {
+        let (book, chapter, verse) = self.from_english(book, chapter, verse);
+        format!("{} {}:{}", book.as_str(), chapter, verse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PSALMS: [u8; 6] = [9, 10, 114, 115, 116, 147];
+
+    //Chapter and verse of the English verse in the numbering
+    fn from_english(versification: Versification, chapter: u8, verse: u8) -> (u8, u8) {
+        let (_, chapter, verse) = versification.from_english(BibleBook::Psalms, chapter, verse);
+        (chapter, verse)
+    }
+
+    #[test]
+    fn psalms_round_trip() {
+        for versification in Versification::ALL {
+            for chapter in PSALMS {
+                for verse in 1..=BibleBook::Psalms.kjv_verse_count(chapter) {
+                    let (to_chapter, to_verse) = from_english(versification, chapter, verse);
+                    assert_eq!(
+                        versification.to_english(BibleBook::Psalms, to_chapter, to_verse),
+                        (BibleBook::Psalms, chapter, verse),
+                        "{} numbering",
+                        versification.as_str()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn greek_psalms() {
+        for versification in [Versification::Septuagint, Versification::Vulgate] {
+            //9 and 10 are one psalm, the title of 9 counts as its first verse
+            assert_eq!(from_english(versification, 9, 1), (9, 2));
+            assert_eq!(from_english(versification, 10, 1), (9, 22));
+            assert_eq!(from_english(versification, 10, 18), (9, 39));
+            //114 and 115 are one, 116 is two
+            assert_eq!(from_english(versification, 114, 1), (113, 1));
+            assert_eq!(from_english(versification, 115, 1), (113, 9));
+            assert_eq!(from_english(versification, 116, 9), (114, 9));
+            assert_eq!(from_english(versification, 116, 10), (115, 1));
+            //147 is two
+            assert_eq!(from_english(versification, 147, 11), (146, 11));
+            assert_eq!(from_english(versification, 147, 12), (147, 1));
+        }
+    }
+
+    #[test]
+    fn hebrew_psalms() {
+        assert_eq!(from_english(Versification::Hebrew, 9, 1), (9, 2));
+        assert_eq!(from_english(Versification::Hebrew, 10, 1), (10, 1));
+        assert_eq!(from_english(Versification::Hebrew, 147, 12), (147, 12));
+        assert_eq!(from_english(Versification::English, 10, 1), (10, 1));
+    }
+
+    #[test]
+    fn hebrew_chapters() {
+        assert_eq!(
+            Versification::Hebrew.reference(BibleBook::Malachi, 4, 1),
+            "Malachi 3:19"
+        );
+        assert_eq!(Versification::Hebrew.max_chapter_count(BibleBook::Joel), 4);
+        assert_eq!(
+            Versification::Hebrew.map(Versification::English, BibleBook::Malachi, 3, 24),
+            (BibleBook::Malachi, 4, 6)
+        );
+    }
+}
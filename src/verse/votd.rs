@@ -0,0 +1,246 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::verse::{
+    date::Date,
+    db::{BibleBook, Database, Error, Result, Verse},
+};
+
+//Verses the verse of the day is picked from
+const CURATED: [(BibleBook, u8, u8); 60] = [
+    (BibleBook::Genesis, 1, 1),
+    (BibleBook::Genesis, 28, 15),
+    (BibleBook::Exodus, 14, 14),
+    (BibleBook::Numbers, 6, 24),
+    (BibleBook::Deuteronomy, 6, 5),
+    (BibleBook::Deuteronomy, 31, 6),
+    (BibleBook::Joshua, 1, 9),
+    (BibleBook::Joshua, 24, 15),
+    (BibleBook::FirstSamuel, 16, 7),
+    (BibleBook::FirstChronicles, 16, 34),
+    (BibleBook::Nehemiah, 8, 10),
+    (BibleBook::Job, 19, 25),
+    (BibleBook::Psalms, 1, 1),
+    (BibleBook::Psalms, 19, 14),
+    (BibleBook::Psalms, 23, 1),
+    (BibleBook::Psalms, 27, 1),
+    (BibleBook::Psalms, 37, 4),
+    (BibleBook::Psalms, 46, 1),
+    (BibleBook::Psalms, 46, 10),
+    (BibleBook::Psalms, 51, 10),
+    (BibleBook::Psalms, 90, 12),
+    (BibleBook::Psalms, 118, 24),
+    (BibleBook::Psalms, 119, 105),
+    (BibleBook::Psalms, 121, 1),
+    (BibleBook::Psalms, 139, 14),
+    (BibleBook::Proverbs, 3, 5),
+    (BibleBook::Proverbs, 16, 3),
+    (BibleBook::Proverbs, 18, 10),
+    (BibleBook::Ecclesiastes, 3, 1),
+    (BibleBook::Isaiah, 26, 3),
+    (BibleBook::Isaiah, 40, 31),
+    (BibleBook::Isaiah, 41, 10),
+    (BibleBook::Isaiah, 53, 5),
+    (BibleBook::Jeremiah, 29, 11),
+    (BibleBook::Lamentations, 3, 22),
+    (BibleBook::Micah, 6, 8),
+    (BibleBook::Habakkuk, 3, 18),
+    (BibleBook::Zephaniah, 3, 17),
+    (BibleBook::Matthew, 5, 9),
+    (BibleBook::Matthew, 6, 33),
+    (BibleBook::Matthew, 11, 28),
+    (BibleBook::Mark, 10, 27),
+    (BibleBook::Luke, 1, 37),
+    (BibleBook::John, 1, 1),
+    (BibleBook::John, 3, 16),
+    (BibleBook::John, 14, 6),
+    (BibleBook::John, 14, 27),
+    (BibleBook::Romans, 5, 8),
+    (BibleBook::Romans, 8, 28),
+    (BibleBook::Romans, 12, 2),
+    (BibleBook::FirstCorinthians, 13, 4),
+    (BibleBook::SecondCorinthians, 5, 17),
+    (BibleBook::Galatians, 5, 22),
+    (BibleBook::Ephesians, 2, 8),
+    (BibleBook::Philippians, 4, 6),
+    (BibleBook::Philippians, 4, 13),
+    (BibleBook::Hebrews, 11, 1),
+    (BibleBook::James, 1, 5),
+    (BibleBook::FirstJohn, 4, 19),
+    (BibleBook::Revelation, 21, 4),
+];
+
+//splitmix64, small and the same everywhere so every install picks the same verses
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    //Seeded from the clock
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Rng::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    //Uniform in 0..n, n has to be above 0
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+//Verse of the day from the curated list, the same for everyone on the same date
+pub fn verse_of_the_day(db: &Database, date: Date) -> Result<Verse> {
+    let (book, chapter, verse) =
+        CURATED[Rng::new(date.ordinal() as u64).below(CURATED.len() as u64) as usize];
+
+    //Translations missing the verse fall back to an empty text rather than failing
+    let text = db.get_verse(book, chapter, verse)?.unwrap_or_default();
+    Ok(Verse {
+        book,
+        chapter,
+        verse,
+        text,
+    })
+}
+
+//Verse of the day picked from the whole Bible instead of the curated list
+pub fn random_of_the_day(db: &Database, date: Date) -> Result<Verse> {
+    random_verse(
+        db,
+        BibleBook::Genesis,
+        BibleBook::LAST,
+        &mut Rng::new(date.ordinal() as u64),
+    )
+}
+
+//Any verse of the books from first to last
+pub fn random_verse(
+    db: &Database,
+    first: BibleBook,
+    last: BibleBook,
+    rng: &mut Rng,
+) -> Result<Verse> {
+    let count = db.count_verses(first, last)?;
+    if count == 0 {
+        return Err(Error::NoSuchVerse);
+    }
+
+    db.nth_verse(first, last, rng.below(count as u64) as u32)
+}
+
+//Any verse of the books, which needn't follow each other. Every verse is as likely, so
+//longer books come up more often
+pub fn random_verse_in(db: &Database, books: &[BibleBook], rng: &mut Rng) -> Result<Verse> {
+    let counts = books
+        .iter()
+        .map(|book| db.count_verses(*book, *book))
+        .collect::<Result<Vec<u32>>>()?;
+    let total: u32 = counts.iter().sum();
+    if total == 0 {
+        return Err(Error::NoSuchVerse);
+    }
+
+    let mut n = rng.below(total as u64) as u32;
+    for (book, count) in books.iter().zip(counts) {
+        if n < count {
+            return db.nth_verse(*book, *book, n);
+        }
+        n -= count;
+    }
+    Err(Error::NoSuchVerse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verse::{index::BibleIndex, source::Memory};
+
+    //Three verses in every book
+    fn db() -> Database {
+        let verses = BibleBook::all().flat_map(|book| {
+            (1..=3).map(move |verse| (book, 1, verse, format!("{} 1:{}", book.as_str(), verse)))
+        });
+        Database::new(Memory::new("test", vec![BibleIndex::build("TEST", verses)])).unwrap()
+    }
+
+    //splitmix64's published first outputs for the seed 0
+    #[test]
+    fn rng() {
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+        assert_eq!(rng.next_u64(), 0x6E789E6AA1B965F4);
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+    }
+
+    #[test]
+    fn same_verse_all_day() {
+        let db = db();
+        let date = Date::from_ymd(2026, 10, 19).unwrap();
+
+        let verse = verse_of_the_day(&db, date).unwrap();
+        assert!(CURATED.contains(&(verse.book, verse.chapter, verse.verse)));
+        assert_eq!(verse_of_the_day(&db, date).unwrap().book, verse.book);
+
+        let picks: Vec<_> = (0..30)
+            .map(|day| verse_of_the_day(&db, date.add_days(day)).unwrap())
+            .map(|verse| (verse.book, verse.chapter, verse.verse))
+            .collect();
+        assert!(picks.iter().any(|pick| *pick != picks[0]));
+
+        let random = random_of_the_day(&db, date).unwrap();
+        let again = random_of_the_day(&db, date).unwrap();
+        assert_eq!(
+            (random.book, random.chapter, random.verse),
+            (again.book, again.chapter, again.verse)
+        );
+        assert_eq!(
+            random.text,
+            format!("{} 1:{}", random.book.as_str(), random.verse)
+        );
+    }
+
+    //A curated verse the translation lacks comes with no text
+    #[test]
+    fn missing_verse() {
+        let db = db();
+        let date = (0..)
+            .map(|day| Date::from_ymd(2026, 1, 1).unwrap().add_days(day))
+            .find(|date| verse_of_the_day(&db, *date).unwrap().verse > 3)
+            .unwrap();
+        assert_eq!(verse_of_the_day(&db, date).unwrap().text, "");
+    }
+
+    #[test]
+    fn random_verses_from_the_books() {
+        let db = db();
+        let mut rng = Rng::new(1);
+        let books = [BibleBook::Genesis, BibleBook::Tobit];
+
+        let mut seen = vec![];
+        for _ in 0..100 {
+            let verse = random_verse_in(&db, &books, &mut rng).unwrap();
+            assert!(books.contains(&verse.book));
+            assert!((1..=3).contains(&verse.verse));
+            seen.push(verse.book);
+        }
+        assert!(seen.contains(&BibleBook::Genesis) && seen.contains(&BibleBook::Tobit));
+
+        let verse = random_verse(&db, BibleBook::John, BibleBook::John, &mut rng).unwrap();
+        assert_eq!(verse.book, BibleBook::John);
+        assert!(random_verse_in(&db, &[], &mut rng).is_err());
+    }
+}
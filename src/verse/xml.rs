@@ -0,0 +1,218 @@
+/*
+Just enough of an XML pull parser for importing Bibles: elements, attributes,
+text with the predefined and numeric entities, CDATA. Comments, processing
+instructions and the doctype are skipped, namespaces are left in the names.
+*/
+
+pub enum Event<'a> {
+    //empty is set for <tag/>, no End follows it
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, String)>,
+        empty: bool,
+    },
+    End(&'a str),
+    Text(String),
+}
+
+impl Event<'_> {
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        match self {
+            Event::Start { attributes, .. } => attributes
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+}
+
+pub struct Reader<'a> {
+    xml: &'a str,
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(xml: &'a str) -> Self {
+        Reader {
+            xml: xml.strip_prefix('\u{feff}').unwrap_or(xml),
+            position: 0,
+        }
+    }
+
+    //Line of the current position, for error messages
+    pub fn line(&self) -> usize {
+        self.xml[..self.position].matches('\n').count() + 1
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line(), message)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.xml[self.position..]
+    }
+
+    //Moves past the terminator, error if it never comes
+    fn skip_past(&mut self, terminator: &str) -> Result<&'a str, String> {
+        let rest = self.rest();
+        let end = rest
+            .find(terminator)
+            .ok_or_else(|| self.error(&format!("missing {}", terminator)))?;
+        self.position += end + terminator.len();
+        Ok(&rest[..end])
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event<'a>>, String> {
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Ok(None);
+            }
+
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.position += end;
+                return Ok(Some(Event::Text(
+                    unescape(&rest[..end]).map_err(|err| self.error(&err))?,
+                )));
+            }
+
+            if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.position += "<![CDATA[".len();
+                return Ok(Some(Event::Text(self.skip_past("]]>")?.to_string())));
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!") {
+                self.skip_doctype()?;
+            } else if rest.starts_with("</") {
+                self.position += "</".len();
+                return Ok(Some(Event::End(self.skip_past(">")?.trim())));
+            } else {
+                return self.start_tag().map(Some);
+            }
+        }
+    }
+
+    //<!DOCTYPE ...> with a possible [internal subset]
+    fn skip_doctype(&mut self) -> Result<(), String> {
+        let mut brackets = 0;
+        for (i, char) in self.rest().char_indices() {
+            match char {
+                '[' => brackets += 1,
+                ']' => brackets -= 1,
+                '>' if brackets == 0 => {
+                    self.position += i + 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        Err(self.error("unterminated doctype"))
+    }
+
+    fn start_tag(&mut self) -> Result<Event<'a>, String> {
+        let start = self.position;
+        //Quoted attribute values may contain >
+        let mut quote = None;
+        let end = self.rest().char_indices().find_map(|(i, char)| {
+            match (quote, char) {
+                (None, '"' | '\'') => quote = Some(char),
+                (Some(open), _) if open == char => quote = None,
+                (None, '>') => return Some(i),
+                _ => {}
+            }
+            None
+        });
+        let end = end.ok_or_else(|| self.error("unterminated tag"))?;
+        self.position += end + 1;
+
+        let tag = &self.xml[start + 1..start + end];
+        let (tag, empty) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let name_end = tag
+            .find(|char: char| char.is_whitespace())
+            .unwrap_or(tag.len());
+        let name = &tag[..name_end];
+        if name.is_empty() {
+            return Err(self.error("tag without a name"));
+        }
+
+        let mut attributes = vec![];
+        let mut rest = tag[name_end..].trim_start();
+        while !rest.is_empty() {
+            let (key, value) = rest
+                .split_once('=')
+                .ok_or_else(|| self.error(&format!("attribute without a value in <{}>", name)))?;
+            let value = value.trim_start();
+            let quote = value
+                .chars()
+                .next()
+                .filter(|char| *char == '"' || *char == '\'')
+                .ok_or_else(|| self.error(&format!("unquoted attribute in <{}>", name)))?;
+            let value_end = value[1..]
+                .find(quote)
+                .ok_or_else(|| self.error(&format!("unterminated attribute in <{}>", name)))?;
+
+            attributes.push((
+                key.trim(),
+                unescape(&value[1..value_end + 1]).map_err(|err| self.error(&err))?,
+            ));
+            rest = value[value_end + 2..].trim_start();
+        }
+
+        Ok(Event::Start {
+            name,
+            attributes,
+            empty,
+        })
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Event<'a>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+//Replaces &amp; and friends, &#65; and &#x41;
+fn unescape(text: &str) -> Result<String, String> {
+    if !text.contains('&') {
+        return Ok(text.to_string());
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| format!("unterminated entity in {:?}", text))?;
+        let entity = &rest[start + 1..start + end];
+
+        let char = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        result.push(char.ok_or_else(|| format!("unknown entity &{};", entity))?);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}